@@ -0,0 +1,670 @@
+//! Subcommands, each returning the JSON value printed by main
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::{
+    token,
+    token_2022::spl_token_2022::{
+        self,
+        extension::{transfer_fee::TransferFeeConfig, StateWithExtensions},
+        state::{Account as TokenAccount, Mint},
+    },
+};
+use anyhow::{anyhow, bail, Result};
+use ftrx_swap_sdk::{
+    constants::{
+        CURVE_CONCENTRATED, CURVE_STABLE, CURVE_WEIGHTED, FEE_MULTIPLIER, MINIMUM_LIQUIDITY, PAUSE_DEPOSIT,
+        PAUSE_SWAP, PAUSE_WITHDRAW, WEIGHT_MULTIPLIER,
+    },
+    deserialize_amm, deserialize_pool, instructions, pda,
+    quote::{self, DepositQuote, WithdrawQuote},
+    PoolKeys, SimpleAmm, SimplePool, UserKeys,
+};
+use ftrx_swap::{math::SwapAmounts, stable_math, token_extensions};
+use serde_json::{json, Value};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use spl_associated_token_account::{get_associated_token_address_with_program_id, instruction::create_associated_token_account_idempotent};
+
+use crate::{rpc::RpcClient, Curve, Side};
+
+/// Slippage tolerances are given out of BPS_MULTIPLIER
+const BPS_MULTIPLIER: u128 = 10000;
+
+/// A SimplePool with the balances its quotes are computed from
+struct LoadedPool {
+    address: Pubkey,
+    state: SimplePool,
+    keys: PoolKeys,
+    decimals_a: u8,
+    decimals_b: u8,
+    reserve_a: u64,
+    reserve_b: u64,
+    lp_supply: u64,
+    transfer_fee_a: Option<TransferFeeConfig>,
+    transfer_fee_b: Option<TransferFeeConfig>,
+    epoch: u64,
+}
+
+impl LoadedPool {
+    fn transfer_fee_config(&self, side_a: bool) -> Option<&TransferFeeConfig> {
+        if side_a {
+            self.transfer_fee_a.as_ref()
+        } else {
+            self.transfer_fee_b.as_ref()
+        }
+    }
+
+    /// Fee the mint of a side withholds when amount is sent
+    fn transfer_fee(&self, side_a: bool, amount: u64) -> u64 {
+        self.transfer_fee_config(side_a)
+            .and_then(|config| config.calculate_epoch_fee(self.epoch, amount))
+            .unwrap_or(0)
+    }
+
+    /// Fee the mint of a side withholds when a transfer credits exactly amount
+    fn inverse_transfer_fee(&self, side_a: bool, amount: u64) -> u64 {
+        self.transfer_fee_config(side_a)
+            .and_then(|config| config.calculate_inverse_epoch_fee(self.epoch, amount))
+            .unwrap_or(0)
+    }
+}
+
+/// Decimals and owning token program of a mint
+fn load_mint(rpc: &RpcClient, mint: &Pubkey) -> Result<(u8, u64, Pubkey)> {
+    let account = rpc.get_existing_account(mint)?;
+    let state = StateWithExtensions::<Mint>::unpack(&account.data)?;
+    Ok((state.base.decimals, state.base.supply, account.owner))
+}
+
+/// Transfer fee settings of a mint, None outside of Token-2022
+fn load_transfer_fee(rpc: &RpcClient, mint: &Pubkey) -> Result<Option<TransferFeeConfig>> {
+    let account = rpc.get_existing_account(mint)?;
+    if account.owner != spl_token_2022::id() {
+        return Ok(None);
+    }
+    token_extensions::mint_transfer_fee_config(&account.data).map_err(|error| anyhow!("mint {}: {}", mint, error))
+}
+
+/// Balance of a token account, 0 if it does not exist
+fn token_balance(rpc: &RpcClient, key: &Pubkey) -> Result<u64> {
+    match rpc.get_account(key)? {
+        Some(account) => Ok(StateWithExtensions::<TokenAccount>::unpack(&account.data)?.base.amount),
+        None => Ok(0),
+    }
+}
+
+fn load_amm(rpc: &RpcClient) -> Result<SimpleAmm> {
+    let account = rpc.get_existing_account(&pda::amm().0)?;
+    Ok(deserialize_amm(&account.data)?)
+}
+
+fn load_pool(rpc: &RpcClient, address: Pubkey) -> Result<LoadedPool> {
+    let account = rpc.get_existing_account(&address)?;
+    if account.owner != ftrx_swap_sdk::ID {
+        bail!("{} is not owned by the ftrx_swap program", address);
+    }
+    let state = deserialize_pool(&account.data)?;
+
+    let (decimals_a, _, token_program_a) = load_mint(rpc, &state.mint_a)?;
+    let (decimals_b, _, token_program_b) = load_mint(rpc, &state.mint_b)?;
+    let keys = PoolKeys::from_pool(address, &state, token_program_a, token_program_b);
+    let (_, lp_supply, _) = load_mint(rpc, &keys.mint_liquidity)?;
+    let (mint_a, mint_b) = (state.mint_a, state.mint_b);
+
+    Ok(LoadedPool {
+        address,
+        reserve_a: token_balance(rpc, &keys.pool_account_a)?,
+        reserve_b: token_balance(rpc, &keys.pool_account_b)?,
+        state,
+        keys,
+        decimals_a,
+        decimals_b,
+        lp_supply,
+        transfer_fee_a: load_transfer_fee(rpc, &mint_a)?,
+        transfer_fee_b: load_transfer_fee(rpc, &mint_b)?,
+        epoch: rpc.get_epoch()?,
+    })
+}
+
+fn send(rpc: &RpcClient, payer: &Keypair, instructions: &[Instruction]) -> Result<Signature> {
+    let blockhash = rpc.get_latest_blockhash()?;
+    let transaction = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &[payer], blockhash);
+    rpc.send_and_confirm(&transaction)
+}
+
+/// Creates the associated account of payer receiving a mint, if missing
+fn create_destination(payer: &Keypair, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    create_associated_token_account_idempotent(&payer.pubkey(), &payer.pubkey(), mint, token_program)
+}
+
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// Least amount accepted when amount is quoted, slippage_bps below it
+fn min_with_slippage(amount: u64, slippage_bps: u16) -> u64 {
+    let tolerance = BPS_MULTIPLIER.saturating_sub(slippage_bps as u128);
+    (amount as u128 * tolerance / BPS_MULTIPLIER) as u64
+}
+
+/// Most amount paid when amount is quoted, slippage_bps above it
+fn max_with_slippage(amount: u64, slippage_bps: u16) -> u64 {
+    let tolerance = BPS_MULTIPLIER + slippage_bps as u128;
+    (amount as u128 * tolerance).div_ceil(BPS_MULTIPLIER).min(u64::MAX as u128) as u64
+}
+
+fn fee_percent(fee: u16) -> f64 {
+    fee as f64 * 100.0 / FEE_MULTIPLIER as f64
+}
+
+fn curve_name(curve: u8) -> &'static str {
+    match curve {
+        CURVE_STABLE => "stable",
+        CURVE_WEIGHTED => "weighted",
+        CURVE_CONCENTRATED => "concentrated",
+        _ => "constant-product",
+    }
+}
+
+/// Marginal price of one token A in tokens B, fees excluded and in UI units
+fn spot_price(pool: &LoadedPool) -> Result<Option<f64>> {
+    let state = &pool.state;
+    let (reserve_a, reserve_b) = (pool.reserve_a as f64, pool.reserve_b as f64);
+
+    let price = match state.curve {
+        CURVE_CONCENTRATED => {
+            let sqrt_price = state.sqrt_price_x64 as f64 / 2f64.powi(64);
+            sqrt_price * sqrt_price
+        }
+        _ if pool.reserve_a == 0 || pool.reserve_b == 0 => return Ok(None),
+        CURVE_WEIGHTED => (reserve_b / state.weight_b as f64) / (reserve_a / state.weight_a as f64),
+        CURVE_STABLE => {
+            // Slope of the invariant over a millionth of the reserve
+            let step = (pool.reserve_a / 1_000_000).max(1);
+            let d = stable_math::compute_d(state.amp, pool.reserve_a, pool.reserve_b)?;
+            let new_reserve_b = stable_math::compute_y(state.amp, pool.reserve_a as u128 + step as u128, d)?;
+            (pool.reserve_b as u128).saturating_sub(new_reserve_b) as f64 / step as f64
+        }
+        _ => reserve_b / reserve_a,
+    };
+
+    Ok(Some(price * 10f64.powi(pool.decimals_a as i32 - pool.decimals_b as i32)))
+}
+
+/// Deposit of up to amount_a and amount_b, the LP tokens being minted for
+/// what the vaults receive of the matched amounts
+fn deposit_quote(pool: &LoadedPool, amount_a: u64, amount_b: u64) -> Result<DepositQuote> {
+    let quote = quote::deposit(&pool.state, pool.reserve_a, pool.reserve_b, pool.lp_supply, amount_a, amount_b)?;
+    let received_a = quote.amount_a - pool.transfer_fee(true, quote.amount_a);
+    let received_b = quote.amount_b - pool.transfer_fee(false, quote.amount_b);
+    if received_a == quote.amount_a && received_b == quote.amount_b {
+        return Ok(quote);
+    }
+
+    let liquidity = quote::deposit_liquidity(&pool.state, pool.reserve_a, pool.reserve_b, pool.lp_supply, received_a, received_b)?;
+    Ok(DepositQuote { liquidity, ..quote })
+}
+
+/// Withdrawal of lp_amount, on what reaches the user once the transfer fees
+/// are withheld
+fn withdraw_quote(pool: &LoadedPool, lp_amount: u64) -> Result<WithdrawQuote> {
+    let quote = quote::withdraw(pool.reserve_a, pool.reserve_b, pool.lp_supply, lp_amount)?;
+    Ok(WithdrawQuote {
+        amount_a: quote.amount_a - pool.transfer_fee(true, quote.amount_a),
+        amount_b: quote.amount_b - pool.transfer_fee(false, quote.amount_b),
+    })
+}
+
+/// A swap as the user sees it, limit being the min output or max input the
+/// instruction is sent with
+struct SwapPlan {
+    quote: SwapAmounts,
+    input: u64,
+    output: u64,
+    limit: u64,
+}
+
+/// Quotes are on what the vaults receive and send, the user pays and gets
+/// the transfer fees of the mints on top
+fn swap_plan(pool: &LoadedPool, swap_a: bool, amount: u64, exact_out: bool, slippage_bps: u16, now: i64) -> Result<SwapPlan> {
+    if exact_out {
+        let vault_output = amount.saturating_add(pool.inverse_transfer_fee(!swap_a, amount));
+        let quote = quote::swap_exact_out(&pool.state, pool.reserve_a, pool.reserve_b, swap_a, vault_output, now)?;
+        let input = quote.input.saturating_add(pool.inverse_transfer_fee(swap_a, quote.input));
+        Ok(SwapPlan { quote, input, output: amount, limit: max_with_slippage(input, slippage_bps) })
+    } else {
+        let vault_input = amount - pool.transfer_fee(swap_a, amount);
+        let quote = quote::swap_exact_in(&pool.state, pool.reserve_a, pool.reserve_b, swap_a, vault_input, now)?;
+        let output = quote.output - pool.transfer_fee(!swap_a, quote.output);
+        Ok(SwapPlan { quote, input: amount, output, limit: min_with_slippage(output, slippage_bps) })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_pool(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    lp_fee: u16,
+    curve: Curve,
+    amp: Option<u64>,
+    weight_a: Option<u64>,
+) -> Result<Value> {
+    // Pools are keyed on sorted mints, the weight following its mint
+    let (mint_a, mint_b, weight_a) = if mint_a < mint_b {
+        (mint_a, mint_b, weight_a)
+    } else {
+        (mint_b, mint_a, weight_a.map(|weight| WEIGHT_MULTIPLIER.saturating_sub(weight)))
+    };
+    let (_, _, token_program_a) = load_mint(rpc, &mint_a)?;
+    let (_, _, token_program_b) = load_mint(rpc, &mint_b)?;
+    // Pools are seeded by the AMM admin
+    let admin = load_amm(rpc)?.admin;
+    let keys = PoolKeys::derive(mint_a, mint_b, admin, payer.pubkey(), lp_fee, token_program_a, token_program_b);
+
+    let instruction = match curve {
+        Curve::ConstantProduct => instructions::create_pool(&keys),
+        Curve::Stable => {
+            instructions::create_stable_pool(&keys, amp.ok_or_else(|| anyhow!("--amp is required for stable pools"))?)
+        }
+        Curve::Weighted => instructions::create_weighted_pool(
+            &keys,
+            weight_a.ok_or_else(|| anyhow!("--weight-a is required for weighted pools"))?,
+        ),
+    };
+    let signature = send(rpc, payer, &[instruction])?;
+
+    Ok(json!({
+        "signature": signature.to_string(),
+        "pool": keys.pool.to_string(),
+        "mint_a": mint_a.to_string(),
+        "mint_b": mint_b.to_string(),
+        "lp_mint": keys.mint_liquidity.to_string(),
+        "admin": admin.to_string(),
+    }))
+}
+
+pub fn deposit(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    pool: Pubkey,
+    amount_a: u64,
+    amount_b: u64,
+    slippage_bps: u16,
+) -> Result<Value> {
+    let pool = load_pool(rpc, pool)?;
+    let quote = deposit_quote(&pool, amount_a, amount_b)?;
+    let liquidity = quote.liquidity;
+    let min_liquidity = min_with_slippage(liquidity, slippage_bps);
+
+    let user = UserKeys::associated(payer.pubkey(), &pool.keys);
+    let signature = send(
+        rpc,
+        payer,
+        &[
+            create_destination(payer, &pool.keys.mint_liquidity, &token::ID),
+            instructions::deposit_liquidity(&pool.keys, &user, amount_a, amount_b, min_liquidity),
+        ],
+    )?;
+
+    Ok(json!({
+        "signature": signature.to_string(),
+        "amount_a": quote.amount_a,
+        "amount_b": quote.amount_b,
+        "lp_tokens": liquidity,
+        "min_lp_tokens": min_liquidity,
+    }))
+}
+
+pub fn withdraw(rpc: &RpcClient, payer: &Keypair, pool: Pubkey, lp_amount: u64, slippage_bps: u16) -> Result<Value> {
+    let pool = load_pool(rpc, pool)?;
+    let WithdrawQuote { amount_a, amount_b } = withdraw_quote(&pool, lp_amount)?;
+    let (min_a, min_b) = (min_with_slippage(amount_a, slippage_bps), min_with_slippage(amount_b, slippage_bps));
+
+    let user = UserKeys::associated(payer.pubkey(), &pool.keys);
+    let keys = &pool.keys;
+    let signature = send(
+        rpc,
+        payer,
+        &[
+            create_destination(payer, &keys.mint_a, &keys.token_program_a),
+            create_destination(payer, &keys.mint_b, &keys.token_program_b),
+            instructions::withdraw_liquidity(keys, &user, lp_amount, min_a, min_b),
+        ],
+    )?;
+
+    Ok(json!({
+        "signature": signature.to_string(),
+        "lp_tokens": lp_amount,
+        "amount_a": amount_a,
+        "amount_b": amount_b,
+        "min_amount_a": min_a,
+        "min_amount_b": min_b,
+    }))
+}
+
+pub fn swap(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    pool: Pubkey,
+    sell: Side,
+    amount: u64,
+    exact_out: bool,
+    slippage_bps: u16,
+) -> Result<Value> {
+    let pool = load_pool(rpc, pool)?;
+    let swap_a = sell == Side::A;
+    let user = UserKeys::associated(payer.pubkey(), &pool.keys);
+
+    let plan = swap_plan(&pool, swap_a, amount, exact_out, slippage_bps, now())?;
+    let (instruction, limit_name) = if exact_out {
+        (instructions::simple_swap_exact_out(&pool.keys, &user, swap_a, amount, plan.limit), "max_input")
+    } else {
+        (instructions::simple_swap_exact_in(&pool.keys, &user, swap_a, amount, plan.limit), "min_output")
+    };
+    let (mint_out, token_program_out) = if swap_a {
+        (&pool.keys.mint_b, &pool.keys.token_program_b)
+    } else {
+        (&pool.keys.mint_a, &pool.keys.token_program_a)
+    };
+    let signature = send(rpc, payer, &[create_destination(payer, mint_out, token_program_out), instruction])?;
+
+    let mut report = json!({
+        "signature": signature.to_string(),
+        "input": plan.input,
+        "output": plan.output,
+        "lp_fee": plan.quote.lp_fee,
+        "protocol_fee": plan.quote.protocol_fee,
+    });
+    report[limit_name] = json!(plan.limit);
+    Ok(report)
+}
+
+pub fn collect_treasury(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    pool: Pubkey,
+    amount_a: u64,
+    amount_b: u64,
+    destination_a: Option<Pubkey>,
+    destination_b: Option<Pubkey>,
+) -> Result<Value> {
+    let pool = load_pool(rpc, pool)?;
+    let keys = &pool.keys;
+    let amount_a = amount_a.min(token_balance(rpc, &keys.treasury_mint_a)?);
+    let amount_b = amount_b.min(token_balance(rpc, &keys.treasury_mint_b)?);
+
+    // Without a destination, a side is paid to the associated account of the
+    // admin, created if needed
+    let mut setup = Vec::new();
+    let mut destination = |amount: u64, destination: Option<Pubkey>, mint: Pubkey, token_program: Pubkey| {
+        if amount == 0 || destination.is_some() {
+            return destination;
+        }
+        setup.push(create_destination(payer, &mint, &token_program));
+        Some(get_associated_token_address_with_program_id(&payer.pubkey(), &mint, &token_program))
+    };
+    let destination_a = destination(amount_a, destination_a, keys.mint_a, keys.token_program_a);
+    let destination_b = destination(amount_b, destination_b, keys.mint_b, keys.token_program_b);
+
+    setup.push(instructions::admin_gets_treasury(keys, payer.pubkey(), destination_a, destination_b, amount_a, amount_b));
+    let signature = send(rpc, payer, &setup)?;
+
+    Ok(json!({
+        "signature": signature.to_string(),
+        "amount_a": amount_a,
+        "amount_b": amount_b,
+        "destination_a": destination_a.map(|key| key.to_string()),
+        "destination_b": destination_b.map(|key| key.to_string()),
+    }))
+}
+
+pub fn inspect(rpc: &RpcClient, pool: Pubkey) -> Result<Value> {
+    let pool = load_pool(rpc, pool)?;
+    let keys = &pool.keys;
+    let treasury = (token_balance(rpc, &keys.treasury_mint_a)?, token_balance(rpc, &keys.treasury_mint_b)?);
+    let creator_vaults = (token_balance(rpc, &keys.creator_vault_a)?, token_balance(rpc, &keys.creator_vault_b)?);
+    inspect_report(&pool, treasury, creator_vaults, now())
+}
+
+/// JSON report of a pool, its treasury and creator vault balances given as
+/// (amount_a, amount_b)
+fn inspect_report(pool: &LoadedPool, treasury: (u64, u64), creator_vaults: (u64, u64), now: i64) -> Result<Value> {
+    let state = &pool.state;
+    let keys = &pool.keys;
+
+    let mut current = state.clone();
+    current.decay_volatility_to(now);
+
+    let paused: Vec<&str> = [(PAUSE_SWAP, "swap"), (PAUSE_DEPOSIT, "deposit"), (PAUSE_WITHDRAW, "withdraw")]
+        .into_iter()
+        .filter(|(flag, _)| state.is_paused(*flag))
+        .map(|(_, name)| name)
+        .collect();
+
+    Ok(json!({
+        "pool": pool.address.to_string(),
+        "curve": curve_name(state.curve),
+        "admin": state.admin.to_string(),
+        "creator": state.creator.to_string(),
+        "mint_a": keys.mint_a.to_string(),
+        "mint_b": keys.mint_b.to_string(),
+        "lp_mint": keys.mint_liquidity.to_string(),
+        "reserve_a": pool.reserve_a,
+        "reserve_b": pool.reserve_b,
+        // The supply locked by the first deposit is not counted by the mint
+        "lp_supply": pool.lp_supply,
+        "locked_lp_supply": MINIMUM_LIQUIDITY,
+        "spot_price_a_in_b": spot_price(pool)?,
+        "fees_percent": {
+            "lp": fee_percent(state.lp_fee),
+            "current_lp": fee_percent(current.dynamic_lp_fee()),
+            "protocol": fee_percent(state.protocol_fee),
+            "creator_share_of_protocol": fee_percent(state.creator_fee_share),
+        },
+        "dynamic_fee": state.dynamic_fee,
+        "paused": paused,
+        "treasury": {
+            "amount_a": treasury.0,
+            "amount_b": treasury.1,
+        },
+        "creator_vaults": {
+            "amount_a": creator_vaults.0,
+            "amount_b": creator_vaults.1,
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFee;
+    use ftrx_swap::math;
+    use ftrx_swap_sdk::constants::CURVE_CONSTANT_PRODUCT;
+
+    const RESERVE_A: u64 = 500_000_000;
+    const RESERVE_B: u64 = 200_000_000;
+    const LP_SUPPLY: u64 = 300_000_000;
+
+    /// Transfer fee of bps with no maximum, the same for every epoch
+    fn transfer_fee_config(bps: u16) -> TransferFeeConfig {
+        let fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: u64::MAX.into(),
+            transfer_fee_basis_points: bps.into(),
+        };
+        TransferFeeConfig { older_transfer_fee: fee, newer_transfer_fee: fee, ..Default::default() }
+    }
+
+    fn loaded_pool(curve: u8, transfer_fee_a: Option<u16>, transfer_fee_b: Option<u16>) -> LoadedPool {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let state = SimplePool {
+            curve,
+            lp_fee: 30,
+            protocol_fee: 5,
+            creator_fee_share: 2000,
+            weight_a: WEIGHT_MULTIPLIER * 4 / 5,
+            weight_b: WEIGHT_MULTIPLIER / 5,
+            mint_a,
+            mint_b,
+            ..Default::default()
+        };
+        let keys = PoolKeys::derive(mint_a, mint_b, Pubkey::new_unique(), Pubkey::new_unique(), 30, token::ID, spl_token_2022::id());
+        LoadedPool {
+            address: keys.pool,
+            state,
+            keys,
+            decimals_a: 6,
+            decimals_b: 9,
+            reserve_a: RESERVE_A,
+            reserve_b: RESERVE_B,
+            lp_supply: LP_SUPPLY,
+            transfer_fee_a: transfer_fee_a.map(transfer_fee_config),
+            transfer_fee_b: transfer_fee_b.map(transfer_fee_config),
+            epoch: 0,
+        }
+    }
+
+    #[test]
+    fn slippage_bounds_round_for_the_user() {
+        assert_eq!(min_with_slippage(1_000_000, 50), 995_000);
+        assert_eq!(max_with_slippage(1_000_000, 50), 1_005_000);
+        assert_eq!(min_with_slippage(999, 1), 998);
+        assert_eq!(max_with_slippage(999, 1), 1_000);
+        assert_eq!(min_with_slippage(1_000_000, 20_000), 0);
+        assert_eq!(max_with_slippage(u64::MAX, 100), u64::MAX);
+    }
+
+    #[test]
+    fn swap_exact_in_bounds_the_output_net_of_the_transfer_fees() {
+        let amount = 10_000_000;
+        let plan = swap_plan(&loaded_pool(CURVE_CONSTANT_PRODUCT, Some(100), Some(200)), true, amount, false, 50, 0).unwrap();
+
+        // 1% of the input and 2% of the output are withheld by the mints
+        let vault_quote = math::swap_exact_in(RESERVE_A, RESERVE_B, amount - amount / 100, 30, 5).unwrap();
+        assert_eq!(plan.quote, vault_quote);
+        assert_eq!(plan.input, amount);
+        assert_eq!(plan.output, vault_quote.output - vault_quote.output.div_ceil(50));
+        assert_eq!(plan.limit, min_with_slippage(plan.output, 50));
+
+        let plain = swap_plan(&loaded_pool(CURVE_CONSTANT_PRODUCT, None, None), true, amount, false, 50, 0).unwrap();
+        assert!(plan.limit < plain.limit);
+        assert_eq!(plain.output, plain.quote.output);
+    }
+
+    #[test]
+    fn swap_exact_out_bounds_the_input_gross_of_the_transfer_fees() {
+        let pool = loaded_pool(CURVE_CONSTANT_PRODUCT, Some(100), Some(200));
+        let amount = 1_000_000;
+        let plan = swap_plan(&pool, false, amount, true, 50, 0).unwrap();
+
+        // The vault sends enough for the user to get amount, who sends enough
+        // for the vault to get the quoted input
+        let vault_output = amount + pool.inverse_transfer_fee(true, amount);
+        assert!(vault_output - pool.transfer_fee(true, vault_output) >= amount);
+        assert_eq!(plan.quote, math::swap_exact_out(RESERVE_B, RESERVE_A, vault_output, 30, 5).unwrap());
+        assert_eq!(plan.output, amount);
+        assert_eq!(plan.input, plan.quote.input + pool.inverse_transfer_fee(false, plan.quote.input));
+        assert!(plan.input - pool.transfer_fee(false, plan.input) >= plan.quote.input);
+        assert_eq!(plan.limit, max_with_slippage(plan.input, 50));
+    }
+
+    #[test]
+    fn liquidity_quotes_are_net_of_the_transfer_fees() {
+        let pool = loaded_pool(CURVE_CONSTANT_PRODUCT, Some(100), None);
+        let plain = loaded_pool(CURVE_CONSTANT_PRODUCT, None, None);
+
+        let withdrawn = withdraw_quote(&pool, 3_000_000).unwrap();
+        let quote = withdraw_quote(&plain, 3_000_000).unwrap();
+        assert_eq!(withdrawn.amount_a, quote.amount_a - quote.amount_a.div_ceil(100));
+        assert_eq!(withdrawn.amount_b, quote.amount_b);
+
+        // The matched amounts are sent in full, the LP tokens follow what arrives
+        let deposited = deposit_quote(&pool, 5_000_000, 2_000_000).unwrap();
+        let quote = deposit_quote(&plain, 5_000_000, 2_000_000).unwrap();
+        assert_eq!((deposited.amount_a, deposited.amount_b), (quote.amount_a, quote.amount_b));
+        assert!(deposited.liquidity < quote.liquidity);
+    }
+
+    #[test]
+    fn spot_price_is_in_ui_units() {
+        let assert_close = |price: Option<f64>, expected: f64| {
+            let price = price.unwrap();
+            assert!((price - expected).abs() <= expected * 1e-9, "{} != {}", price, expected);
+        };
+
+        // 0.4 B per A in base units, B having 3 more decimals
+        let pool = loaded_pool(CURVE_CONSTANT_PRODUCT, None, None);
+        assert_close(spot_price(&pool).unwrap(), 0.0004);
+
+        // An 80/20 pool prices A at four times the ratio of the reserves
+        assert_close(spot_price(&loaded_pool(CURVE_WEIGHTED, None, None)).unwrap(), 0.0016);
+
+        let mut concentrated = loaded_pool(CURVE_CONCENTRATED, None, None);
+        concentrated.state.sqrt_price_x64 = 2 << 64;
+        concentrated.reserve_a = 0;
+        assert_close(spot_price(&concentrated).unwrap(), 0.004);
+
+        let mut empty = loaded_pool(CURVE_CONSTANT_PRODUCT, None, None);
+        empty.reserve_b = 0;
+        assert_eq!(spot_price(&empty).unwrap(), None);
+    }
+
+    #[test]
+    fn inspect_report_shape() {
+        let mut pool = loaded_pool(CURVE_CONSTANT_PRODUCT, None, None);
+        pool.state.paused_flags = PAUSE_SWAP | PAUSE_WITHDRAW;
+        let report = inspect_report(&pool, (7, 8), (1, 2), 0).unwrap();
+
+        let mut fields: Vec<&str> = report.as_object().unwrap().keys().map(String::as_str).collect();
+        fields.sort_unstable();
+        assert_eq!(
+            fields,
+            [
+                "admin",
+                "creator",
+                "creator_vaults",
+                "curve",
+                "dynamic_fee",
+                "fees_percent",
+                "locked_lp_supply",
+                "lp_mint",
+                "lp_supply",
+                "mint_a",
+                "mint_b",
+                "paused",
+                "pool",
+                "reserve_a",
+                "reserve_b",
+                "spot_price_a_in_b",
+                "treasury",
+            ]
+        );
+
+        assert_eq!(report["pool"], json!(pool.address.to_string()));
+        assert_eq!(report["curve"], json!("constant-product"));
+        assert_eq!(report["lp_mint"], json!(pool.keys.mint_liquidity.to_string()));
+        assert_eq!(report["reserve_a"], json!(RESERVE_A));
+        assert_eq!(report["lp_supply"], json!(LP_SUPPLY));
+        assert_eq!(report["locked_lp_supply"], json!(MINIMUM_LIQUIDITY));
+        assert!(report["spot_price_a_in_b"].is_f64());
+        assert_eq!(
+            report["fees_percent"],
+            json!({ "lp": 0.03, "current_lp": 0.03, "protocol": 0.005, "creator_share_of_protocol": 2.0 })
+        );
+        assert_eq!(report["dynamic_fee"], json!(false));
+        assert_eq!(report["paused"], json!(["swap", "withdraw"]));
+        assert_eq!(report["treasury"], json!({ "amount_a": 7, "amount_b": 8 }));
+        assert_eq!(report["creator_vaults"], json!({ "amount_a": 1, "amount_b": 2 }));
+
+        // Without liquidity there is no price to report
+        pool.reserve_a = 0;
+        assert!(inspect_report(&pool, (0, 0), (0, 0), 0).unwrap()["spot_price_a_in_b"].is_null());
+    }
+}
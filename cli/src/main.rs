@@ -0,0 +1,187 @@
+//! Command-line client for the ftrx_swap program
+//!
+//! Amounts are in base units of their mint, slippage tolerances in basis
+//! points of the quoted amounts
+
+mod commands;
+mod rpc;
+
+use std::{env, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::Value;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair},
+};
+
+use rpc::RpcClient;
+
+#[derive(Parser)]
+#[command(name = "ftrx-swap", version, about = "Create, trade and inspect ftrx_swap pools")]
+struct Cli {
+    /// JSON-RPC endpoint of the cluster
+    #[arg(long, short = 'u', global = true, default_value = "http://127.0.0.1:8899")]
+    url: String,
+
+    /// Keypair file signing and paying for the transactions, the Solana CLI
+    /// one by default
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<PathBuf>,
+
+    /// Print the results as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct Slippage {
+    /// Tolerated move of the quoted amounts, in basis points
+    #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(u16).range(0..=10000))]
+    slippage_bps: u16,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Curve {
+    ConstantProduct,
+    Stable,
+    Weighted,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Side {
+    A,
+    B,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a pool of two mints, the signer being its creator
+    CreatePool {
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        /// LP fee out of 100000, an enabled fee tier must exist for it
+        #[arg(long)]
+        lp_fee: u16,
+        #[arg(long, value_enum, default_value_t = Curve::ConstantProduct)]
+        curve: Curve,
+        /// Amplification coefficient of a stable pool
+        #[arg(long, required_if_eq("curve", "stable"))]
+        amp: Option<u64>,
+        /// Weight of mint_a in a weighted pool, out of 1000000
+        #[arg(long, required_if_eq("curve", "weighted"))]
+        weight_a: Option<u64>,
+    },
+    /// Deposit up to amount_a and amount_b, matched to the pool price
+    Deposit {
+        pool: Pubkey,
+        amount_a: u64,
+        amount_b: u64,
+        #[command(flatten)]
+        slippage: Slippage,
+    },
+    /// Burn LP tokens for their share of the reserves
+    Withdraw {
+        pool: Pubkey,
+        lp_amount: u64,
+        #[command(flatten)]
+        slippage: Slippage,
+    },
+    /// Swap the given input amount, or buy the given output with --exact-out
+    Swap {
+        pool: Pubkey,
+        /// Input amount, or output amount with --exact-out
+        amount: u64,
+        /// Token sold to the pool
+        #[arg(long, value_enum)]
+        sell: Side,
+        /// Buy exactly amount, constant product pools only
+        #[arg(long)]
+        exact_out: bool,
+        #[command(flatten)]
+        slippage: Slippage,
+    },
+    /// Send the protocol fees of the treasury to the admin signer
+    CollectTreasury {
+        pool: Pubkey,
+        /// Amounts to collect, everything by default
+        #[arg(long, default_value_t = u64::MAX)]
+        amount_a: u64,
+        #[arg(long, default_value_t = u64::MAX)]
+        amount_b: u64,
+        /// Token accounts receiving the fees, the associated accounts of the
+        /// signer by default
+        #[arg(long)]
+        destination_a: Option<Pubkey>,
+        #[arg(long)]
+        destination_b: Option<Pubkey>,
+    },
+    /// Print the reserves, LP supply, fees, spot price and treasury of a pool
+    Inspect { pool: Pubkey },
+}
+
+fn load_keypair(path: Option<PathBuf>) -> Result<Keypair> {
+    let path = match path {
+        Some(path) => path,
+        None => PathBuf::from(env::var("HOME")?).join(".config/solana/id.json"),
+    };
+    read_keypair_file(&path).map_err(|error| anyhow!("cannot read keypair {}: {}", path.display(), error))
+}
+
+/// Indented key: value lines of a JSON object
+fn print_human(value: &Value, indent: usize) {
+    let Some(object) = value.as_object() else {
+        return println!("{:indent$}{}", "", value);
+    };
+    for (key, value) in object {
+        match value {
+            Value::Object(_) => {
+                println!("{:indent$}{}:", "", key);
+                print_human(value, indent + 2);
+            }
+            Value::String(string) => println!("{:indent$}{}: {}", "", key, string),
+            _ => println!("{:indent$}{}: {}", "", key, value),
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let rpc = RpcClient::new(cli.url);
+
+    let report = match cli.command {
+        Command::Inspect { pool } => commands::inspect(&rpc, pool)?,
+        command => {
+            let payer = load_keypair(cli.keypair)?;
+            match command {
+                Command::CreatePool { mint_a, mint_b, lp_fee, curve, amp, weight_a } => {
+                    commands::create_pool(&rpc, &payer, mint_a, mint_b, lp_fee, curve, amp, weight_a)?
+                }
+                Command::Deposit { pool, amount_a, amount_b, slippage } => {
+                    commands::deposit(&rpc, &payer, pool, amount_a, amount_b, slippage.slippage_bps)?
+                }
+                Command::Withdraw { pool, lp_amount, slippage } => {
+                    commands::withdraw(&rpc, &payer, pool, lp_amount, slippage.slippage_bps)?
+                }
+                Command::Swap { pool, amount, sell, exact_out, slippage } => {
+                    commands::swap(&rpc, &payer, pool, sell, amount, exact_out, slippage.slippage_bps)?
+                }
+                Command::CollectTreasury { pool, amount_a, amount_b, destination_a, destination_b } => {
+                    commands::collect_treasury(&rpc, &payer, pool, amount_a, amount_b, destination_a, destination_b)?
+                }
+                Command::Inspect { .. } => unreachable!(),
+            }
+        }
+    };
+
+    if cli.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_human(&report, 0);
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! Blocking JSON-RPC client covering the few calls the CLI makes
+
+use std::{str::FromStr, thread, time::Duration};
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Signature, transaction::Transaction};
+
+/// Seconds a sent transaction is polled for before giving up
+const CONFIRMATION_TIMEOUT: u64 = 60;
+
+pub struct Account {
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+pub struct RpcClient {
+    url: String,
+}
+
+impl RpcClient {
+    pub fn new(url: String) -> Self {
+        RpcClient { url }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let mut response: Value = ureq::post(&self.url)
+            .send_json(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .with_context(|| format!("{} request to {} failed", method, self.url))?
+            .into_json()?;
+
+        if let Some(error) = response.get("error") {
+            // Simulation failures carry the program logs, they say why
+            let logs = error["data"]["logs"]
+                .as_array()
+                .map(|logs| logs.iter().filter_map(Value::as_str).collect::<Vec<_>>().join("\n"))
+                .unwrap_or_default();
+            bail!("{}: {}\n{}", method, error["message"].as_str().unwrap_or("unknown error"), logs);
+        }
+        Ok(response["result"].take())
+    }
+
+    /// Account at key, None if it does not exist
+    pub fn get_account(&self, key: &Pubkey) -> Result<Option<Account>> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([key.to_string(), { "encoding": "base64", "commitment": "confirmed" }]),
+        )?;
+        let value = &result["value"];
+        if value.is_null() {
+            return Ok(None);
+        }
+        let data = value["data"][0].as_str().ok_or_else(|| anyhow!("malformed account {}", key))?;
+        let owner = value["owner"].as_str().ok_or_else(|| anyhow!("malformed account {}", key))?;
+        Ok(Some(Account { owner: Pubkey::from_str(owner)?, data: STANDARD.decode(data)? }))
+    }
+
+    /// Same as get_account, failing if the account does not exist
+    pub fn get_existing_account(&self, key: &Pubkey) -> Result<Account> {
+        self.get_account(key)?.ok_or_else(|| anyhow!("account {} not found", key))
+    }
+
+    pub fn get_latest_blockhash(&self) -> Result<Hash> {
+        let result = self.call("getLatestBlockhash", json!([{ "commitment": "confirmed" }]))?;
+        let blockhash = result["value"]["blockhash"].as_str().ok_or_else(|| anyhow!("malformed blockhash"))?;
+        Ok(Hash::from_str(blockhash)?)
+    }
+
+    /// Current epoch, which Token-2022 transfer fees are scheduled on
+    pub fn get_epoch(&self) -> Result<u64> {
+        let result = self.call("getEpochInfo", json!([{ "commitment": "confirmed" }]))?;
+        result["epoch"].as_u64().ok_or_else(|| anyhow!("malformed epoch info"))
+    }
+
+    /// Sends transaction and waits until it is confirmed
+    pub fn send_and_confirm(&self, transaction: &Transaction) -> Result<Signature> {
+        let encoded = STANDARD.encode(bincode::serialize(transaction)?);
+        let signature = self.call(
+            "sendTransaction",
+            json!([encoded, { "encoding": "base64", "preflightCommitment": "confirmed" }]),
+        )?;
+        let signature = Signature::from_str(signature.as_str().ok_or_else(|| anyhow!("malformed signature"))?)?;
+
+        for _ in 0..CONFIRMATION_TIMEOUT * 2 {
+            let result = self.call("getSignatureStatuses", json!([[signature.to_string()]]))?;
+            let status = &result["value"][0];
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    bail!("transaction {} failed: {}", signature, status["err"]);
+                }
+                if matches!(status["confirmationStatus"].as_str(), Some("confirmed" | "finalized")) {
+                    return Ok(signature);
+                }
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+        bail!("transaction {} not confirmed after {} seconds", signature, CONFIRMATION_TIMEOUT)
+    }
+}
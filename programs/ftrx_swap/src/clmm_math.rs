@@ -0,0 +1,370 @@
+use anchor_lang::prelude::*;
+use fixed::types::I64F64;
+
+use crate::{
+    constants::{FEE_MULTIPLIER, MAX_SQRT_PRICE_X64, MAX_TICK, MIN_SQRT_PRICE_X64, MIN_TICK},
+    errors::FTRXSwapError,
+    stable_math::U256,
+    weighted_math,
+};
+
+/// 2^128 / sqrt(1.0001)^(2^i), multiplied together for the bits of a tick
+const TICK_FACTORS: [u128; 19] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e2139,
+    0xfff2e50f5f656932ef12357cf3c7fdcb,
+    0xffe5caca7e10e4e61c3624eaa0941ccf,
+    0xffcb9843d60f6159c9db58835c926643,
+    0xff973b41fa98c081472e6896dfb254bf,
+    0xff2ea16466c96a3843ec78b326b52860,
+    0xfe5dee046a99a2a811c461f1969c3052,
+    0xfcbe86c7900a88aedcffc83b479aa3a3,
+    0xf987a7253ac413176f2b074cf7815e53,
+    0xf3392b0822b70005940c7a398e4b70f2,
+    0xe7159475a2c29b7443b29c7fa6e889d8,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e4,
+    0x70d869a156d2a1b890bb3df62baf32f6,
+    0x31be135f97d08fd981231505542fcfa5,
+    0x09aa508b5b7a84e1c677de54f3e99bc8,
+    0x005d6af8dedb81196699c329225ee604,
+    0x00002216e584f5fa1ea926041bedfe97,
+];
+
+/// ln(sqrt(1.0001)) as I64F64 bits
+const LN_SQRT_TICK_BITS: i128 = 922291089899520;
+
+fn to_u128(value: U256) -> Result<u128> {
+    if value.bits() > 128 {
+        return err!(FTRXSwapError::MathOverflow);
+    }
+    Ok(value.low_u128())
+}
+
+fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| error!(FTRXSwapError::MathOverflow))
+}
+
+fn div_rounding(numerator: U256, denominator: U256, round_up: bool) -> U256 {
+    let (quotient, remainder) = numerator.div_mod(denominator);
+    if round_up && !remainder.is_zero() {
+        quotient + U256::one()
+    } else {
+        quotient
+    }
+}
+
+/// sqrt(1.0001^tick) in Q64.64, rounded up
+pub fn sqrt_price_at_tick(tick: i32) -> Result<u128> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return err!(FTRXSwapError::InvalidTick);
+    }
+
+    let absolute_tick = tick.unsigned_abs();
+    let mut ratio = if absolute_tick & 1 != 0 {
+        U256::from(TICK_FACTORS[0])
+    } else {
+        U256::one() << 128
+    };
+    for (bit, factor) in TICK_FACTORS.iter().enumerate().skip(1) {
+        if absolute_tick & (1 << bit) != 0 {
+            ratio = (ratio * U256::from(*factor)) >> 128;
+        }
+    }
+
+    // The factors are below one, positive ticks take the inverse
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    to_u128(div_rounding(ratio, U256::one() << 64, true))
+}
+
+/// Greatest tick whose sqrt price is at most sqrt_price_x64, estimated with
+/// the logarithm then settled against sqrt_price_at_tick
+pub fn tick_at_sqrt_price(sqrt_price_x64: u128) -> Result<i32> {
+    if !(MIN_SQRT_PRICE_X64..=MAX_SQRT_PRICE_X64).contains(&sqrt_price_x64) {
+        return err!(FTRXSwapError::InvalidSqrtPrice);
+    }
+
+    let log = weighted_math::ln(I64F64::from_bits(sqrt_price_x64 as i128))?;
+    let mut tick = (log / I64F64::from_bits(LN_SQRT_TICK_BITS))
+        .floor()
+        .to_num::<i32>()
+        .clamp(MIN_TICK, MAX_TICK);
+
+    while tick > MIN_TICK && sqrt_price_at_tick(tick)? > sqrt_price_x64 {
+        tick -= 1;
+    }
+    while tick < MAX_TICK && sqrt_price_at_tick(tick + 1)? <= sqrt_price_x64 {
+        tick += 1;
+    }
+
+    Ok(tick)
+}
+
+/// Token A held by `liquidity` between two sqrt prices,
+/// L * (sqrt_upper - sqrt_lower) / (sqrt_upper * sqrt_lower)
+pub fn amount_a_delta(sqrt_price_a: u128, sqrt_price_b: u128, liquidity: u128, round_up: bool) -> Result<u128> {
+    let (lower, upper) = if sqrt_price_a < sqrt_price_b { (sqrt_price_a, sqrt_price_b) } else { (sqrt_price_b, sqrt_price_a) };
+    if lower == 0 {
+        return err!(FTRXSwapError::InvalidSqrtPrice);
+    }
+
+    let numerator = (U256::from(liquidity) << 64)
+        .checked_mul(U256::from(upper - lower))
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    let amount = div_rounding(
+        div_rounding(numerator, U256::from(upper), round_up),
+        U256::from(lower),
+        round_up,
+    );
+    to_u128(amount)
+}
+
+/// Token B held by `liquidity` between two sqrt prices,
+/// L * (sqrt_upper - sqrt_lower)
+pub fn amount_b_delta(sqrt_price_a: u128, sqrt_price_b: u128, liquidity: u128, round_up: bool) -> Result<u128> {
+    let difference = sqrt_price_a.abs_diff(sqrt_price_b);
+    let amount = div_rounding(U256::from(liquidity) * U256::from(difference), U256::one() << 64, round_up);
+    to_u128(amount)
+}
+
+/// Sqrt price once `amount` is added to the pool, rounded so that the pool
+/// never gives out more than the input pays for
+pub fn next_sqrt_price_from_input(sqrt_price_x64: u128, liquidity: u128, amount: u64, a_to_b: bool) -> Result<u128> {
+    if liquidity == 0 {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+
+    let next = if a_to_b {
+        // L * sqrt / (L + amount * sqrt), rounded up
+        let shifted_liquidity = U256::from(liquidity) << 64;
+        let denominator = shifted_liquidity + U256::from(amount) * U256::from(sqrt_price_x64);
+        let numerator = shifted_liquidity
+            .checked_mul(U256::from(sqrt_price_x64))
+            .ok_or(FTRXSwapError::MathOverflow)?;
+        div_rounding(numerator, denominator, true)
+    } else {
+        // sqrt + amount / L, rounded down
+        U256::from(sqrt_price_x64) + (U256::from(amount) << 64) / U256::from(liquidity)
+    };
+
+    let next = to_u128(next)?;
+    if !(MIN_SQRT_PRICE_X64..=MAX_SQRT_PRICE_X64).contains(&next) {
+        return err!(FTRXSwapError::InvalidSqrtPrice);
+    }
+    Ok(next)
+}
+
+/// Token amounts backing a change of `liquidity` on [sqrt_lower, sqrt_upper)
+/// at the current sqrt price
+pub fn liquidity_amounts(
+    sqrt_price_x64: u128,
+    sqrt_lower: u128,
+    sqrt_upper: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(u64, u64)> {
+    let (amount_a, amount_b) = if sqrt_price_x64 < sqrt_lower {
+        (amount_a_delta(sqrt_lower, sqrt_upper, liquidity, round_up)?, 0)
+    } else if sqrt_price_x64 < sqrt_upper {
+        (
+            amount_a_delta(sqrt_price_x64, sqrt_upper, liquidity, round_up)?,
+            amount_b_delta(sqrt_lower, sqrt_price_x64, liquidity, round_up)?,
+        )
+    } else {
+        (0, amount_b_delta(sqrt_lower, sqrt_upper, liquidity, round_up)?)
+    };
+    Ok((to_u64(amount_a)?, to_u64(amount_b)?))
+}
+
+/// One step of a swap, within a range of constant liquidity
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapStep {
+    pub sqrt_price_next: u128,
+    /// Input moving the price, fees excluded
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// LP and protocol fees on amount_in
+    pub fee_amount: u64,
+}
+
+/// Moves the price towards sqrt_target with at most amount_remaining of
+/// input, fees included, `fee_rate` being out of FEE_MULTIPLIER
+pub fn compute_swap_step(
+    sqrt_price_x64: u128,
+    sqrt_target: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee_rate: u64,
+    a_to_b: bool,
+) -> Result<SwapStep> {
+    let taxed_share = FEE_MULTIPLIER
+        .checked_sub(fee_rate)
+        .filter(|share| *share > 0)
+        .ok_or(FTRXSwapError::InvalidFee)?;
+    let amount_less_fee = to_u64(amount_remaining as u128 * taxed_share as u128 / FEE_MULTIPLIER as u128)?;
+
+    let amount_to_target = if a_to_b {
+        amount_a_delta(sqrt_target, sqrt_price_x64, liquidity, true)?
+    } else {
+        amount_b_delta(sqrt_price_x64, sqrt_target, liquidity, true)?
+    };
+
+    let (sqrt_price_next, amount_in) = if amount_less_fee as u128 >= amount_to_target {
+        (sqrt_target, to_u64(amount_to_target)?)
+    } else {
+        let next = next_sqrt_price_from_input(sqrt_price_x64, liquidity, amount_less_fee, a_to_b)?;
+        let amount_in = if a_to_b {
+            amount_a_delta(next, sqrt_price_x64, liquidity, true)?
+        } else {
+            amount_b_delta(sqrt_price_x64, next, liquidity, true)?
+        };
+        (next, to_u64(amount_in)?)
+    };
+
+    let amount_out = if a_to_b {
+        amount_b_delta(sqrt_price_next, sqrt_price_x64, liquidity, false)?
+    } else {
+        amount_a_delta(sqrt_price_x64, sqrt_price_next, liquidity, false)?
+    };
+
+    // Short of the target the whole remainder is spent, the rest is fees
+    let fee_amount = if sqrt_price_next != sqrt_target {
+        amount_remaining
+            .checked_sub(amount_in)
+            .ok_or(FTRXSwapError::MathOverflow)?
+    } else {
+        to_u64(
+            (amount_in as u128 * fee_rate as u128)
+                .checked_add(taxed_share as u128 - 1)
+                .ok_or(FTRXSwapError::MathOverflow)?
+                / taxed_share as u128,
+        )?
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out: to_u64(amount_out)?,
+        fee_amount,
+    })
+}
+
+/// Fee growth per unit of liquidity between two ticks, from their outside
+/// values as Uniswap v3 keeps them, wrapping
+pub fn fee_growth_inside(
+    tick_current: i32,
+    tick_lower: i32,
+    fee_growth_outside_lower: u128,
+    tick_upper: i32,
+    fee_growth_outside_upper: u128,
+    fee_growth_global: u128,
+) -> u128 {
+    let below = if tick_current >= tick_lower {
+        fee_growth_outside_lower
+    } else {
+        fee_growth_global.wrapping_sub(fee_growth_outside_lower)
+    };
+    let above = if tick_current < tick_upper {
+        fee_growth_outside_upper
+    } else {
+        fee_growth_global.wrapping_sub(fee_growth_outside_upper)
+    };
+    fee_growth_global.wrapping_sub(below).wrapping_sub(above)
+}
+
+/// Fees earned by `liquidity` for a growth of fee_growth_delta per unit
+pub fn fees_earned(fee_growth_delta: u128, liquidity: u128) -> Result<u64> {
+    let fees = (U256::from(fee_growth_delta) * U256::from(liquidity)) >> 64;
+    to_u64(to_u128(fees)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tick_bounds_match_the_sqrt_price_bounds() {
+        assert_eq!(sqrt_price_at_tick(0).unwrap(), 1 << 64);
+        assert_eq!(sqrt_price_at_tick(MIN_TICK).unwrap(), MIN_SQRT_PRICE_X64);
+        assert_eq!(sqrt_price_at_tick(MAX_TICK).unwrap(), MAX_SQRT_PRICE_X64);
+        assert!(sqrt_price_at_tick(MIN_TICK - 1).is_err());
+        assert!(sqrt_price_at_tick(MAX_TICK + 1).is_err());
+        assert!(tick_at_sqrt_price(MIN_SQRT_PRICE_X64 - 1).is_err());
+    }
+
+    #[test]
+    fn sqrt_price_grows_with_the_tick() {
+        let mut previous = 0;
+        for tick in (MIN_TICK + 1..=MAX_TICK).step_by(997) {
+            let sqrt_price = sqrt_price_at_tick(tick).unwrap();
+            assert!(sqrt_price > previous);
+            assert!(sqrt_price > sqrt_price_at_tick(tick - 1).unwrap());
+            previous = sqrt_price;
+        }
+    }
+
+    #[test]
+    fn ticks_round_trip_through_their_sqrt_price() {
+        for tick in (MIN_TICK..MAX_TICK).step_by(1_009).chain([-2, -1, 0, 1, 2, MAX_TICK - 1]) {
+            let sqrt_price = sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(tick_at_sqrt_price(sqrt_price).unwrap(), tick);
+
+            // Any price short of the next tick still belongs to this one
+            let next_sqrt_price = sqrt_price_at_tick(tick + 1).unwrap();
+            assert_eq!(tick_at_sqrt_price(next_sqrt_price - 1).unwrap(), tick);
+            assert_eq!(tick_at_sqrt_price((sqrt_price + next_sqrt_price) / 2).unwrap(), tick);
+        }
+        assert_eq!(tick_at_sqrt_price(MAX_SQRT_PRICE_X64).unwrap(), MAX_TICK);
+    }
+
+    #[test]
+    fn amount_deltas_round_in_the_requested_direction() {
+        let ranges = [(-100, 100), (MIN_TICK, -400_000), (400_000, MAX_TICK), (-7, 13)];
+        for (lower, upper) in ranges {
+            let (sqrt_lower, sqrt_upper) = (sqrt_price_at_tick(lower).unwrap(), sqrt_price_at_tick(upper).unwrap());
+            for liquidity in [1, 1_000_003, u64::MAX as u128] {
+                let a_down = amount_a_delta(sqrt_lower, sqrt_upper, liquidity, false).unwrap();
+                let a_up = amount_a_delta(sqrt_lower, sqrt_upper, liquidity, true).unwrap();
+                let b_down = amount_b_delta(sqrt_lower, sqrt_upper, liquidity, false).unwrap();
+                let b_up = amount_b_delta(sqrt_lower, sqrt_upper, liquidity, true).unwrap();
+
+                // Two roundings on token A, one on token B
+                assert!(a_down <= a_up && a_up <= a_down + 2);
+                assert!(b_down <= b_up && b_up <= b_down + 1);
+
+                // The exact amounts lie in between, the order of the prices does not matter
+                let exact_a = liquidity as f64 * (1.0 / sqrt_lower as f64 - 1.0 / sqrt_upper as f64) * 2f64.powi(64);
+                let exact_b = liquidity as f64 * (sqrt_upper - sqrt_lower) as f64 / 2f64.powi(64);
+                let tolerance = |exact: f64| exact * 1e-12 + 1.0;
+                assert!(a_down as f64 <= exact_a + tolerance(exact_a) && a_up as f64 >= exact_a - tolerance(exact_a));
+                assert!(b_down as f64 <= exact_b + tolerance(exact_b) && b_up as f64 >= exact_b - tolerance(exact_b));
+                assert_eq!(amount_a_delta(sqrt_upper, sqrt_lower, liquidity, true).unwrap(), a_up);
+                assert_eq!(amount_b_delta(sqrt_upper, sqrt_lower, liquidity, false).unwrap(), b_down);
+            }
+        }
+
+        // A third of a unit rounds to zero or one
+        let sqrt_price = 1u128 << 64;
+        let third = sqrt_price + sqrt_price / 3;
+        assert_eq!(amount_b_delta(sqrt_price, third, 1, false).unwrap(), 0);
+        assert_eq!(amount_b_delta(sqrt_price, third, 1, true).unwrap(), 1);
+    }
+
+    #[test]
+    fn swap_step_never_gives_out_more_than_it_takes() {
+        let liquidity = 1_000_000_000u128;
+        let sqrt_price = sqrt_price_at_tick(0).unwrap();
+        for a_to_b in [true, false] {
+            let target = sqrt_price_at_tick(if a_to_b { -1_000 } else { 1_000 }).unwrap();
+            for amount in [1, 7, 1_000, 1_000_000] {
+                let step = compute_swap_step(sqrt_price, target, liquidity, amount, 30, a_to_b).unwrap();
+                assert!(step.amount_in + step.fee_amount <= amount);
+                // Around a price of one, output and input match up to the fees and rounding
+                assert!(step.amount_out <= step.amount_in);
+            }
+        }
+    }
+}
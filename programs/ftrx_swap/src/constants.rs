@@ -9,6 +9,93 @@ pub const MINIMUM_LIQUIDITY: u64 = 1000;
 pub const FEE_MULTIPLIER: u64 = 100000;
 
 
+/// Bits of SimplePool::paused_flags
+#[constant]
+pub const PAUSE_SWAP: u8 = 1;
+
+#[constant]
+pub const PAUSE_DEPOSIT: u8 = 2;
+
+#[constant]
+pub const PAUSE_WITHDRAW: u8 = 4;
+
+#[constant]
+pub const PAUSE_ALL: u8 = 7;
+
+
+/// Values of SimplePool::curve
+#[constant]
+pub const CURVE_CONSTANT_PRODUCT: u8 = 0;
+
+#[constant]
+pub const CURVE_STABLE: u8 = 1;
+
+#[constant]
+pub const CURVE_WEIGHTED: u8 = 2;
+
+#[constant]
+pub const CURVE_CONCENTRATED: u8 = 3;
+
+/// Bounds on the amplification coefficient of stable pools
+#[constant]
+pub const MIN_AMP: u64 = 1;
+
+#[constant]
+pub const MAX_AMP: u64 = 10000;
+
+/// Weights of weighted pools add up to WEIGHT_MULTIPLIER, each at least
+/// MIN_WEIGHT so that the swap exponent stays below 49
+#[constant]
+pub const WEIGHT_MULTIPLIER: u64 = 1000000;
+
+#[constant]
+pub const MIN_WEIGHT: u64 = 20000;
+
+/// Newton iterations before a curve solver gives up
+#[constant]
+pub const STABLE_MAX_ITERATIONS: u8 = 64;
+
+
+/// Volatility accumulator of dynamic fee pools, in FEE_MULTIPLIER units of
+/// relative price moves, halved every VOLATILITY_DECAY_PERIOD seconds, the
+/// LP fee reaching its max from VOLATILITY_CEILING on
+#[constant]
+pub const VOLATILITY_DECAY_PERIOD: i64 = 60;
+
+#[constant]
+pub const VOLATILITY_CEILING: u64 = 5000;
+
+/// Ticks of concentrated pools, sqrt(1.0001)^tick being the sqrt price,
+/// and the Q64.64 sqrt prices of the extreme ticks
+#[constant]
+pub const MIN_TICK: i32 = -443636;
+
+#[constant]
+pub const MAX_TICK: i32 = 443636;
+
+#[constant]
+pub const MIN_SQRT_PRICE_X64: u128 = 4295048017;
+
+#[constant]
+pub const MAX_SQRT_PRICE_X64: u128 = 79226673515401279992447579062;
+
+/// Bound on the spacing between usable ticks of a concentrated pool
+#[constant]
+pub const MAX_TICK_SPACING: u16 = 16384;
+
+/// Number of spaced ticks stored in a TickArray
+#[constant]
+pub const TICK_ARRAY_SIZE: usize = 32;
+
+
+/// Number of entries in the PoolObservations ring buffer
+#[constant]
+pub const OBSERVATION_COUNT: usize = 32;
+
+
+#[constant]
+pub const AMM_SEED: &str = "amm";
+
 #[constant]
 pub const AUTHORITY_SEED: &str = "authority";
 
@@ -16,4 +103,26 @@ pub const AUTHORITY_SEED: &str = "authority";
 pub const LIQUIDITY_SEED: &str = "liquidity";
 
 #[constant]
-pub const TREASURY_SEED: &str = "treasury";
\ No newline at end of file
+pub const TREASURY_SEED: &str = "treasury";
+
+#[constant]
+pub const OBSERVATION_SEED: &str = "observation";
+
+#[constant]
+pub const CREATOR_SEED: &str = "creator";
+
+#[constant]
+pub const TICK_ARRAY_SEED: &str = "tick_array";
+
+#[constant]
+pub const POSITION_SEED: &str = "position";
+
+#[constant]
+pub const FEE_TIER_SEED: &str = "fee_tier";
+
+#[constant]
+pub const METADATA_SEED: &str = "metadata";
+
+/// Symbol of the LP tokens, also prefixing their name
+#[constant]
+pub const LP_SYMBOL: &str = "FTRX-LP";
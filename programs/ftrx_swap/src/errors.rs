@@ -29,6 +29,87 @@ pub enum FTRXSwapError {
     #[msg("Slippage exceeded")]
     SlippageExceeded,
 
+    #[msg("Not enough liquidity in the pool for this output")]
+    InsufficientLiquidity,
+
+    #[msg("Invalid swap route")]
+    InvalidRoute,
+
+    #[msg("The AMM is paused")]
+    AmmPaused,
+
+    #[msg("Signer is not the admin")]
+    InvalidAdmin,
+
+    #[msg("Signer is not the pool creator")]
+    InvalidCreator,
+
+    #[msg("Missing destination account for a non zero amount")]
+    MissingDestination,
+
+    #[msg("This operation is paused on the pool")]
+    PoolPaused,
+
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+
+    #[msg("Deadline exceeded")]
+    DeadlineExceeded,
+
+    #[msg("Mint uses an unsupported token extension")]
+    UnsupportedMintExtension,
+
+    #[msg("The pool is locked by an outstanding flash loan")]
+    PoolLocked,
+
+    #[msg("Flash loan is not repaid in the same transaction")]
+    FlashLoanNotRepaid,
+
+    #[msg("Invalid flash loan")]
+    InvalidFlashLoan,
+
+    #[msg("Invalid flash swap receiver program")]
+    InvalidReceiver,
+
+    #[msg("Invalid amplification coefficient")]
+    InvalidAmp,
+
+    #[msg("Curve solver did not converge")]
+    CurveDidNotConverge,
+
+    #[msg("Operation not supported by the pool curve")]
+    UnsupportedCurve,
+
+    #[msg("Invalid pool weights")]
+    InvalidWeights,
+
+    #[msg("Invalid tick spacing")]
+    InvalidTickSpacing,
+
+    #[msg("Tick is out of bounds or not on the tick spacing")]
+    InvalidTick,
+
+    #[msg("Invalid tick range")]
+    InvalidTickRange,
+
+    #[msg("Sqrt price is out of bounds")]
+    InvalidSqrtPrice,
+
+    #[msg("Tick array does not cover the expected ticks")]
+    InvalidTickArray,
+
+    #[msg("Position still holds liquidity or fees")]
+    PositionNotEmpty,
+
+    #[msg("Fee tier is disabled")]
+    FeeTierDisabled,
+
+    #[msg("Metadata URI is too long")]
+    InvalidUri,
+
+    #[msg("Pool does not use the legacy layout")]
+    PoolAlreadyMigrated,
+
     
 
 }
\ No newline at end of file
@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct PoolFeesUpdated {
+    pub pool: Pubkey,
+    pub old_lp_fee: u16,
+    pub new_lp_fee: u16,
+    pub old_protocol_fee: u16,
+    pub new_protocol_fee: u16,
+}
+
+#[event]
+pub struct PoolDynamicFeeUpdated {
+    pub pool: Pubkey,
+    pub dynamic_fee: bool,
+    pub max_dynamic_lp_fee: u16,
+}
+
+#[event]
+pub struct FeeTierUpdated {
+    pub fee_tier: Pubkey,
+    pub lp_fee: u16,
+    pub protocol_fee: u16,
+    pub creator_fee_share: u16,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct PoolAdminProposed {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminProposalCancelled {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct PoolAdminUpdated {
+    pub pool: Pubkey,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct PoolPausedFlagsUpdated {
+    pub pool: Pubkey,
+    pub old_paused_flags: u8,
+    pub new_paused_flags: u8,
+}
+
+#[event]
+pub struct PoolCreated {
+    pub pool: Pubkey,
+    pub amm: Pubkey,
+    pub creator: Pubkey,
+    pub admin: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub mint_liquidity: Pubkey,
+    pub lp_fee: u16,
+    pub protocol_fee: u16,
+}
+
+#[event]
+pub struct Swap {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub swap_a: bool,
+    pub input: u64,
+    pub output: u64,
+    pub lp_fee: u64,
+    pub protocol_fee: u64,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    /// LP fee rate the trade paid, out of FEE_MULTIPLIER
+    pub lp_fee_rate: u16,
+}
+
+#[event]
+pub struct LiquidityDeposited {
+    pub pool: Pubkey,
+    pub depositor: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub lp_minted: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct LiquidityWithdrawn {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub lp_burned: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct TreasuryCollected {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct CreatorFeesCollected {
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct FlashLoan {
+    pub pool: Pubkey,
+    pub borrower: Pubkey,
+    pub borrow_a: bool,
+    pub amount: u64,
+    pub lp_fee: u64,
+    pub protocol_fee: u64,
+}
+
+#[event]
+pub struct PositionLiquidityChanged {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity_delta: i128,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct PositionFeesCollected {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct LpMetadataUriUpdated {
+    pub pool: Pubkey,
+    pub mint_liquidity: Pubkey,
+    pub uri: String,
+}
+
+#[event]
+pub struct PoolMigrated {
+    pub pool: Pubkey,
+    pub amm: Pubkey,
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::*,
+    events::PoolAdminUpdated,
+    state::SimplePool,
+};
+
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    // The pool, LP mint and treasury addresses stay derived from admin_seed,
+    // so they are still reachable by the new admin
+    let pool = &mut ctx.accounts.pool;
+    let old_admin = pool.admin;
+    pool.admin = ctx.accounts.pending_admin.key();
+    pool.pending_admin = Pubkey::default();
+
+    emit!(PoolAdminUpdated {
+        pool: pool.key(),
+        old_admin,
+        new_admin: pool.admin,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = pending_admin @ FTRXSwapError::InvalidAdmin,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    pub pending_admin: Signer<'info>,
+}
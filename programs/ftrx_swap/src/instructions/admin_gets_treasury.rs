@@ -1,60 +1,74 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{self, Burn, Mint, Token, TokenAccount, Transfer},
-};
-use fixed::types::I64F64;
-use fixed_sqrt::FixedSqrt;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 use crate::{
-    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY},
     constants::TREASURY_SEED,
     errors::FTRXSwapError,
+    events::TreasuryCollected,
     state::SimplePool,
 };
 
 pub fn admin_gets_treasury(ctx: Context<AdminGetsTreasury>, amount_a: u64,amount_b: u64) -> Result<()> {
    
     let actual_pool=&ctx.accounts.pool;
-    let lp_fee=actual_pool.lp_fee.to_le_bytes();
+    let lp_fee_seed=actual_pool.lp_fee_seed.to_le_bytes();
 
     let authority_seeds = &[
         actual_pool.mint_a.as_ref(),
         actual_pool.mint_b.as_ref(),
-        actual_pool.admin.as_ref(),
-        lp_fee.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
         &[actual_pool.pool_bump],
     ];
     let signer_seeds = &[&authority_seeds[..]];
 
-
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.treasury_mint_a.to_account_info(),
-                to: ctx.accounts.depositor_account_a.to_account_info(),
-                authority: ctx.accounts.pool.to_account_info(),
-            },
-            signer_seeds,
-        ),
-        ctx.accounts.treasury_mint_a.amount,
-    )?;
-
-
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.treasury_mint_b.to_account_info(),
-                to: ctx.accounts.depositor_account_b.to_account_info(),
-                authority: ctx.accounts.pool.to_account_info(),
-            },
-            signer_seeds,
-        ),
-        ctx.accounts.treasury_mint_b.amount,
-    )?;
-
+    // Requested amounts are capped by the treasury balances, u64::MAX sweeps everything
+    // A side with nothing to send is skipped and does not need a destination account
+    let amount_a = amount_a.min(ctx.accounts.treasury_mint_a.amount);
+    let amount_b = amount_b.min(ctx.accounts.treasury_mint_b.amount);
+
+    if amount_a > 0 {
+        let destination_a = ctx.accounts.depositor_account_a.as_ref().ok_or(FTRXSwapError::MissingDestination)?;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_a.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.treasury_mint_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
+                    to: destination_a.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_a,
+            ctx.accounts.mint_a.decimals,
+        )?;
+    }
+
+    if amount_b > 0 {
+        let destination_b = ctx.accounts.depositor_account_b.as_ref().ok_or(FTRXSwapError::MissingDestination)?;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_b.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.treasury_mint_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
+                    to: destination_b.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_b,
+            ctx.accounts.mint_b.decimals,
+        )?;
+    }
+
+    emit!(TreasuryCollected {
+        pool: ctx.accounts.pool.key(),
+        admin: ctx.accounts.admin.key(),
+        amount_a,
+        amount_b,
+    });
 
     Ok(())
 }
@@ -67,30 +81,31 @@ pub struct AdminGetsTreasury<'info> {
        
         mint_a.key().as_ref(),
         mint_b.key().as_ref(),
-        admin.key().as_ref(),
-        &pool.lp_fee.to_le_bytes(),
+        pool.admin_seed.as_ref(),
+        &pool.lp_fee_seed.to_le_bytes(),
 
         ],
         bump,
         has_one = mint_a,
         has_one = mint_b,
+        has_one = admin @ FTRXSwapError::InvalidAdmin,
     )]
     pub pool: Account<'info, SimplePool>,
 
 
+    /// Any token account of mint A, e.g. a multisig vault
     #[account(
         mut,
-        associated_token::mint = mint_a,
-        associated_token::authority = admin,
+        token::mint = mint_a,
     )]
-    pub depositor_account_a: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_a: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
+    /// Any token account of mint B
     #[account(
         mut,
-        associated_token::mint = mint_b,
-        associated_token::authority = admin,
+        token::mint = mint_b,
     )]
-    pub depositor_account_b: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
 
         
@@ -101,12 +116,12 @@ pub struct AdminGetsTreasury<'info> {
         pool.mint_a.key().as_ref(),
         pool.key().as_ref(),
         TREASURY_SEED.as_ref(),
-        pool.admin.key().as_ref(),
+        pool.admin_seed.as_ref(),
         ],
         bump,
 
       )]
-    pub treasury_mint_a: Box<Account<'info, TokenAccount>>,
+    pub treasury_mint_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(mut,
         token::mint = mint_b,
@@ -115,25 +130,30 @@ pub struct AdminGetsTreasury<'info> {
         pool.mint_b.key().as_ref(),
         pool.key().as_ref(),
         TREASURY_SEED.as_ref(),
-        pool.admin.key().as_ref(),
+        pool.admin_seed.as_ref(),
         ],
         bump,
    
       )]
-    pub treasury_mint_b: Box<Account<'info, TokenAccount>>,
+    pub treasury_mint_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
 
-    pub mint_a: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    pub mint_b: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
 
-    /// The account paying for all rents
-    #[account(mut)]
+    /// Admin of the pool, the treasury is sent wherever it chooses
     pub admin: Signer<'info>,
 
     /// Solana ecosystem accounts
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
+    /// Token programs of mints A and B, legacy or Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
 }
\ No newline at end of file
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::*,
+    events::AdminProposalCancelled,
+    state::SimplePool,
+};
+
+pub fn cancel_admin_proposal(ctx: Context<CancelAdminProposal>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let pending_admin = pool.pending_admin;
+    pool.pending_admin = Pubkey::default();
+
+    emit!(AdminProposalCancelled {
+        pool: pool.key(),
+        admin: pool.admin,
+        pending_admin,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelAdminProposal<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = admin @ FTRXSwapError::InvalidAdmin,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::POSITION_SEED,
+    errors::FTRXSwapError,
+    state::Position,
+};
+
+pub fn close_position(_ctx: Context<ClosePosition>) -> Result<()> {
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+
+    /// Only empty positions are closed, nothing is left behind
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            POSITION_SEED.as_ref(),
+            position.pool.as_ref(),
+            owner.key().as_ref(),
+            &position.tick_lower.to_le_bytes(),
+            &position.tick_upper.to_le_bytes(),
+        ],
+        bump = position.bump,
+        has_one = owner,
+        constraint = position.liquidity == 0 @ FTRXSwapError::PositionNotEmpty,
+        constraint = position.fees_owed_a == 0 && position.fees_owed_b == 0 @ FTRXSwapError::PositionNotEmpty,
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    /// Receives the rent of the position
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
@@ -0,0 +1,387 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    clmm_math,
+    constants::{AMM_SEED, CURVE_CONCENTRATED, PAUSE_DEPOSIT, PAUSE_WITHDRAW, POSITION_SEED, TICK_ARRAY_SEED},
+    errors::FTRXSwapError,
+    events::{PositionFeesCollected, PositionLiquidityChanged},
+    state::{Position, SimpleAmm, SimplePool, TickArray},
+};
+
+pub fn increase_liquidity(ctx: Context<ModifyPosition>, liquidity: u128, max_amount_a: u64, max_amount_b: u64) -> Result<()> {
+    if ctx.accounts.pool.is_paused(PAUSE_DEPOSIT) {
+        return err!(FTRXSwapError::PoolPaused);
+    }
+    if liquidity == 0 {
+        return err!(FTRXSwapError::DepositTooSmall);
+    }
+    let liquidity_delta = i128::try_from(liquidity).map_err(|_| error!(FTRXSwapError::MathOverflow))?;
+
+    ctx.accounts.modify(liquidity_delta)?;
+
+    // Amounts are rounded up, in favor of the pool
+    let (amount_a, amount_b) = ctx.accounts.position_amounts(liquidity, true)?;
+    if amount_a > max_amount_a || amount_b > max_amount_b {
+        return err!(FTRXSwapError::SlippageExceeded);
+    }
+
+    let (vault_a_before, vault_b_before) = (ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+    ctx.accounts.transfer_to_vault(true, amount_a)?;
+    ctx.accounts.transfer_to_vault(false, amount_b)?;
+
+    // Reload accounts because of the CPIs
+    // Transfer fee mints would credit less than the liquidity is worth
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    if ctx.accounts.pool_account_a.amount - vault_a_before < amount_a
+        || ctx.accounts.pool_account_b.amount - vault_b_before < amount_b
+    {
+        return err!(FTRXSwapError::DepositTooSmall);
+    }
+
+    emit!(PositionLiquidityChanged {
+        pool: ctx.accounts.pool.key(),
+        position: ctx.accounts.position.key(),
+        owner: ctx.accounts.owner.key(),
+        tick_lower: ctx.accounts.position.tick_lower,
+        tick_upper: ctx.accounts.position.tick_upper,
+        liquidity_delta,
+        amount_a,
+        amount_b,
+    });
+
+    Ok(())
+}
+
+pub fn decrease_liquidity(ctx: Context<ModifyPosition>, liquidity: u128, min_amount_a: u64, min_amount_b: u64) -> Result<()> {
+    if ctx.accounts.pool.is_paused(PAUSE_WITHDRAW) {
+        return err!(FTRXSwapError::PoolPaused);
+    }
+    if liquidity == 0 || liquidity > ctx.accounts.position.liquidity {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+    let liquidity_delta = -(liquidity as i128);
+
+    ctx.accounts.modify(liquidity_delta)?;
+
+    // Amounts are rounded down, in favor of the pool
+    let (amount_a, amount_b) = ctx.accounts.position_amounts(liquidity, false)?;
+    if amount_a < min_amount_a || amount_b < min_amount_b {
+        return err!(FTRXSwapError::SlippageExceeded);
+    }
+
+    ctx.accounts.transfer_from_vault(true, amount_a)?;
+    ctx.accounts.transfer_from_vault(false, amount_b)?;
+
+    emit!(PositionLiquidityChanged {
+        pool: ctx.accounts.pool.key(),
+        position: ctx.accounts.position.key(),
+        owner: ctx.accounts.owner.key(),
+        tick_lower: ctx.accounts.position.tick_lower,
+        tick_upper: ctx.accounts.position.tick_upper,
+        liquidity_delta,
+        amount_a,
+        amount_b,
+    });
+
+    Ok(())
+}
+
+/// Sends the owner every fee the position earned so far
+pub fn collect_position_fees(ctx: Context<ModifyPosition>) -> Result<()> {
+    if ctx.accounts.pool.is_paused(PAUSE_WITHDRAW) {
+        return err!(FTRXSwapError::PoolPaused);
+    }
+
+    ctx.accounts.modify(0)?;
+
+    let position = &mut ctx.accounts.position;
+    let (amount_a, amount_b) = (position.fees_owed_a, position.fees_owed_b);
+    position.fees_owed_a = 0;
+    position.fees_owed_b = 0;
+
+    ctx.accounts.transfer_from_vault(true, amount_a)?;
+    ctx.accounts.transfer_from_vault(false, amount_b)?;
+
+    emit!(PositionFeesCollected {
+        pool: ctx.accounts.pool.key(),
+        position: ctx.accounts.position.key(),
+        owner: ctx.accounts.owner.key(),
+        amount_a,
+        amount_b,
+    });
+
+    Ok(())
+}
+
+impl<'info> ModifyPosition<'info> {
+    /// Applies liquidity_delta to the position, its ticks and the pool if
+    /// the range is active, after crediting the fees earned so far
+    fn modify(&mut self, liquidity_delta: i128) -> Result<()> {
+        let pool = &mut self.pool;
+        let position = &mut self.position;
+        let tick_spacing = pool.tick_spacing;
+        let (tick_lower, tick_upper) = (position.tick_lower, position.tick_upper);
+        let same_array = self.tick_array_lower.key() == self.tick_array_upper.key();
+
+        if liquidity_delta != 0 {
+            self.tick_array_lower
+                .tick_mut(tick_lower, tick_spacing)?
+                .update(tick_lower, pool, liquidity_delta, true)?;
+
+            // Both bounds may lie in the same array, each copy must hold both
+            // updates since the upper one is written last
+            if same_array {
+                self.tick_array_upper.ticks = self.tick_array_lower.ticks;
+            }
+            self.tick_array_upper
+                .tick_mut(tick_upper, tick_spacing)?
+                .update(tick_upper, pool, liquidity_delta, false)?;
+            if same_array {
+                self.tick_array_lower.ticks = self.tick_array_upper.ticks;
+            }
+        }
+
+        let lower = self.tick_array_lower.tick(tick_lower, tick_spacing)?;
+        let upper = self.tick_array_upper.tick(tick_upper, tick_spacing)?;
+        let fee_growth_inside_a = clmm_math::fee_growth_inside(
+            pool.tick_current,
+            tick_lower,
+            lower.fee_growth_outside_a_x64,
+            tick_upper,
+            upper.fee_growth_outside_a_x64,
+            pool.fee_growth_global_a_x64,
+        );
+        let fee_growth_inside_b = clmm_math::fee_growth_inside(
+            pool.tick_current,
+            tick_lower,
+            lower.fee_growth_outside_b_x64,
+            tick_upper,
+            upper.fee_growth_outside_b_x64,
+            pool.fee_growth_global_b_x64,
+        );
+        position.update_fees(fee_growth_inside_a, fee_growth_inside_b)?;
+
+        let apply = |liquidity: u128| -> Result<u128> {
+            liquidity
+                .checked_add_signed(liquidity_delta)
+                .ok_or(error!(FTRXSwapError::MathOverflow))
+        };
+        position.liquidity = apply(position.liquidity)?;
+        if (tick_lower..tick_upper).contains(&pool.tick_current) {
+            pool.liquidity = apply(pool.liquidity)?;
+        }
+
+        Ok(())
+    }
+
+    /// Token amounts backing `liquidity` on the position range
+    fn position_amounts(&self, liquidity: u128, round_up: bool) -> Result<(u64, u64)> {
+        clmm_math::liquidity_amounts(
+            self.pool.sqrt_price_x64,
+            clmm_math::sqrt_price_at_tick(self.position.tick_lower)?,
+            clmm_math::sqrt_price_at_tick(self.position.tick_upper)?,
+            liquidity,
+            round_up,
+        )
+    }
+
+    fn transfer_to_vault(&self, side_a: bool, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let (owner_account, vault, mint, token_program) = if side_a {
+            (&self.owner_account_a, &self.pool_account_a, &self.mint_a, &self.token_program_a)
+        } else {
+            (&self.owner_account_b, &self.pool_account_b, &self.mint_b, &self.token_program_b)
+        };
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from: owner_account.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: vault.to_account_info(),
+                    authority: self.owner.to_account_info(),
+                },
+            ),
+            amount,
+            mint.decimals,
+        )
+    }
+
+    fn transfer_from_vault(&self, side_a: bool, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let (owner_account, vault, mint, token_program) = if side_a {
+            (&self.owner_account_a, &self.pool_account_a, &self.mint_a, &self.token_program_a)
+        } else {
+            (&self.owner_account_b, &self.pool_account_b, &self.mint_b, &self.token_program_b)
+        };
+
+        let actual_pool = &self.pool;
+        let lp_fee_seed = actual_pool.lp_fee_seed.to_le_bytes();
+
+        let authority_seeds = &[
+            actual_pool.mint_a.as_ref(),
+            actual_pool.mint_b.as_ref(),
+            actual_pool.admin_seed.as_ref(),
+            lp_fee_seed.as_ref(),
+            &[actual_pool.pool_bump],
+        ];
+        let signer_seeds = &[&authority_seeds[..]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from: vault.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: owner_account.to_account_info(),
+                    authority: actual_pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            mint.decimals,
+        )
+    }
+}
+
+/// Accounts of increase_liquidity, decrease_liquidity and collect_position_fees
+#[derive(Accounts)]
+pub struct ModifyPosition<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        constraint = !pool.locked @ FTRXSwapError::PoolLocked,
+        constraint = pool.curve == CURVE_CONCENTRATED @ FTRXSwapError::UnsupportedCurve,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        constraint = !amm.paused @ FTRXSwapError::AmmPaused,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_ref(),
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+            &position.tick_lower.to_le_bytes(),
+            &position.tick_upper.to_le_bytes(),
+        ],
+        bump = position.bump,
+        has_one = pool,
+        has_one = owner,
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    /// Tick arrays holding the lower and upper ticks of the position, possibly the same
+    #[account(
+        mut,
+        seeds = [
+            TICK_ARRAY_SEED.as_ref(),
+            pool.key().as_ref(),
+            &tick_array_lower.start_tick_index.to_le_bytes(),
+        ],
+        bump = tick_array_lower.bump,
+    )]
+    pub tick_array_lower: Box<Account<'info, TickArray>>,
+
+    #[account(
+        mut,
+        seeds = [
+            TICK_ARRAY_SEED.as_ref(),
+            pool.key().as_ref(),
+            &tick_array_upper.start_tick_index.to_le_bytes(),
+        ],
+        bump = tick_array_upper.bump,
+    )]
+    pub tick_array_upper: Box<Account<'info, TickArray>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_a,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_a,
+    )]
+    pub owner_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_b,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_b,
+    )]
+    pub owner_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Owner of the position, paying for its token accounts if needed
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    /// Token programs of mints A and B, legacy or Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,390 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    clmm_math,
+    constants::{AMM_SEED, CREATOR_SEED, CURVE_CONCENTRATED, MAX_TICK, MIN_TICK, PAUSE_SWAP, TREASURY_SEED},
+    errors::FTRXSwapError,
+    events::Swap,
+    state::{SimpleAmm, SimplePool, TickArray},
+    token_extensions,
+};
+
+// Next usable tick the price moves to, initialized or at the edge of the
+// loaded arrays, with the array and slot of an initialized one
+fn next_tick(
+    tick_arrays: &[Account<TickArray>],
+    tick_current: i32,
+    tick_spacing: u16,
+    a_to_b: bool,
+) -> Result<(i32, Option<(usize, usize)>)> {
+    let spacing = tick_spacing as i32;
+    let mut candidate = tick_current.div_euclid(spacing) * spacing;
+    if !a_to_b {
+        candidate += spacing;
+    }
+
+    let mut last_covered = None;
+    while (MIN_TICK..=MAX_TICK).contains(&candidate) {
+        let Some(index) = tick_arrays.iter().position(|array| array.contains(candidate, tick_spacing)) else {
+            break;
+        };
+        let offset = tick_arrays[index].offset(candidate, tick_spacing)?;
+        if tick_arrays[index].ticks[offset].initialized {
+            return Ok((candidate, Some((index, offset))));
+        }
+        last_covered = Some(candidate);
+        candidate = if a_to_b { candidate - spacing } else { candidate + spacing };
+    }
+
+    // Without any initialized tick the price stops at the last loaded one
+    last_covered
+        .map(|tick| (tick, None))
+        .ok_or(error!(FTRXSwapError::InvalidTickArray))
+}
+
+/// Swap of a given input on a concentrated pool, selling token A if a_to_b
+///
+/// The tick arrays the price moves through are passed as remaining accounts,
+/// the one holding the current tick first, then the next ones in the swap
+/// direction
+pub fn concentrated_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, ConcentratedSwap<'info>>,
+    a_to_b: bool,
+    input_amount: u64,
+    min_output_amount: u64,
+) -> Result<()> {
+
+    // Load the tick arrays and check that they follow each other
+    let tick_spacing = ctx.accounts.pool.tick_spacing;
+    let span = TickArray::span(tick_spacing);
+    let mut tick_arrays: Vec<Account<'info, TickArray>> = Vec::with_capacity(ctx.remaining_accounts.len());
+    for account in ctx.remaining_accounts.iter() {
+        let tick_array = Account::<TickArray>::try_from(account)?;
+        let expected_start = match tick_arrays.last() {
+            None => TickArray::start_index_for(ctx.accounts.pool.tick_current, tick_spacing),
+            Some(previous) if a_to_b => previous.start_tick_index - span,
+            Some(previous) => previous.start_tick_index + span,
+        };
+        if tick_array.pool != ctx.accounts.pool.key() || tick_array.start_tick_index != expected_start || !account.is_writable {
+            return err!(FTRXSwapError::InvalidTickArray);
+        }
+        tick_arrays.push(tick_array);
+    }
+
+    // Prevent selling assets the trader does not own
+    let accounts = &ctx.accounts;
+    let trader_in = if a_to_b { &accounts.trader_account_a } else { &accounts.trader_account_b };
+    let input = input_amount.min(trader_in.amount);
+
+    let mint_in = if a_to_b { &accounts.mint_a } else { &accounts.mint_b };
+
+    // Transfer fee mints credit less than what was sent, price what arrives
+    let received_input = input - token_extensions::transfer_fee(&mint_in.to_account_info(), input)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let fee_rate = pool.lp_fee as u64 + pool.protocol_fee as u64;
+
+    let mut remaining = received_input;
+    let mut output: u64 = 0;
+    let mut lp_fee: u64 = 0;
+    let mut protocol_fee: u64 = 0;
+    while remaining > 0 {
+        let (tick_next, initialized) = next_tick(&tick_arrays, pool.tick_current, tick_spacing, a_to_b)
+            .map_err(|_| error!(FTRXSwapError::InsufficientLiquidity))?;
+        let sqrt_target = clmm_math::sqrt_price_at_tick(tick_next)?;
+
+        let step = clmm_math::compute_swap_step(pool.sqrt_price_x64, sqrt_target, pool.liquidity, remaining, fee_rate, a_to_b)?;
+        remaining -= step.amount_in + step.fee_amount;
+        output = output.checked_add(step.amount_out).ok_or(FTRXSwapError::MathOverflow)?;
+
+        // The protocol share of the fee goes to the treasury, the rest to
+        // the liquidity in range, or to the treasury when there is none
+        let step_protocol_fee = (step.fee_amount as u128 * pool.protocol_fee as u128)
+            .checked_div(fee_rate as u128)
+            .unwrap_or(0) as u64;
+        let step_lp_fee = step.fee_amount - step_protocol_fee;
+        match ((step_lp_fee as u128) << 64).checked_div(pool.liquidity) {
+            Some(growth) => {
+                if a_to_b {
+                    pool.fee_growth_global_a_x64 = pool.fee_growth_global_a_x64.wrapping_add(growth);
+                } else {
+                    pool.fee_growth_global_b_x64 = pool.fee_growth_global_b_x64.wrapping_add(growth);
+                }
+                lp_fee += step_lp_fee;
+                protocol_fee += step_protocol_fee;
+            }
+            None => protocol_fee += step.fee_amount,
+        }
+
+        pool.sqrt_price_x64 = step.sqrt_price_next;
+        if step.sqrt_price_next == sqrt_target {
+            if let Some((index, offset)) = initialized {
+                let liquidity_net = tick_arrays[index].ticks[offset]
+                    .cross(pool.fee_growth_global_a_x64, pool.fee_growth_global_b_x64);
+                let liquidity_delta = if a_to_b { -liquidity_net } else { liquidity_net };
+                pool.liquidity = pool.liquidity
+                    .checked_add_signed(liquidity_delta)
+                    .ok_or(FTRXSwapError::MathOverflow)?;
+            }
+            pool.tick_current = if a_to_b { tick_next - 1 } else { tick_next };
+        } else {
+            pool.tick_current = clmm_math::tick_at_sqrt_price(step.sqrt_price_next)?;
+        }
+    }
+
+    for tick_array in tick_arrays.iter() {
+        tick_array.exit(ctx.program_id)?;
+    }
+
+    let accounts = &ctx.accounts;
+    let (trader_in, trader_out, vault_in, vault_out, treasury_in, creator_in, mint_in, mint_out, program_in, program_out) = if a_to_b {
+        (&accounts.trader_account_a, &accounts.trader_account_b, &accounts.pool_account_a, &accounts.pool_account_b,
+            &accounts.treasury_mint_a, &accounts.creator_vault_a, &accounts.mint_a, &accounts.mint_b, &accounts.token_program_a, &accounts.token_program_b)
+    } else {
+        (&accounts.trader_account_b, &accounts.trader_account_a, &accounts.pool_account_b, &accounts.pool_account_a,
+            &accounts.treasury_mint_b, &accounts.creator_vault_b, &accounts.mint_b, &accounts.mint_a, &accounts.token_program_b, &accounts.token_program_a)
+    };
+
+    // Slippage is checked on what the trader ends up with
+    let received_output = output - token_extensions::transfer_fee(&mint_out.to_account_info(), output)?;
+    if received_output < min_output_amount {
+        return err!(FTRXSwapError::OutputTooSmall);
+    }
+
+    // Transfer tokens to the pool
+    token_interface::transfer_checked(
+        CpiContext::new(
+            program_in.to_account_info(),
+            TransferChecked {
+                from: trader_in.to_account_info(),
+                mint: mint_in.to_account_info(),
+                to: vault_in.to_account_info(),
+                authority: accounts.payer.to_account_info(),
+            },
+        ),
+        input,
+        mint_in.decimals,
+    )?;
+
+    let actual_pool = &accounts.pool;
+    let lp_fee_seed = actual_pool.lp_fee_seed.to_le_bytes();
+
+    let authority_seeds = &[
+        actual_pool.mint_a.as_ref(),
+        actual_pool.mint_b.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
+        &[actual_pool.pool_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            program_out.to_account_info(),
+            TransferChecked {
+                from: vault_out.to_account_info(),
+                mint: mint_out.to_account_info(),
+                to: trader_out.to_account_info(),
+                authority: accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        output,
+        mint_out.decimals,
+    )?;
+
+    let creator_fee = actual_pool.creator_fee(protocol_fee);
+    let destinations = [
+        (treasury_in.to_account_info(), protocol_fee - creator_fee),
+        (creator_in.to_account_info(), creator_fee),
+    ];
+    for (destination, amount) in destinations {
+        if amount == 0 {
+            continue;
+        }
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                program_in.to_account_info(),
+                TransferChecked {
+                    from: vault_in.to_account_info(),
+                    mint: mint_in.to_account_info(),
+                    to: destination,
+                    authority: accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            mint_in.decimals,
+        )?;
+    }
+
+    // Reload accounts because of the CPIs
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+
+    emit!(Swap {
+        pool: ctx.accounts.pool.key(),
+        trader: ctx.accounts.payer.key(),
+        swap_a: a_to_b,
+        input: received_input,
+        output,
+        lp_fee,
+        protocol_fee,
+        reserve_a: ctx.accounts.pool_account_a.amount,
+        reserve_b: ctx.accounts.pool_account_b.amount,
+        lp_fee_rate: ctx.accounts.pool.lp_fee,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ConcentratedSwap<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        constraint = !pool.is_paused(PAUSE_SWAP) @ FTRXSwapError::PoolPaused,
+        constraint = !pool.locked @ FTRXSwapError::PoolLocked,
+        constraint = pool.curve == CURVE_CONCENTRATED @ FTRXSwapError::UnsupportedCurve,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        constraint = !amm.paused @ FTRXSwapError::AmmPaused,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program_a,
+    )]
+    pub trader_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program_b,
+    )]
+    pub trader_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vaults accruing the creator share of the protocol fees
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    /// Token programs of mints A and B, legacy or Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{AMM_SEED, FEE_TIER_SEED},
+    errors::*,
+    events::FeeTierUpdated,
+    state::{FeeTier, SimpleAmm},
+};
+
+pub fn create_fee_tier(ctx: Context<CreateFeeTier>, lp_fee: u16, protocol_fee: u16, creator_fee_share: u16) -> Result<()> {
+    ctx.accounts.amm.validate_fee_tier(lp_fee, protocol_fee, creator_fee_share)?;
+
+    let fee_tier = &mut ctx.accounts.fee_tier;
+    fee_tier.amm = ctx.accounts.amm.key();
+    fee_tier.lp_fee = lp_fee;
+    fee_tier.protocol_fee = protocol_fee;
+    fee_tier.creator_fee_share = creator_fee_share;
+    fee_tier.enabled = true;
+    fee_tier.bump = *ctx.bumps.get("fee_tier").unwrap();
+
+    emit!(FeeTierUpdated {
+        fee_tier: fee_tier.key(),
+        lp_fee,
+        protocol_fee,
+        creator_fee_share,
+        enabled: true,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(lp_fee: u16)]
+pub struct CreateFeeTier<'info> {
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        has_one = admin @ FTRXSwapError::InvalidAdmin,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = FeeTier::LEN,
+        seeds = [
+            FEE_TIER_SEED.as_bytes(),
+            &lp_fee.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub fee_tier: Account<'info, FeeTier>,
+
+    pub admin: Signer<'info>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    pub system_program: Program<'info, System>,
+}
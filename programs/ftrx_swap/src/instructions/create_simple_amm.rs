@@ -1,28 +1,39 @@
 use anchor_lang::prelude::*;
 
-use crate::{errors::*, state::SimpleAmm};
+use crate::{
+    constants::AMM_SEED,
+    errors::*,
+    program::FtrxSwap,
+    state::SimpleAmm,
+};
 
-pub fn create_amm(ctx: Context<CreateAmm>, id: Pubkey, fee: u16) -> Result<()> {
+pub fn create_amm(
+    ctx: Context<CreateAmm>,
+    min_lp_fee: u16,
+    max_lp_fee: u16,
+    max_protocol_fee: u16,
+) -> Result<()> {
     let amm = &mut ctx.accounts.amm;
-    amm.id = id;
     amm.admin = ctx.accounts.admin.key();
-    amm.fee = fee;
+    amm.paused = false;
+    amm.min_lp_fee = min_lp_fee;
+    amm.max_lp_fee = max_lp_fee;
+    amm.max_protocol_fee = max_protocol_fee;
+    amm.bump = *ctx.bumps.get("amm").unwrap();
 
-    Ok(())
+    amm.validate_fees()
 }
 
 #[derive(Accounts)]
-#[instruction(id: Pubkey, fee: u16)]
 pub struct CreateAmm<'info> {
     #[account(
         init,
         payer = payer,
         space = SimpleAmm::LEN,
         seeds = [
-            id.as_ref()
+            AMM_SEED.as_ref()
         ],
         bump,
-        constraint = fee < 10000 @ FTRXSwapError::InvalidFee,
     )]
     pub amm: Account<'info, SimpleAmm>,
 
@@ -30,10 +41,17 @@ pub struct CreateAmm<'info> {
     /// CHECK: Read only, delegatable creation
     pub admin: AccountInfo<'info>,
 
+    /// The config is a singleton, only the upgrade authority can create it
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, FtrxSwap>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(payer.key()) @ FTRXSwapError::InvalidAdmin)]
+    pub program_data: Account<'info, ProgramData>,
+
     /// The account paying for all rents
     #[account(mut)]
     pub payer: Signer<'info>,
 
     /// Solana ecosystem accounts
     pub system_program: Program<'info, System>,
-}
\ No newline at end of file
+}
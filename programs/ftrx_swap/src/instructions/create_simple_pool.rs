@@ -1,22 +1,33 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    metadata::{self, CreateMetadataAccountsV3, Metadata},
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
+use mpl_token_metadata::state::DataV2;
 
 use crate::{
-    constants::{AUTHORITY_SEED, LIQUIDITY_SEED,TREASURY_SEED},
+    clmm_math,
+    constants::{AMM_SEED, CREATOR_SEED, CURVE_CONCENTRATED, CURVE_STABLE, CURVE_WEIGHTED, FEE_TIER_SEED, LIQUIDITY_SEED, LP_SYMBOL, MAX_AMP, METADATA_SEED, MAX_TICK_SPACING, MIN_AMP, MIN_WEIGHT, OBSERVATION_SEED, TREASURY_SEED, WEIGHT_MULTIPLIER},
     errors::*,
-    state::{SimplePool},
+    events::PoolCreated,
+    state::{FeeTier, Observation, PoolObservations, SimpleAmm, SimplePool},
+    token_extensions,
+    token_metadata,
 };
 
 pub fn create_pool(ctx: Context<CreatePool>,lp_fee:u16,bump_pool:u8,bump_vault_a:u8,bump_vault_b:u8,bump_treas_a:u8,bump_treas_b:u8) -> Result<()> {
+    token_extensions::validate_mint_extensions(&ctx.accounts.mint_a.to_account_info())?;
+    token_extensions::validate_mint_extensions(&ctx.accounts.mint_b.to_account_info())?;
+
     let pool = &mut ctx.accounts.pool;
     pool.pool_bump=bump_pool;
     pool.vault_a_bump=bump_vault_a;
     pool.vault_b_bump=bump_vault_b;
     pool.treas_a_bump=bump_treas_a;
     pool.treas_b_bump=bump_treas_b;
+    pool.amm = ctx.accounts.amm.key();
     pool.creator = ctx.accounts.payer.key();
     pool.mint_a = ctx.accounts.mint_a.key();
     pool.mint_b = ctx.accounts.mint_b.key();
@@ -25,8 +36,159 @@ pub fn create_pool(ctx: Context<CreatePool>,lp_fee:u16,bump_pool:u8,bump_vault_a
     pool.treasury_mint_a = ctx.accounts.treasury_mint_a.key();
     pool.treasury_mint_b = ctx.accounts.treasury_mint_b.key();
     pool.admin = ctx.accounts.admin.key();
+    pool.admin_seed = ctx.accounts.admin.key();
     pool.lp_fee = lp_fee;
-    pool.protocol_fee = 1;
+    pool.lp_fee_seed = lp_fee;
+    pool.protocol_fee = ctx.accounts.fee_tier.protocol_fee;
+    pool.creator_fee_share = ctx.accounts.fee_tier.creator_fee_share;
+    pool.creator_vault_a = ctx.accounts.creator_vault_a.key();
+    pool.creator_vault_b = ctx.accounts.creator_vault_b.key();
+    pool.last_update_timestamp = Clock::get()?.unix_timestamp;
+
+    // First observation at creation so TWAP windows can start right away
+    let observations = &mut ctx.accounts.observations;
+    observations.pool = pool.key();
+    observations.bump = *ctx.bumps.get("observations").unwrap();
+    observations.index = 0;
+    observations.observations[0] = Observation {
+        timestamp: pool.last_update_timestamp,
+        price_a_cumulative: 0,
+        price_b_cumulative: 0,
+    };
+
+    // Name and symbol for wallets, from the symbols of the mints when they
+    // have metadata, the URI is left for the admin to set
+    let lp_fee_seed = lp_fee.to_le_bytes();
+    let authority_seeds = &[
+        pool.mint_a.as_ref(),
+        pool.mint_b.as_ref(),
+        pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
+        &[pool.pool_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    metadata::create_metadata_accounts_v3(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_metadata_program.to_account_info(),
+            CreateMetadataAccountsV3 {
+                metadata: ctx.accounts.lp_metadata.to_account_info(),
+                mint: ctx.accounts.mint_liquidity.to_account_info(),
+                mint_authority: pool.to_account_info(),
+                payer: ctx.accounts.payer.to_account_info(),
+                update_authority: pool.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        DataV2 {
+            name: pool.lp_token_name(
+                token_metadata::symbol(&ctx.accounts.metadata_a),
+                token_metadata::symbol(&ctx.accounts.metadata_b),
+            ),
+            symbol: LP_SYMBOL.to_string(),
+            uri: String::new(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        },
+        true,
+        true,
+        None,
+    )?;
+
+    emit!(PoolCreated {
+        pool: pool.key(),
+        amm: pool.amm,
+        creator: pool.creator,
+        admin: pool.admin,
+        mint_a: pool.mint_a,
+        mint_b: pool.mint_b,
+        mint_liquidity: ctx.accounts.mint_liquidity.key(),
+        lp_fee,
+        protocol_fee: pool.protocol_fee,
+    });
+
+    Ok(())
+}
+
+/// Same as create_pool, for a pool of pegged assets priced on the StableSwap
+/// curve with amplification coefficient amp
+#[allow(clippy::too_many_arguments)]
+pub fn create_stable_pool<'info>(ctx: Context<'_, '_, '_, 'info, CreatePool<'info>>,lp_fee:u16,amp:u64,bump_pool:u8,bump_vault_a:u8,bump_vault_b:u8,bump_treas_a:u8,bump_treas_b:u8) -> Result<()> {
+    if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+        return err!(FTRXSwapError::InvalidAmp);
+    }
+
+    create_pool(
+        Context::new(ctx.program_id, &mut *ctx.accounts, ctx.remaining_accounts, ctx.bumps.clone()),
+        lp_fee,
+        bump_pool,
+        bump_vault_a,
+        bump_vault_b,
+        bump_treas_a,
+        bump_treas_b,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.curve = CURVE_STABLE;
+    pool.amp = amp;
+
+    Ok(())
+}
+
+/// Same as create_pool, for a pool priced on the weighted product
+/// x^weight_a * y^weight_b, token B getting the remaining weight
+#[allow(clippy::too_many_arguments)]
+pub fn create_weighted_pool<'info>(ctx: Context<'_, '_, '_, 'info, CreatePool<'info>>,lp_fee:u16,weight_a:u64,bump_pool:u8,bump_vault_a:u8,bump_vault_b:u8,bump_treas_a:u8,bump_treas_b:u8) -> Result<()> {
+    if !(MIN_WEIGHT..=WEIGHT_MULTIPLIER - MIN_WEIGHT).contains(&weight_a) {
+        return err!(FTRXSwapError::InvalidWeights);
+    }
+
+    create_pool(
+        Context::new(ctx.program_id, &mut *ctx.accounts, ctx.remaining_accounts, ctx.bumps.clone()),
+        lp_fee,
+        bump_pool,
+        bump_vault_a,
+        bump_vault_b,
+        bump_treas_a,
+        bump_treas_b,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.curve = CURVE_WEIGHTED;
+    pool.weight_a = weight_a;
+    pool.weight_b = WEIGHT_MULTIPLIER - weight_a;
+
+    Ok(())
+}
+
+/// Same as create_pool, for a pool of concentrated liquidity positions
+/// starting at the Q64.64 sqrt price sqrt_price_x64, the LP mint staying unused
+#[allow(clippy::too_many_arguments)]
+pub fn create_concentrated_pool<'info>(ctx: Context<'_, '_, '_, 'info, CreatePool<'info>>,lp_fee:u16,tick_spacing:u16,sqrt_price_x64:u128,bump_pool:u8,bump_vault_a:u8,bump_vault_b:u8,bump_treas_a:u8,bump_treas_b:u8) -> Result<()> {
+    if tick_spacing == 0 || tick_spacing > MAX_TICK_SPACING {
+        return err!(FTRXSwapError::InvalidTickSpacing);
+    }
+    let tick_current = clmm_math::tick_at_sqrt_price(sqrt_price_x64)?;
+
+    create_pool(
+        Context::new(ctx.program_id, &mut *ctx.accounts, ctx.remaining_accounts, ctx.bumps.clone()),
+        lp_fee,
+        bump_pool,
+        bump_vault_a,
+        bump_vault_b,
+        bump_treas_a,
+        bump_treas_b,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.curve = CURVE_CONCENTRATED;
+    pool.tick_spacing = tick_spacing;
+    pool.sqrt_price_x64 = sqrt_price_x64;
+    pool.tick_current = tick_current;
 
     Ok(())
 }
@@ -47,13 +209,48 @@ pub struct CreatePool<'info> {
         ],
         bump,
         constraint = mint_a.key() < mint_b.key() @ FTRXSwapError::InvalidMint,
-        constraint = lp_fee < 2000 @ FTRXSwapError::InvalidFee,
       
        
     )]
     pub pool: Account<'info, SimplePool>,
 
-    /// CHECK: Read only authority
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        constraint = !amm.paused @ FTRXSwapError::AmmPaused,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    /// Tier of lp_fee, only the fees listed by the AMM admin make pools
+    #[account(
+        seeds = [
+            FEE_TIER_SEED.as_bytes(),
+            &lp_fee.to_le_bytes(),
+        ],
+        bump = fee_tier.bump,
+        has_one = amm,
+        constraint = fee_tier.enabled @ FTRXSwapError::FeeTierDisabled,
+    )]
+    pub fee_tier: Account<'info, FeeTier>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PoolObservations::LEN,
+        seeds = [
+            OBSERVATION_SEED.as_ref(),
+            pool.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub observations: Box<Account<'info, PoolObservations>>,
+
+    /// CHECK: Read only authority, pools are always seeded by the AMM admin
+    #[account(
+        address = amm.admin @ FTRXSwapError::InvalidAdmin,
+    )]
     pub admin: AccountInfo<'info>,
 
     #[account(
@@ -63,21 +260,67 @@ pub struct CreatePool<'info> {
             mint_a.key().as_ref(),
             mint_b.key().as_ref(),
             admin.key().as_ref(),
+            &lp_fee.to_le_bytes(),
             LIQUIDITY_SEED.as_ref(),
         ],
         bump,
-        mint::decimals = 6,
+        mint::decimals = ((mint_a.decimals as u16 + mint_b.decimals as u16) / 2) as u8,
         mint::authority = pool,
+        mint::token_program = token_program,
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Metadata of the LP mint, created by the token metadata program
+    #[account(
+        mut,
+        seeds = [
+            METADATA_SEED.as_bytes(),
+            token_metadata_program.key().as_ref(),
+            mint_liquidity.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub lp_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metadata of mint A, only read for its symbol and possibly empty
+    #[account(
+        seeds = [
+            METADATA_SEED.as_bytes(),
+            token_metadata_program.key().as_ref(),
+            mint_a.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
     )]
-    pub mint_liquidity: Box<Account<'info, Mint>>,
+    pub metadata_a: UncheckedAccount<'info>,
 
-    pub mint_a: Box<Account<'info, Mint>>,
+    /// CHECK: Metadata of mint B, only read for its symbol and possibly empty
+    #[account(
+        seeds = [
+            METADATA_SEED.as_bytes(),
+            token_metadata_program.key().as_ref(),
+            mint_b.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub metadata_b: UncheckedAccount<'info>,
 
-    pub mint_b: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(init,
         token::mint = mint_a,
         token::authority = pool,
+        token::token_program = token_program_a,
         seeds = [
         mint_a.key().as_ref(),
         pool.key().as_ref(),
@@ -85,11 +328,12 @@ pub struct CreatePool<'info> {
         bump,
         payer = payer
       )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(init,
         token::mint = mint_b,
         token::authority = pool,
+        token::token_program = token_program_b,
         seeds = [
         mint_b.key().as_ref(),
         pool.key().as_ref(),
@@ -97,7 +341,7 @@ pub struct CreatePool<'info> {
         bump,
         payer = payer
       )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
 
 
@@ -105,6 +349,7 @@ pub struct CreatePool<'info> {
     #[account(init,
         token::mint = mint_a,
         token::authority = pool,
+        token::token_program = token_program_a,
         seeds = [
         mint_a.key().as_ref(),
         pool.key().as_ref(),
@@ -114,11 +359,12 @@ pub struct CreatePool<'info> {
         bump,
         payer = payer
       )]
-    pub treasury_mint_a: Box<Account<'info, TokenAccount>>,
+    pub treasury_mint_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(init,
         token::mint = mint_b,
         token::authority = pool,
+        token::token_program = token_program_b,
         seeds = [
         mint_b.key().as_ref(),
         pool.key().as_ref(),
@@ -128,14 +374,51 @@ pub struct CreatePool<'info> {
         bump,
         payer = payer
       )]
-    pub treasury_mint_b: Box<Account<'info, TokenAccount>>,
+    pub treasury_mint_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vaults accruing the creator share of the protocol fees
+    #[account(init,
+        token::mint = mint_a,
+        token::authority = pool,
+        token::token_program = token_program_a,
+        seeds = [
+        mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        payer.key().as_ref(),
+        ],
+        bump,
+        payer = payer
+      )]
+    pub creator_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(init,
+        token::mint = mint_b,
+        token::authority = pool,
+        token::token_program = token_program_b,
+        seeds = [
+        mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        payer.key().as_ref(),
+        ],
+        bump,
+        payer = payer
+      )]
+    pub creator_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// The account paying for all rents
+    /// The account paying for all rents, creator of the pool
     #[account(mut)]
     pub payer: Signer<'info>,
 
     /// Solana ecosystem accounts
+    /// Token program of the LP mint, the legacy one that Metaplex metadata
+    /// supports, and of mints A and B, legacy or Token-2022
     pub token_program: Program<'info, Token>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
\ No newline at end of file
@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{
+    constants::CREATOR_SEED,
+    errors::FTRXSwapError,
+    events::CreatorFeesCollected,
+    state::SimplePool,
+};
+
+pub fn creator_collect_fees(ctx: Context<CreatorCollectFees>, amount_a: u64, amount_b: u64) -> Result<()> {
+
+    let actual_pool=&ctx.accounts.pool;
+    let lp_fee_seed=actual_pool.lp_fee_seed.to_le_bytes();
+
+    let authority_seeds = &[
+        actual_pool.mint_a.as_ref(),
+        actual_pool.mint_b.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
+        &[actual_pool.pool_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    // Requested amounts are capped by the creator vault balances, u64::MAX sweeps everything
+    // A side with nothing to send is skipped and does not need a destination account
+    let amount_a = amount_a.min(ctx.accounts.creator_vault_a.amount);
+    let amount_b = amount_b.min(ctx.accounts.creator_vault_b.amount);
+
+    if amount_a > 0 {
+        let destination_a = ctx.accounts.depositor_account_a.as_ref().ok_or(FTRXSwapError::MissingDestination)?;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_a.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.creator_vault_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
+                    to: destination_a.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_a,
+            ctx.accounts.mint_a.decimals,
+        )?;
+    }
+
+    if amount_b > 0 {
+        let destination_b = ctx.accounts.depositor_account_b.as_ref().ok_or(FTRXSwapError::MissingDestination)?;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_b.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.creator_vault_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
+                    to: destination_b.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount_b,
+            ctx.accounts.mint_b.decimals,
+        )?;
+    }
+
+    emit!(CreatorFeesCollected {
+        pool: ctx.accounts.pool.key(),
+        creator: ctx.accounts.creator.key(),
+        amount_a,
+        amount_b,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreatorCollectFees<'info> {
+
+    #[account(
+        seeds = [
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = creator @ FTRXSwapError::InvalidCreator,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    /// Any token account of mint A
+    #[account(
+        mut,
+        token::mint = mint_a,
+    )]
+    pub depositor_account_a: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Any token account of mint B
+    #[account(
+        mut,
+        token::mint = mint_b,
+    )]
+    pub depositor_account_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Creator of the pool
+    pub creator: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    /// Token programs of mints A and B, legacy or Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
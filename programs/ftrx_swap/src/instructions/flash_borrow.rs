@@ -0,0 +1,173 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
+        sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+    },
+    Discriminator,
+};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{
+    constants::{AMM_SEED, CURVE_CONCENTRATED},
+    errors::FTRXSwapError,
+    state::{SimpleAmm, SimplePool},
+};
+
+pub fn flash_borrow(ctx: Context<FlashBorrow>, borrow_a: bool, amount: u64) -> Result<()> {
+
+    // Called through a CPI the repayment could not be found among the
+    // transaction instructions
+    if get_stack_height() > TRANSACTION_LEVEL_STACK_HEIGHT {
+        return err!(FTRXSwapError::InvalidFlashLoan);
+    }
+
+    // A flash_repay for this pool must follow in the same transaction
+    let instructions = ctx.accounts.instructions.to_account_info();
+    let current_index = load_current_index_checked(&instructions)? as usize;
+    let mut index = current_index + 1;
+    let mut repaid = false;
+    while let Ok(instruction) = load_instruction_at_checked(index, &instructions) {
+        if instruction.program_id == crate::ID
+            && instruction.data.starts_with(&crate::instruction::FlashRepay::DISCRIMINATOR)
+            && instruction.accounts.first().map(|meta| meta.pubkey) == Some(ctx.accounts.pool.key())
+        {
+            repaid = true;
+            break;
+        }
+        index += 1;
+    }
+    if !repaid {
+        return err!(FTRXSwapError::FlashLoanNotRepaid);
+    }
+
+    let accounts = &ctx.accounts;
+    let (vault, mint, token_program) = if borrow_a {
+        (&accounts.pool_account_a, &accounts.mint_a, &accounts.token_program_a)
+    } else {
+        (&accounts.pool_account_b, &accounts.mint_b, &accounts.token_program_b)
+    };
+
+    if amount == 0 || amount >= vault.amount {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+    if accounts.borrower_account.mint != mint.key() {
+        return err!(FTRXSwapError::InvalidMint);
+    }
+    let reserve_before = vault.amount;
+
+    let actual_pool=&accounts.pool;
+    let lp_fee_seed=actual_pool.lp_fee_seed.to_le_bytes();
+
+    let authority_seeds = &[
+        actual_pool.mint_a.as_ref(),
+        actual_pool.mint_b.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
+        &[actual_pool.pool_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: accounts.borrower_account.to_account_info(),
+                authority: accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        mint.decimals,
+    )?;
+
+    // Lock the pool until flash_repay checks the vault
+    let pool = &mut ctx.accounts.pool;
+    pool.locked = true;
+    pool.flash_loan_a = borrow_a;
+    pool.flash_loan_amount = amount;
+    pool.flash_loan_reserve_before = reserve_before;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FlashBorrow<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        constraint = !pool.locked @ FTRXSwapError::PoolLocked,
+        constraint = pool.curve != CURVE_CONCENTRATED @ FTRXSwapError::UnsupportedCurve,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        constraint = !amm.paused @ FTRXSwapError::AmmPaused,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token account receiving the loan, of mint a if borrow_a
+    #[account(mut)]
+    pub borrower_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub borrower: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, used to find the repayment
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+
+    /// Solana ecosystem accounts
+    /// Token programs of mints A and B, legacy or Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
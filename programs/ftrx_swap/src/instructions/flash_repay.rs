@@ -0,0 +1,253 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{
+    constants::TREASURY_SEED,
+    constants::CREATOR_SEED,
+    errors::FTRXSwapError,
+    events::FlashLoan,
+    math,
+    state::SimplePool,
+    token_extensions,
+};
+
+pub fn flash_repay(ctx: Context<FlashRepay>) -> Result<()> {
+
+    let accounts = &ctx.accounts;
+    let actual_pool=&accounts.pool;
+    // Only an outstanding flash_borrow can be repaid, not a flash swap
+    if !actual_pool.locked || actual_pool.flash_swap_locked || actual_pool.flash_loan_amount == 0 {
+        return err!(FTRXSwapError::InvalidFlashLoan);
+    }
+
+    let borrow_a = actual_pool.flash_loan_a;
+    let amount = actual_pool.flash_loan_amount;
+    let reserve_before = actual_pool.flash_loan_reserve_before;
+
+    // Same split as the swap fees, the LP part stays in the vault
+    let (lp_fee, protocol_fee) = math::flash_loan_fees(amount, actual_pool.lp_fee, actual_pool.protocol_fee)?;
+    let repaid_amount = amount
+        .checked_add(lp_fee)
+        .and_then(|value| value.checked_add(protocol_fee))
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    let (vault, treasury, creator_vault, mint, token_program) = if borrow_a {
+        (&accounts.pool_account_a, &accounts.treasury_mint_a, &accounts.creator_vault_a, &accounts.mint_a, &accounts.token_program_a)
+    } else {
+        (&accounts.pool_account_b, &accounts.treasury_mint_b, &accounts.creator_vault_b, &accounts.mint_b, &accounts.token_program_b)
+    };
+    let creator_fee = actual_pool.creator_fee(protocol_fee);
+
+    // Transfer fee mints credit less than what was sent, the borrower sends
+    // enough for the vault to receive repaid_amount
+    let sent_amount = repaid_amount
+        .checked_add(token_extensions::inverse_transfer_fee(&mint.to_account_info(), repaid_amount)?)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    let lp_fee_seed=actual_pool.lp_fee_seed.to_le_bytes();
+
+    let authority_seeds = &[
+        actual_pool.mint_a.as_ref(),
+        actual_pool.mint_b.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
+        &[actual_pool.pool_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: accounts.repayer_account.to_account_info(),
+                mint: mint.to_account_info(),
+                to: vault.to_account_info(),
+                authority: accounts.borrower.to_account_info(),
+            },
+        ),
+        sent_amount,
+        mint.decimals,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: treasury.to_account_info(),
+                authority: accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        protocol_fee - creator_fee,
+        mint.decimals,
+    )?;
+
+    if creator_fee > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from: vault.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: creator_vault.to_account_info(),
+                    authority: accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            creator_fee,
+            mint.decimals,
+        )?;
+    }
+
+    // The vault must hold at least its balance before the loan plus the LP fee
+    // Reload accounts because of the CPIs
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    let reserve_after = if borrow_a { ctx.accounts.pool_account_a.amount } else { ctx.accounts.pool_account_b.amount };
+
+    let reserve_expected = reserve_before
+        .checked_add(lp_fee)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    if reserve_after < reserve_expected {
+        return err!(FTRXSwapError::FlashLoanNotRepaid);
+    }
+
+    let pool = &mut ctx.accounts.pool;
+    pool.locked = false;
+    pool.flash_loan_a = false;
+    pool.flash_loan_amount = 0;
+    pool.flash_loan_reserve_before = 0;
+
+    emit!(FlashLoan {
+        pool: pool.key(),
+        borrower: ctx.accounts.borrower.key(),
+        borrow_a,
+        amount,
+        lp_fee,
+        protocol_fee,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FlashRepay<'info> {
+
+    /// Must stay the first account, flash_borrow looks for it
+    #[account(
+        mut,
+        seeds = [
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vaults accruing the creator share of the protocol fees
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token account the loan and its fees are paid back from
+    #[account(
+        mut,
+        token::authority = borrower,
+    )]
+    pub repayer_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub borrower: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    /// Token programs of mints A and B, legacy or Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CURVE_CONCENTRATED, TICK_ARRAY_SEED},
+    errors::FTRXSwapError,
+    state::{SimplePool, TickArray},
+};
+
+pub fn initialize_tick_array(ctx: Context<InitializeTickArray>, start_tick_index: i32) -> Result<()> {
+    if !TickArray::is_valid_start(start_tick_index, ctx.accounts.pool.tick_spacing) {
+        return err!(FTRXSwapError::InvalidTickArray);
+    }
+
+    let tick_array = &mut ctx.accounts.tick_array;
+    tick_array.pool = ctx.accounts.pool.key();
+    tick_array.start_tick_index = start_tick_index;
+    tick_array.ticks = Default::default();
+    tick_array.bump = *ctx.bumps.get("tick_array").unwrap();
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(start_tick_index: i32)]
+pub struct InitializeTickArray<'info> {
+
+    #[account(
+        constraint = pool.curve == CURVE_CONCENTRATED @ FTRXSwapError::UnsupportedCurve,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = TickArray::LEN,
+        seeds = [
+            TICK_ARRAY_SEED.as_ref(),
+            pool.key().as_ref(),
+            &start_tick_index.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub tick_array: Box<Account<'info, TickArray>>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,192 @@
+use anchor_lang::{prelude::*, system_program, Discriminator};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    constants::{AMM_SEED, CREATOR_SEED, OBSERVATION_SEED},
+    errors::FTRXSwapError,
+    events::PoolMigrated,
+    state::{LegacySimplePool, Observation, PoolObservations, SimpleAmm, SimplePool},
+};
+
+pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+    let pool_info = ctx.accounts.pool.to_account_info();
+
+    let legacy = {
+        let data = pool_info.try_borrow_data()?;
+        if data.len() != LegacySimplePool::LEN || data[..8] != SimplePool::DISCRIMINATOR {
+            return err!(FTRXSwapError::PoolAlreadyMigrated);
+        }
+        LegacySimplePool::deserialize(&mut &data[8..])?
+    };
+
+    if legacy.mint_a != ctx.accounts.mint_a.key() || legacy.mint_b != ctx.accounts.mint_b.key() {
+        return err!(FTRXSwapError::InvalidMint);
+    }
+    if legacy.creator != ctx.accounts.creator.key() {
+        return err!(FTRXSwapError::InvalidCreator);
+    }
+
+    // Legacy pools were derived with their admin and LP fee, neither could change since
+    let pool_address = Pubkey::create_program_address(
+        &[
+            legacy.mint_a.as_ref(),
+            legacy.mint_b.as_ref(),
+            legacy.admin.as_ref(),
+            &legacy.lp_fee.to_le_bytes(),
+            &[legacy.pool_bump],
+        ],
+        ctx.program_id,
+    )
+    .map_err(|_| error!(ErrorCode::ConstraintSeeds))?;
+    if pool_address != pool_info.key() {
+        return err!(ErrorCode::ConstraintSeeds);
+    }
+
+    // The payer tops up the rent of the extended account
+    let rent = Rent::get()?.minimum_balance(SimplePool::LEN);
+    let missing_rent = rent.saturating_sub(pool_info.lamports());
+    if missing_rent > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: pool_info.clone(),
+                },
+            ),
+            missing_rent,
+        )?;
+    }
+    pool_info.realloc(SimplePool::LEN, true)?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let pool = SimplePool {
+        pool_bump: legacy.pool_bump,
+        vault_a_bump: legacy.vault_a_bump,
+        vault_b_bump: legacy.vault_b_bump,
+        treas_a_bump: legacy.treas_a_bump,
+        treas_b_bump: legacy.treas_b_bump,
+        creator: legacy.creator,
+        admin: legacy.admin,
+        lp_fee: legacy.lp_fee,
+        protocol_fee: legacy.protocol_fee,
+        mint_a: legacy.mint_a,
+        vault_mint_a: legacy.vault_mint_a,
+        treasury_mint_a: legacy.treasury_mint_a,
+        mint_b: legacy.mint_b,
+        vault_mint_b: legacy.vault_mint_b,
+        treasury_mint_b: legacy.treasury_mint_b,
+        amm: ctx.accounts.amm.key(),
+        admin_seed: legacy.admin,
+        lp_fee_seed: legacy.lp_fee,
+        last_update_timestamp: timestamp,
+        creator_vault_a: ctx.accounts.creator_vault_a.key(),
+        creator_vault_b: ctx.accounts.creator_vault_b.key(),
+        ..Default::default()
+    };
+    pool.try_serialize(&mut &mut pool_info.try_borrow_mut_data()?[..])?;
+
+    // First observation at migration, as for a new pool
+    let observations = &mut ctx.accounts.observations;
+    observations.pool = pool_info.key();
+    observations.bump = *ctx.bumps.get("observations").unwrap();
+    observations.index = 0;
+    observations.observations[0] = Observation {
+        timestamp,
+        price_a_cumulative: 0,
+        price_b_cumulative: 0,
+    };
+
+    emit!(PoolMigrated {
+        pool: pool_info.key(),
+        amm: pool.amm,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+
+    /// CHECK: SimplePool in the legacy layout, checked and rewritten by the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+    )]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PoolObservations::LEN,
+        seeds = [
+            OBSERVATION_SEED.as_ref(),
+            pool.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub observations: Box<Account<'info, PoolObservations>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Creator stored in the pool, checked by the handler
+    pub creator: UncheckedAccount<'info>,
+
+    /// Vaults accruing the creator share of the protocol fees, which stays
+    /// at zero for legacy pools
+    #[account(init,
+        token::mint = mint_a,
+        token::authority = pool,
+        token::token_program = token_program_a,
+        seeds = [
+        mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        creator.key().as_ref(),
+        ],
+        bump,
+        payer = payer
+      )]
+    pub creator_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(init,
+        token::mint = mint_b,
+        token::authority = pool,
+        token::token_program = token_program_b,
+        seeds = [
+        mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        creator.key().as_ref(),
+        ],
+        bump,
+        payer = payer
+      )]
+    pub creator_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Anyone can migrate a pool, paying for the new rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    /// Token programs of mints A and B
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
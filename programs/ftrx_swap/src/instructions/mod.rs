@@ -1,12 +1,61 @@
-
+mod create_simple_amm;
 mod create_simple_pool;
 mod simple_deposit_liquidity;
+mod simple_deposit_single_sided;
 mod simple_swap_exact_in;
+mod simple_swap_exact_out;
+mod simple_swap_routed;
+mod simple_flash_swap;
 mod simple_withdraw_liquidity;
+mod simple_withdraw_single_sided;
 mod admin_gets_treasury;
+mod creator_collect_fees;
+mod flash_borrow;
+mod flash_repay;
+mod set_pool_fees;
+mod set_pool_dynamic_fee;
+mod set_pool_paused_flags;
+mod propose_admin;
+mod accept_admin;
+mod cancel_admin_proposal;
+mod update_simple_amm;
+mod create_fee_tier;
+mod update_fee_tier;
+mod initialize_tick_array;
+mod open_position;
+mod concentrated_liquidity;
+mod close_position;
+mod concentrated_swap;
+mod update_lp_metadata_uri;
+mod migrate_pool;
 
+pub use create_simple_amm::*;
 pub use create_simple_pool::*;
 pub use simple_deposit_liquidity::*;
+pub use simple_deposit_single_sided::*;
 pub use simple_swap_exact_in::*;
+pub use simple_swap_exact_out::*;
+pub use simple_swap_routed::*;
+pub use simple_flash_swap::*;
 pub use simple_withdraw_liquidity::*;
-pub use admin_gets_treasury::*;
\ No newline at end of file
+pub use simple_withdraw_single_sided::*;
+pub use admin_gets_treasury::*;
+pub use creator_collect_fees::*;
+pub use flash_borrow::*;
+pub use flash_repay::*;
+pub use set_pool_fees::*;
+pub use set_pool_dynamic_fee::*;
+pub use set_pool_paused_flags::*;
+pub use propose_admin::*;
+pub use accept_admin::*;
+pub use cancel_admin_proposal::*;
+pub use update_simple_amm::*;
+pub use create_fee_tier::*;
+pub use update_fee_tier::*;
+pub use initialize_tick_array::*;
+pub use open_position::*;
+pub use concentrated_liquidity::*;
+pub use close_position::*;
+pub use concentrated_swap::*;
+pub use update_lp_metadata_uri::*;
+pub use migrate_pool::*;
\ No newline at end of file
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{CURVE_CONCENTRATED, MAX_TICK, MIN_TICK, POSITION_SEED},
+    errors::FTRXSwapError,
+    state::{Position, SimplePool},
+};
+
+pub fn open_position(ctx: Context<OpenPosition>, tick_lower: i32, tick_upper: i32) -> Result<()> {
+    let tick_spacing = ctx.accounts.pool.tick_spacing as i32;
+    if tick_lower >= tick_upper
+        || tick_lower < MIN_TICK
+        || tick_upper > MAX_TICK
+        || tick_lower % tick_spacing != 0
+        || tick_upper % tick_spacing != 0
+    {
+        return err!(FTRXSwapError::InvalidTickRange);
+    }
+
+    let position = &mut ctx.accounts.position;
+    position.pool = ctx.accounts.pool.key();
+    position.owner = ctx.accounts.owner.key();
+    position.tick_lower = tick_lower;
+    position.tick_upper = tick_upper;
+    position.bump = *ctx.bumps.get("position").unwrap();
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct OpenPosition<'info> {
+
+    #[account(
+        constraint = pool.curve == CURVE_CONCENTRATED @ FTRXSwapError::UnsupportedCurve,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = Position::LEN,
+        seeds = [
+            POSITION_SEED.as_ref(),
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+            &tick_lower.to_le_bytes(),
+            &tick_upper.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub position: Box<Account<'info, Position>>,
+
+    pub owner: Signer<'info>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::*,
+    events::PoolAdminProposed,
+    state::SimplePool,
+};
+
+pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    if new_admin == Pubkey::default() {
+        return err!(FTRXSwapError::InvalidAdmin);
+    }
+
+    // Nothing changes until the nominated key accepts
+    let pool = &mut ctx.accounts.pool;
+    pool.pending_admin = new_admin;
+
+    emit!(PoolAdminProposed {
+        pool: pool.key(),
+        admin: pool.admin,
+        pending_admin: new_admin,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = admin @ FTRXSwapError::InvalidAdmin,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{AMM_SEED, CURVE_CONCENTRATED, FEE_MULTIPLIER},
+    errors::*,
+    events::PoolDynamicFeeUpdated,
+    state::{SimpleAmm, SimplePool},
+};
+
+pub fn set_pool_dynamic_fee(ctx: Context<SetPoolDynamicFee>, dynamic_fee: bool, max_dynamic_lp_fee: u16) -> Result<()> {
+    let amm = &ctx.accounts.amm;
+    let pool = &mut ctx.accounts.pool;
+
+    // The fee ranges from the pool LP fee to a max within the AMM bounds
+    if dynamic_fee
+        && (max_dynamic_lp_fee < pool.lp_fee
+            || max_dynamic_lp_fee > amm.max_lp_fee
+            || max_dynamic_lp_fee as u64 + pool.protocol_fee as u64 >= FEE_MULTIPLIER)
+    {
+        return err!(FTRXSwapError::InvalidFee);
+    }
+
+    // The accumulator starts over from the next swap
+    pool.dynamic_fee = dynamic_fee;
+    pool.max_dynamic_lp_fee = max_dynamic_lp_fee;
+    pool.volatility_accumulator = 0;
+    pool.volatility_reference_price_x64 = 0;
+    pool.volatility_last_timestamp = Clock::get()?.unix_timestamp;
+
+    emit!(PoolDynamicFeeUpdated {
+        pool: pool.key(),
+        dynamic_fee,
+        max_dynamic_lp_fee,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPoolDynamicFee<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = admin @ FTRXSwapError::InvalidAdmin,
+        has_one = amm,
+        // Concentrated swaps always charge the static LP fee
+        constraint = pool.curve != CURVE_CONCENTRATED @ FTRXSwapError::UnsupportedCurve,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    pub admin: Signer<'info>,
+}
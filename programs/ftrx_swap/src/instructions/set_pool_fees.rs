@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{AMM_SEED, FEE_MULTIPLIER},
+    errors::*,
+    events::PoolFeesUpdated,
+    state::{SimpleAmm, SimplePool},
+};
+
+pub fn set_pool_fees(ctx: Context<SetPoolFees>, lp_fee: u16, protocol_fee: u16) -> Result<()> {
+    let amm = &ctx.accounts.amm;
+    let pool = &mut ctx.accounts.pool;
+
+    // Same bounds as at pool creation, and the fees can never eat the whole
+    // input, even at the highest dynamic LP fee
+    let highest_lp_fee = if pool.dynamic_fee { lp_fee.max(pool.max_dynamic_lp_fee) } else { lp_fee };
+    if lp_fee < amm.min_lp_fee
+        || lp_fee > amm.max_lp_fee
+        || protocol_fee > amm.max_protocol_fee
+        || highest_lp_fee as u64 + protocol_fee as u64 >= FEE_MULTIPLIER
+    {
+        return err!(FTRXSwapError::InvalidFee);
+    }
+
+    // Only the fees change, the pool keeps the address derived from lp_fee_seed
+    let old_lp_fee = pool.lp_fee;
+    let old_protocol_fee = pool.protocol_fee;
+    pool.lp_fee = lp_fee;
+    pool.protocol_fee = protocol_fee;
+
+    emit!(PoolFeesUpdated {
+        pool: pool.key(),
+        old_lp_fee,
+        new_lp_fee: lp_fee,
+        old_protocol_fee,
+        new_protocol_fee: protocol_fee,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPoolFees<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = admin @ FTRXSwapError::InvalidAdmin,
+        has_one = amm,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    pub admin: Signer<'info>,
+}
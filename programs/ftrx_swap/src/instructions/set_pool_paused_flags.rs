@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::PAUSE_ALL,
+    errors::*,
+    events::PoolPausedFlagsUpdated,
+    state::SimplePool,
+};
+
+pub fn set_pool_paused_flags(ctx: Context<SetPoolPausedFlags>, paused_flags: u8) -> Result<()> {
+    if paused_flags & !PAUSE_ALL != 0 {
+        return err!(FTRXSwapError::InvalidPauseFlags);
+    }
+
+    // PAUSE_SWAP | PAUSE_DEPOSIT is the withdraw only mode, LPs can still exit
+    let pool = &mut ctx.accounts.pool;
+    let old_paused_flags = pool.paused_flags;
+    pool.paused_flags = paused_flags;
+
+    emit!(PoolPausedFlagsUpdated {
+        pool: pool.key(),
+        old_paused_flags,
+        new_paused_flags: paused_flags,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPoolPausedFlags<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = admin @ FTRXSwapError::InvalidAdmin,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    pub admin: Signer<'info>,
+}
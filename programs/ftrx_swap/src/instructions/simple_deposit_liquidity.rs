@@ -1,15 +1,19 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, MintTo, Token, TokenAccount, Transfer},
+    token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
 };
 use fixed::types::I64F64;
-use fixed_sqrt::FixedSqrt;
 
 use crate::{
-    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY},
+    constants::{AMM_SEED, CURVE_CONCENTRATED, CURVE_STABLE, CURVE_WEIGHTED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY, OBSERVATION_SEED, PAUSE_DEPOSIT},
     errors::FTRXSwapError,
-    state::SimplePool,
+    events::LiquidityDeposited,
+    math,
+    native_sol,
+    stable_math::{self, U256},
+    weighted_math,
+    state::{Deadline, PoolObservations, SimpleAmm, SimplePool},
 };
 
 pub fn deposit_liquidity(
@@ -19,6 +23,12 @@ pub fn deposit_liquidity(
     expected_lp_token:u64
 ) -> Result<()> {
 
+    // Accumulate the prices of the reserves before they move
+    let (reserve_a, reserve_b) = (ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+    ctx.accounts.pool.update_price_cumulatives(reserve_a, reserve_b)?;
+    ctx.accounts.observations.record(&ctx.accounts.pool);
+
+
     // Prevent depositing assets the depositor does not own
     let mut amount_a = if amount_a > ctx.accounts.depositor_account_a.amount {
         ctx.accounts.depositor_account_a.amount
@@ -41,112 +51,98 @@ pub fn deposit_liquidity(
     // Is it the first time we deposit amounts in the pool ?
     let pool_creation = pool_a.amount == 0 && pool_b.amount == 0;
 
-    //Calculating price as a ratio before deposit if there is available liquidity
-    let mut a_sup_b=false;
-    let mut ratio_price_check=I64F64::from_num(1);
-    if !pool_creation{
-   
-        //Calculating ratio before deposit assuming poolb has more token than poola
-        //For precision, we'll evaluate a/b if a>b, and b/a otherwise
-        if pool_a.amount>pool_b.amount{
-            a_sup_b=true;
-            ratio_price_check=I64F64::from_num(pool_a.amount).checked_div(I64F64::from_num(pool_b.amount)).unwrap();
-        }else{
-            ratio_price_check=I64F64::from_num(pool_b.amount).checked_div(I64F64::from_num(pool_a.amount)).unwrap();
-        
-        }
-    }
-    //DONE - Calculating price as a ratio before deposit if there is available liquidity
+    // Stable pools take any ratio, imbalances pay a fee instead
+    let stable = ctx.accounts.pool.curve == CURVE_STABLE;
 
     // Initializing or making sure the price ratio constraint is respected
-    (amount_a, amount_b) = if pool_creation {
-        // Add as is if there is no liquidity
-        (amount_a, amount_b)
-    } else {
-        if a_sup_b{
-            // ratio_price_check is a/b and added_a should be added_b*ratio_price_check
-            (
-                I64F64::from_num(amount_b)
-                    .checked_mul(ratio_price_check)
-                    .unwrap().ceil()
-                    .to_num::<u64>(),
-                amount_b,
-            )
-            
-        } else {
-            // ratio_price_check is b/a and added_b should be added_a*ratio_price_check
-            (
-                amount_a,
-                I64F64::from_num(amount_a)
-                    .checked_mul(ratio_price_check)
-                    .unwrap().ceil()
-                    .to_num::<u64>(),
-            )
-        }
-    };
-
-
-    let  mint_liquidity_supply_before = ctx.accounts.mint_liquidity.supply + MINIMUM_LIQUIDITY;
-
-
-    // Computing the amount of liquidity about to be deposited
-    // This formula will only be used for the pool creation ie the first deposit
-    let mut liquidity = I64F64::from_num(amount_a)
-        .checked_mul(I64F64::from_num(amount_b))
-        .unwrap()
-        .sqrt()
-        .floor()
-        .to_num::<u64>();
-    
-
-    // Lock some minimum liquidity on the first deposit
-    if pool_creation {
-        if liquidity < MINIMUM_LIQUIDITY {
-            return err!(FTRXSwapError::DepositTooSmall);
-        }
-
-        liquidity -= MINIMUM_LIQUIDITY;
-  
-    }else{
-        // For all deposits after the very first successful deposit, this is the relevant formula 
-        // for calculating the amount of LP token to mint
-        liquidity =I64F64::from_num(mint_liquidity_supply_before).checked_mul(I64F64::from_num(amount_a)).unwrap().checked_div(I64F64::from_num(pool_a.amount)).unwrap().floor().to_num::<u64>();
+    // Add as is if there is no liquidity
+    if !pool_creation && !stable {
+        (amount_a, amount_b) = math::balanced_deposit_amounts(pool_a.amount, pool_b.amount, amount_a, amount_b)?;
     }
 
+
     // Transfer tokens to the pool
-    token::transfer(
+    let reserve_a_before = ctx.accounts.pool_account_a.amount;
+    let reserve_b_before = ctx.accounts.pool_account_b.amount;
+    token_interface::transfer_checked(
         CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.token_program_a.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.depositor_account_a.to_account_info(),
+                mint: ctx.accounts.mint_a.to_account_info(),
                 to: ctx.accounts.pool_account_a.to_account_info(),
                 authority: ctx.accounts.depositor.to_account_info(),
             },
         ),
         amount_a,
+        ctx.accounts.mint_a.decimals,
     )?;
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.token_program_b.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.depositor_account_b.to_account_info(),
+                mint: ctx.accounts.mint_b.to_account_info(),
                 to: ctx.accounts.pool_account_b.to_account_info(),
                 authority: ctx.accounts.depositor.to_account_info(),
             },
         ),
         amount_b,
+        ctx.accounts.mint_b.decimals,
     )?;
 
+    // Transfer fee mints credit less than what was sent, from here on only
+    // the amounts the pool actually received count
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    let amount_a = ctx.accounts.pool_account_a.amount - reserve_a_before;
+    let amount_b = ctx.accounts.pool_account_b.amount - reserve_b_before;
+
+    let  mint_liquidity_supply_before = ctx.accounts.mint_liquidity.supply + MINIMUM_LIQUIDITY;
+
+
+    // Computing the amount of liquidity about to be deposited
+    let mut liquidity = if stable {
+        let actual_pool=&ctx.accounts.pool;
+        stable_math::deposit_liquidity(
+            actual_pool.amp,
+            reserve_a_before,
+            reserve_b_before,
+            amount_a,
+            amount_b,
+            mint_liquidity_supply_before,
+            actual_pool.lp_fee,
+        )?
+    } else if ctx.accounts.pool.curve == CURVE_WEIGHTED && pool_creation {
+        // Weighted pools are joined in proportion like constant product ones,
+        // only the first supply follows the weights
+        let actual_pool=&ctx.accounts.pool;
+        weighted_math::initial_liquidity(actual_pool.weight_a, actual_pool.weight_b, amount_a, amount_b)?
+    } else {
+        math::deposit_liquidity(reserve_a_before, reserve_b_before, amount_a, amount_b, mint_liquidity_supply_before)?
+    };
+
+
+    // Lock some minimum liquidity on the first deposit
+    if pool_creation {
+        if liquidity < MINIMUM_LIQUIDITY {
+            return err!(FTRXSwapError::DepositTooSmall);
+        }
+
+        liquidity -= MINIMUM_LIQUIDITY;
+
+    }
+
     // Mint the liquidity to user
 
     let actual_pool=&ctx.accounts.pool;
-    let lp_fee=actual_pool.lp_fee.to_le_bytes();
+    let lp_fee_seed=actual_pool.lp_fee_seed.to_le_bytes();
     
     let authority_seeds = &[
         actual_pool.mint_a.as_ref(),
         actual_pool.mint_b.as_ref(),
-        actual_pool.admin.as_ref(),
-        lp_fee.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
         &[actual_pool.pool_bump],
     ];
 
@@ -154,7 +150,7 @@ pub fn deposit_liquidity(
 
 
     let signer_seeds = &[&authority_seeds[..]];
-    token::mint_to(
+    token_interface::mint_to(
         CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             MintTo {
@@ -189,7 +185,18 @@ pub fn deposit_liquidity(
     //These are potentially triggering an error preventing from the tx to complete
     // We want to have added_a/a_before > added_lp_token_supply/lp_token_supply
     //and same for b
-    if !pool_creation{
+    if !pool_creation && stable{
+
+        // The invariant held by each LP token must not drop
+        let invariant_before = ctx.accounts.pool.invariant(reserve_a_before, reserve_b_before)?;
+        let invariant_after = ctx.accounts.pool.invariant(new_pool_a_amount, new_pool_b_amount)?;
+        if U256::from(invariant_after) * U256::from(mint_liquidity_supply_before)
+            < U256::from(invariant_before) * U256::from(mint_liquidity_supply_after)
+        {
+            return err!(FTRXSwapError::InconsistentPriceRatioLiquidity);
+        }
+
+    }else if !pool_creation{
 
         let ratio_a_check_after=I64F64::from_num(amount_a).checked_div(I64F64::from_num(amount_a_before)).unwrap();
         let ratio_b_check_after=I64F64::from_num(amount_b).checked_div(I64F64::from_num(amount_b_before)).unwrap();
@@ -202,28 +209,126 @@ pub fn deposit_liquidity(
 
     }
 
+    emit!(LiquidityDeposited {
+        pool: ctx.accounts.pool.key(),
+        depositor: ctx.accounts.depositor.key(),
+        amount_a,
+        amount_b,
+        lp_minted: liquidity,
+        lp_supply: ctx.accounts.mint_liquidity.supply,
+    });
+
+    Ok(())
+}
+
+/// Same as deposit_liquidity, rejected once the deadline has passed
+pub fn deposit_liquidity_with_deadline(
+    ctx: Context<DepositLiquidity>,
+    amount_a: u64,
+    amount_b: u64,
+    expected_lp_token: u64,
+    deadline: Deadline,
+) -> Result<()> {
+    deadline.check()?;
+    deposit_liquidity(ctx, amount_a, amount_b, expected_lp_token)
+}
+
+/// Same as deposit_liquidity, paying lamports on a NATIVE_MINT side through
+/// the depositor wSOL account, closed afterwards when unwrap is set so any
+/// unused amount comes back as lamports
+pub fn deposit_liquidity_native<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositLiquidity<'info>>,
+    amount_a: u64,
+    amount_b: u64,
+    expected_lp_token: u64,
+    unwrap: bool,
+) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    if native_sol::is_native(&accounts.mint_a.key()) {
+        native_sol::wrap(
+            accounts.depositor.to_account_info(),
+            accounts.depositor_account_a.to_account_info(),
+            amount_a,
+            accounts.system_program.to_account_info(),
+            accounts.token_program_a.to_account_info(),
+        )?;
+        accounts.depositor_account_a.reload()?;
+    }
+    if native_sol::is_native(&accounts.mint_b.key()) {
+        native_sol::wrap(
+            accounts.depositor.to_account_info(),
+            accounts.depositor_account_b.to_account_info(),
+            amount_b,
+            accounts.system_program.to_account_info(),
+            accounts.token_program_b.to_account_info(),
+        )?;
+        accounts.depositor_account_b.reload()?;
+    }
+
+    deposit_liquidity(
+        Context::new(ctx.program_id, &mut *ctx.accounts, ctx.remaining_accounts, ctx.bumps.clone()),
+        amount_a,
+        amount_b,
+        expected_lp_token,
+    )?;
+
+    if unwrap {
+        let accounts = &ctx.accounts;
+        if native_sol::is_native(&accounts.mint_a.key()) {
+            native_sol::unwrap(accounts.depositor_account_a.to_account_info(), accounts.depositor.to_account_info(), accounts.token_program_a.to_account_info())?;
+        }
+        if native_sol::is_native(&accounts.mint_b.key()) {
+            native_sol::unwrap(accounts.depositor_account_b.to_account_info(), accounts.depositor.to_account_info(), accounts.token_program_b.to_account_info())?;
+        }
+    }
+
     Ok(())
 }
 
 #[derive(Accounts)]
 pub struct DepositLiquidity<'info> {
     #[account(
+        mut,
         seeds = [
          
             mint_a.key().as_ref(),
             mint_b.key().as_ref(),
-            pool.admin.key().as_ref(),
-            &pool.lp_fee.to_le_bytes(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
         ],
         bump,
         has_one = mint_a,
         has_one = mint_b,
+        has_one = amm,
+        constraint = !pool.is_paused(PAUSE_DEPOSIT) @ FTRXSwapError::PoolPaused,
+        constraint = !pool.locked @ FTRXSwapError::PoolLocked,
+        constraint = pool.curve != CURVE_CONCENTRATED @ FTRXSwapError::UnsupportedCurve,
 
     )]
     pub pool: Account<'info, SimplePool>,
 
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        constraint = !amm.paused @ FTRXSwapError::AmmPaused,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
 
-    /// The account paying for all rents
+    #[account(
+        mut,
+        seeds = [
+            OBSERVATION_SEED.as_ref(),
+            pool.key().as_ref(),
+        ],
+        bump = observations.bump,
+    )]
+    pub observations: Box<Account<'info, PoolObservations>>,
+
+
+    /// Owner of the deposited tokens, mutable to wrap and unwrap native SOL
+    #[account(mut)]
     pub depositor: Signer<'info>,
 
     #[account(
@@ -232,17 +337,25 @@ pub struct DepositLiquidity<'info> {
           
         mint_a.key().as_ref(),
         mint_b.key().as_ref(),
-        pool.admin.key().as_ref(),
+        pool.admin_seed.as_ref(),
+        &pool.lp_fee_seed.to_le_bytes(),
         LIQUIDITY_SEED.as_ref(),
 
         ],
         bump,
+        mint::token_program = token_program,
     )]
-    pub mint_liquidity: Box<Account<'info, Mint>>,
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
 
-    pub mint_a: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    pub mint_b: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
@@ -254,7 +367,7 @@ pub struct DepositLiquidity<'info> {
         ],
         bump
     )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -266,34 +379,44 @@ pub struct DepositLiquidity<'info> {
         ],
         bump
     )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint_liquidity,
         associated_token::authority = depositor,
+        associated_token::token_program = token_program,
     )]
-    pub depositor_account_liquidity: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    #[account(mut,
+    #[account(
+        init_if_needed,
+        payer = payer,
         associated_token::mint = mint_a,
         associated_token::authority = depositor,
+        associated_token::token_program = token_program_a,
     )]
-    pub depositor_account_a: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    #[account(mut,
+    #[account(
+        init_if_needed,
+        payer = payer,
         associated_token::mint = mint_b,
         associated_token::authority = depositor,
+        associated_token::token_program = token_program_b,
     )]
-    pub depositor_account_b: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// The account paying for all rents
     #[account(mut)]
     pub payer: Signer<'info>,
 
     /// Solana ecosystem accounts
-    pub token_program: Program<'info, Token>,
+    /// Token programs of the LP mint and of mints A and B, legacy or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
@@ -0,0 +1,408 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
+};
+use fixed::types::I64F64;
+
+use crate::{
+    constants::{AMM_SEED, CREATOR_SEED, CURVE_CONSTANT_PRODUCT, LIQUIDITY_SEED, MINIMUM_LIQUIDITY, OBSERVATION_SEED, PAUSE_DEPOSIT, PAUSE_SWAP, TREASURY_SEED},
+    errors::FTRXSwapError,
+    events::LiquidityDeposited,
+    math,
+    state::{PoolObservations, SimpleAmm, SimplePool},
+};
+
+pub fn deposit_single_sided(
+    ctx: Context<DepositSingleSided>,
+    swap_a: bool,
+    amount_in: u64,
+    min_lp_out: u64,
+) -> Result<()> {
+
+    // Accumulate the prices of the reserves before they move
+    let (reserve_a, reserve_b) = (ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+    ctx.accounts.pool.update_price_cumulatives(reserve_a, reserve_b)?;
+    ctx.accounts.observations.record(&ctx.accounts.pool);
+
+
+    let expected_mint = if swap_a { ctx.accounts.mint_a.key() } else { ctx.accounts.mint_b.key() };
+    if ctx.accounts.depositor_source.mint != expected_mint {
+        return err!(FTRXSwapError::InvalidMint);
+    }
+
+    // Prevent depositing assets the depositor does not own
+    let amount_in = if amount_in > ctx.accounts.depositor_source.amount {
+        ctx.accounts.depositor_source.amount
+    } else {
+        amount_in
+    };
+
+    let (reserve_in, reserve_out) = if swap_a {
+        (ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)
+    } else {
+        (ctx.accounts.pool_account_b.amount, ctx.accounts.pool_account_a.amount)
+    };
+
+    // A single sided deposit needs a price to swap against
+    if reserve_in == 0 || reserve_out == 0 {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+
+    let (vault_in, mint_in, program_in) = if swap_a {
+        (&ctx.accounts.pool_account_a, &ctx.accounts.mint_a, &ctx.accounts.token_program_a)
+    } else {
+        (&ctx.accounts.pool_account_b, &ctx.accounts.mint_b, &ctx.accounts.token_program_b)
+    };
+
+    // Transfer the whole input to the pool
+    token_interface::transfer_checked(
+        CpiContext::new(
+            program_in.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.depositor_source.to_account_info(),
+                mint: mint_in.to_account_info(),
+                to: vault_in.to_account_info(),
+                authority: ctx.accounts.depositor.to_account_info(),
+            },
+        ),
+        amount_in,
+        mint_in.decimals,
+    )?;
+
+    // Transfer fee mints credit less than what was sent, from here on only
+    // the amount the pool actually received counts
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    let amount_in = if swap_a { ctx.accounts.pool_account_a.amount } else { ctx.accounts.pool_account_b.amount } - reserve_in;
+
+    // Dynamic fee pools charge the fee of the decayed volatility
+    ctx.accounts.pool.decay_volatility()?;
+    let lp_fee_rate = ctx.accounts.pool.dynamic_lp_fee();
+
+    let actual_pool=&ctx.accounts.pool;
+
+    // Swap part of the input so that the remainder and the output match the
+    // pool ratio after the swap, the output never leaves the vault
+    let swap_amount = math::single_sided_swap_amount(reserve_in, amount_in, lp_fee_rate, actual_pool.protocol_fee)?;
+    let swap_amounts = math::swap_exact_in(reserve_in, reserve_out, swap_amount, lp_fee_rate, actual_pool.protocol_fee)?;
+
+    let reserve_in_after_swap = reserve_in + swap_amount - swap_amounts.protocol_fee;
+    let reserve_out_after_swap = reserve_out - swap_amounts.output;
+
+    // The internal swap must not lower the invariant
+    let invariant_before_trade = (reserve_in as u128) * (reserve_out as u128);
+    if invariant_before_trade > (reserve_in_after_swap as u128) * (reserve_out_after_swap as u128) {
+        return err!(FTRXSwapError::InvariantViolated);
+    }
+
+    let deposited_in = amount_in - swap_amount;
+    let deposited_out = swap_amounts.output;
+
+    let  mint_liquidity_supply_before = ctx.accounts.mint_liquidity.supply + MINIMUM_LIQUIDITY;
+
+    // Mint for the side bringing the least liquidity, any rounding dust
+    // on the other side stays in the pool
+    let liquidity_in = I64F64::from_num(mint_liquidity_supply_before)
+        .checked_mul(I64F64::from_num(deposited_in))
+        .ok_or(FTRXSwapError::MathOverflow)?
+        .checked_div(I64F64::from_num(reserve_in_after_swap))
+        .ok_or(FTRXSwapError::MathOverflow)?
+        .floor()
+        .to_num::<u64>();
+    let liquidity_out = I64F64::from_num(mint_liquidity_supply_before)
+        .checked_mul(I64F64::from_num(deposited_out))
+        .ok_or(FTRXSwapError::MathOverflow)?
+        .checked_div(I64F64::from_num(reserve_out_after_swap))
+        .ok_or(FTRXSwapError::MathOverflow)?
+        .floor()
+        .to_num::<u64>();
+    let liquidity = liquidity_in.min(liquidity_out);
+
+    if liquidity == 0 {
+        return err!(FTRXSwapError::DepositTooSmall);
+    }
+
+    if liquidity < min_lp_out {
+        return err!(FTRXSwapError::SlippageExceeded);
+    }
+
+    let lp_fee_seed=actual_pool.lp_fee_seed.to_le_bytes();
+
+    let authority_seeds = &[
+        actual_pool.mint_a.as_ref(),
+        actual_pool.mint_b.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
+        &[actual_pool.pool_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    let (vault_in, treasury_in, creator_in) = if swap_a {
+        (ctx.accounts.pool_account_a.to_account_info(), ctx.accounts.treasury_mint_a.to_account_info(), ctx.accounts.creator_vault_a.to_account_info())
+    } else {
+        (ctx.accounts.pool_account_b.to_account_info(), ctx.accounts.treasury_mint_b.to_account_info(), ctx.accounts.creator_vault_b.to_account_info())
+    };
+    let creator_fee = actual_pool.creator_fee(swap_amounts.protocol_fee);
+
+    // The treasury cut of the swap leaves the vault
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            program_in.to_account_info(),
+            TransferChecked {
+                from: vault_in.clone(),
+                mint: mint_in.to_account_info(),
+                to: treasury_in,
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        swap_amounts.protocol_fee - creator_fee,
+        mint_in.decimals,
+    )?;
+
+    if creator_fee > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                program_in.to_account_info(),
+                TransferChecked {
+                    from: vault_in,
+                    mint: mint_in.to_account_info(),
+                    to: creator_in,
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            creator_fee,
+            mint_in.decimals,
+        )?;
+    }
+
+    // Mint the liquidity to user
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mint_liquidity.to_account_info(),
+                to: ctx.accounts.depositor_account_liquidity.to_account_info(),
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        liquidity,
+    )?;
+
+    //We reload amounts
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    ctx.accounts.mint_liquidity.reload()?;
+
+    let new_reserve_in = if swap_a { ctx.accounts.pool_account_a.amount } else { ctx.accounts.pool_account_b.amount };
+    if new_reserve_in != reserve_in_after_swap + deposited_in {
+        return err!(FTRXSwapError::InvariantViolated);
+    }
+
+    // The move of the internal swap raises the fee of the next trades
+    let pool = &mut ctx.accounts.pool;
+    if pool.dynamic_fee {
+        pool.accumulate_volatility(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+        msg!(
+            "dynamic lp fee {} volatility accumulator {}",
+            lp_fee_rate,
+            pool.volatility_accumulator
+        );
+    }
+
+    // Checking the liquidity ratio vs the deposited ratios are in favor of the lp
+    // We want added/reserve_after_swap >= added_lp_token_supply/lp_token_supply on both sides
+    let ratio_in_check=I64F64::from_num(deposited_in).checked_div(I64F64::from_num(reserve_in_after_swap)).unwrap();
+    let ratio_out_check=I64F64::from_num(deposited_out).checked_div(I64F64::from_num(reserve_out_after_swap)).unwrap();
+    let ratio_supply_check=I64F64::from_num(liquidity).checked_div(I64F64::from_num(mint_liquidity_supply_before)).unwrap();
+
+    if ratio_supply_check>ratio_in_check || ratio_supply_check>ratio_out_check{
+        return err!(FTRXSwapError::InconsistentPriceRatioLiquidity);
+    }
+
+    emit!(LiquidityDeposited {
+        pool: ctx.accounts.pool.key(),
+        depositor: ctx.accounts.depositor.key(),
+        amount_a: if swap_a { amount_in } else { 0 },
+        amount_b: if swap_a { 0 } else { amount_in },
+        lp_minted: liquidity,
+        lp_supply: ctx.accounts.mint_liquidity.supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositSingleSided<'info> {
+    #[account(
+        mut,
+        seeds = [
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        constraint = !pool.is_paused(PAUSE_DEPOSIT) @ FTRXSwapError::PoolPaused,
+        constraint = !pool.is_paused(PAUSE_SWAP) @ FTRXSwapError::PoolPaused,
+        constraint = !pool.locked @ FTRXSwapError::PoolLocked,
+        constraint = pool.curve == CURVE_CONSTANT_PRODUCT @ FTRXSwapError::UnsupportedCurve,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        constraint = !amm.paused @ FTRXSwapError::AmmPaused,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        mut,
+        seeds = [
+            OBSERVATION_SEED.as_ref(),
+            pool.key().as_ref(),
+        ],
+        bump = observations.bump,
+    )]
+    pub observations: Box<Account<'info, PoolObservations>>,
+
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+        mint_a.key().as_ref(),
+        mint_b.key().as_ref(),
+        pool.admin_seed.as_ref(),
+        &pool.lp_fee_seed.to_le_bytes(),
+        LIQUIDITY_SEED.as_ref(),
+        ],
+        bump,
+        mint::token_program = token_program,
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vaults accruing the creator share of the protocol fees
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_liquidity,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token account holding the single sided input, of mint a if swap_a
+    #[account(
+        mut,
+        token::authority = depositor,
+    )]
+    pub depositor_source: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    /// Token programs of the LP mint and of mints A and B, legacy or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
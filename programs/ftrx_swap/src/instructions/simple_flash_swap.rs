@@ -0,0 +1,349 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke,
+    },
+};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{
+    constants::{AMM_SEED, CREATOR_SEED, CURVE_CONCENTRATED, FEE_MULTIPLIER, OBSERVATION_SEED, PAUSE_SWAP, TREASURY_SEED},
+    errors::FTRXSwapError,
+    events::Swap,
+    state::{PoolObservations, SimpleAmm, SimplePool},
+};
+
+// Fee on an amount, rounded up when it stays with the LPs
+fn fee_amount(amount: u64, fee: u16, round_up: bool) -> u128 {
+    let numerator = amount as u128 * fee as u128;
+    let rounding = if round_up { FEE_MULTIPLIER as u128 - 1 } else { 0 };
+    (numerator + rounding) / FEE_MULTIPLIER as u128
+}
+
+pub fn simple_flash_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, SimpleFlashSwap<'info>>,
+    swap_a: bool,
+    output_amount: u64,
+    data: Vec<u8>,
+) -> Result<()> {
+
+    // Accumulate the prices of the reserves before they move
+    let (reserve_a, reserve_b) = (ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+    ctx.accounts.pool.update_price_cumulatives(reserve_a, reserve_b)?;
+    ctx.accounts.observations.record(&ctx.accounts.pool);
+
+    let (reserve_in, reserve_out) = if swap_a { (reserve_a, reserve_b) } else { (reserve_b, reserve_a) };
+    if output_amount == 0 || output_amount >= reserve_out {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+
+    // Dynamic fee pools charge the fee of the decayed volatility
+    ctx.accounts.pool.decay_volatility()?;
+    let lp_fee_rate = ctx.accounts.pool.dynamic_lp_fee();
+
+    // The pool is written to around the callback, so the seeds are copied out
+    let (mint_a_seed, mint_b_seed, admin_seed) = (ctx.accounts.pool.mint_a, ctx.accounts.pool.mint_b, ctx.accounts.pool.admin_seed);
+    let lp_fee_seed=ctx.accounts.pool.lp_fee_seed.to_le_bytes();
+    let pool_bump=ctx.accounts.pool.pool_bump;
+
+    let authority_seeds = &[
+        mint_a_seed.as_ref(),
+        mint_b_seed.as_ref(),
+        admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
+        &[pool_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    let accounts = &ctx.accounts;
+    let (vault_out, mint_out, program_out) = if swap_a {
+        (&accounts.pool_account_b, &accounts.mint_b, &accounts.token_program_b)
+    } else {
+        (&accounts.pool_account_a, &accounts.mint_a, &accounts.token_program_a)
+    };
+
+    // Send the output first
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            program_out.to_account_info(),
+            TransferChecked {
+                from: vault_out.to_account_info(),
+                mint: mint_out.to_account_info(),
+                to: accounts.trader_destination.to_account_info(),
+                authority: accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        output_amount,
+        mint_out.decimals,
+    )?;
+
+    // Lock the pool and write it back before handing over control, so the
+    // receiver cannot trade against the reserves it is settling
+    ctx.accounts.pool.locked = true;
+    ctx.accounts.pool.flash_swap_locked = true;
+    ctx.accounts.pool.exit(ctx.program_id)?;
+
+    // The pool never signs the callback, the receiver only gets the remaining accounts
+    let receiver_accounts: Vec<AccountMeta> = ctx.remaining_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    invoke(
+        &Instruction {
+            program_id: ctx.accounts.receiver_program.key(),
+            accounts: receiver_accounts,
+            data,
+        },
+        ctx.remaining_accounts,
+    )?;
+
+    // Reload accounts because of the CPIs
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    let (balance_in, balance_out) = if swap_a {
+        (ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)
+    } else {
+        (ctx.accounts.pool_account_b.amount, ctx.accounts.pool_account_a.amount)
+    };
+
+    // Input paid in, and output paid back, by the receiver
+    let amount_in = balance_in.saturating_sub(reserve_in);
+    let amount_out_repaid = balance_out.saturating_sub(reserve_out - output_amount);
+    if amount_in == 0 && amount_out_repaid == 0 {
+        return err!(FTRXSwapError::InvariantViolated);
+    }
+
+    let actual_pool=&ctx.accounts.pool;
+    let protocol_fee_in = fee_amount(amount_in, actual_pool.protocol_fee, false) as u64;
+    let protocol_fee_out = fee_amount(amount_out_repaid, actual_pool.protocol_fee, false) as u64;
+
+    // Fee adjusted constant product, the LP fees are left out of the
+    // balances so that they are really paid on top
+    let adjusted_in = (balance_in as u128)
+        .checked_sub(protocol_fee_in as u128 + fee_amount(amount_in, lp_fee_rate, true))
+        .ok_or(FTRXSwapError::InvariantViolated)?;
+    let adjusted_out = (balance_out as u128)
+        .checked_sub(protocol_fee_out as u128 + fee_amount(amount_out_repaid, lp_fee_rate, true))
+        .ok_or(FTRXSwapError::InvariantViolated)?;
+
+    let (adjusted_a, adjusted_b) = if swap_a { (adjusted_in, adjusted_out) } else { (adjusted_out, adjusted_in) };
+    let invariant_before_trade = actual_pool.invariant(reserve_a, reserve_b)?;
+    let invariant_after_trade = actual_pool.invariant(adjusted_a as u64, adjusted_b as u64)?;
+    if invariant_before_trade > invariant_after_trade {
+        return err!(FTRXSwapError::InvariantViolated);
+    }
+
+    // Treasury and creator cuts of what came in on each side
+    let accounts = &ctx.accounts;
+    let sides = [
+        (true, if swap_a { protocol_fee_in } else { protocol_fee_out }),
+        (false, if swap_a { protocol_fee_out } else { protocol_fee_in }),
+    ];
+    for (side_a, protocol_fee) in sides {
+        let (vault, treasury, creator_vault, mint, token_program) = if side_a {
+            (&accounts.pool_account_a, &accounts.treasury_mint_a, &accounts.creator_vault_a, &accounts.mint_a, &accounts.token_program_a)
+        } else {
+            (&accounts.pool_account_b, &accounts.treasury_mint_b, &accounts.creator_vault_b, &accounts.mint_b, &accounts.token_program_b)
+        };
+        let creator_fee = accounts.pool.creator_fee(protocol_fee);
+        let destinations = [
+            (treasury.to_account_info(), protocol_fee - creator_fee),
+            (creator_vault.to_account_info(), creator_fee),
+        ];
+        for (destination, amount) in destinations {
+            if amount == 0 {
+                continue;
+            }
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: vault.to_account_info(),
+                        mint: mint.to_account_info(),
+                        to: destination,
+                        authority: accounts.pool.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+                mint.decimals,
+            )?;
+        }
+    }
+
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    ctx.accounts.pool.locked = false;
+    ctx.accounts.pool.flash_swap_locked = false;
+
+    // The move of this trade raises the fee of the next ones
+    let pool = &mut ctx.accounts.pool;
+    pool.accumulate_volatility(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+
+    emit!(Swap {
+        pool: ctx.accounts.pool.key(),
+        trader: ctx.accounts.payer.key(),
+        swap_a,
+        input: amount_in,
+        output: output_amount.saturating_sub(amount_out_repaid),
+        lp_fee: fee_amount(amount_in, lp_fee_rate, true) as u64,
+        protocol_fee: protocol_fee_in,
+        reserve_a: ctx.accounts.pool_account_a.amount,
+        reserve_b: ctx.accounts.pool_account_b.amount,
+        lp_fee_rate,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SimpleFlashSwap<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        constraint = !pool.is_paused(PAUSE_SWAP) @ FTRXSwapError::PoolPaused,
+        constraint = !pool.locked @ FTRXSwapError::PoolLocked,
+        constraint = pool.curve != CURVE_CONCENTRATED @ FTRXSwapError::UnsupportedCurve,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        constraint = !amm.paused @ FTRXSwapError::AmmPaused,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        mut,
+        seeds = [
+            OBSERVATION_SEED.as_ref(),
+            pool.key().as_ref(),
+        ],
+        bump = observations.bump,
+    )]
+    pub observations: Box<Account<'info, PoolObservations>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vaults accruing the creator share of the protocol fees
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token account receiving the output, of mint b if swap_a
+    #[account(mut)]
+    pub trader_destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Program called back once the output is sent, anything but this program
+    #[account(
+        executable,
+        constraint = receiver_program.key() != crate::ID @ FTRXSwapError::InvalidReceiver,
+    )]
+    pub receiver_program: AccountInfo<'info>,
+
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    /// Token programs of mints A and B, legacy or Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+}
@@ -1,16 +1,20 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
-use fixed::types::I64F64;
 
 use crate::{
-    constants::AUTHORITY_SEED,
-    constants::FEE_MULTIPLIER,
+    constants::AMM_SEED,
+    constants::OBSERVATION_SEED,
+    constants::PAUSE_SWAP,
     constants::TREASURY_SEED,
+    constants::CREATOR_SEED,
     errors::*,
-    state::{SimplePool},
+    events::Swap,
+    native_sol,
+    state::{Deadline, PoolObservations, SimpleAmm, SimplePool},
+    token_extensions,
 };
 
 pub fn simple_swap_exact_in(
@@ -19,6 +23,12 @@ pub fn simple_swap_exact_in(
     input_amount: u64,
     min_output_amount: u64,
 ) -> Result<()> {
+
+    // Accumulate the prices of the reserves before they move
+    let (reserve_a, reserve_b) = (ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+    ctx.accounts.pool.update_price_cumulatives(reserve_a, reserve_b)?;
+    ctx.accounts.observations.record(&ctx.accounts.pool);
+
     // Prevent depositing assets the depositor does not own
     let input = if swap_a && input_amount > ctx.accounts.trader_account_a.amount {
         ctx.accounts.trader_account_a.amount
@@ -28,178 +38,114 @@ pub fn simple_swap_exact_in(
         input_amount
     };
 
-    // Apply trading fee for the treasury
-    let actual_pool=&ctx.accounts.pool;
-
-
-    let to_treasury_fee=input.checked_mul(actual_pool.protocol_fee as u64).unwrap().checked_div(FEE_MULTIPLIER).unwrap();
-    let lp_fee_amount= input.checked_mul(actual_pool.lp_fee as u64).unwrap().checked_div(FEE_MULTIPLIER).unwrap();
-    let taxed_input = input.checked_sub(to_treasury_fee).unwrap().checked_sub(lp_fee_amount).unwrap();
-
-    let pool_a = &ctx.accounts.pool_account_a;
-    let pool_b = &ctx.accounts.pool_account_b;
-
-
-    let raw_output =if swap_a{
-
-    let new_pool_a_with_lp_fees=pool_a.
-    amount.
-    checked_add(lp_fee_amount).unwrap();
-
-    let new_k_after_fees=new_pool_a_with_lp_fees.
-    checked_mul(pool_b.amount)
-    .ok_or(FTRXSwapError::MathOverflow)?;
-
-
-
-    let new_pool_a = new_pool_a_with_lp_fees
-    .checked_add(taxed_input)
-    .unwrap();
-
-    let theoretical_new_pool_b = I64F64::from_num(new_k_after_fees)
-    .checked_div(I64F64::from_num(new_pool_a))
-    .unwrap().ceil().to_num::<u64>();
-
-     pool_b
-    .amount
-    .checked_sub(theoretical_new_pool_b)
-    .unwrap()
-
-    }else{
+    // Dynamic fee pools charge the fee of the decayed volatility
+    ctx.accounts.pool.decay_volatility()?;
+    let lp_fee_rate = ctx.accounts.pool.dynamic_lp_fee();
 
-        
-    let new_pool_b_with_lp_fees=pool_b.
-    amount.
-    checked_add(lp_fee_amount).unwrap();
-
-
-    let new_k_after_fees=pool_a.amount.
-    checked_mul(new_pool_b_with_lp_fees)
-    .ok_or(FTRXSwapError::MathOverflow)?;
-
-    
-
-
-    let new_pool_b = new_pool_b_with_lp_fees
-    .checked_add(taxed_input)
-    .unwrap();
-
-
-
-
-    let theoretical_new_pool_a = I64F64::from_num(new_k_after_fees)
-    .checked_div(I64F64::from_num(new_pool_b))
-    .unwrap().ceil().to_num::<u64>();
-
-     pool_a
-    .amount
-    .checked_sub(theoretical_new_pool_a)
-    .unwrap()
+    // Compute the invariant before the trade
+    let invariant_before_trade = ctx.accounts.pool.invariant(reserve_a, reserve_b)?;
 
+    let accounts = &ctx.accounts;
+    let (trader_in, trader_out, vault_in, vault_out, treasury_in, creator_in, mint_in, mint_out, program_in, program_out) = if swap_a {
+        (&accounts.trader_account_a, &accounts.trader_account_b, &accounts.pool_account_a, &accounts.pool_account_b,
+            &accounts.treasury_mint_a, &accounts.creator_vault_a, &accounts.mint_a, &accounts.mint_b, &accounts.token_program_a, &accounts.token_program_b)
+    } else {
+        (&accounts.trader_account_b, &accounts.trader_account_a, &accounts.pool_account_b, &accounts.pool_account_a,
+            &accounts.treasury_mint_b, &accounts.creator_vault_b, &accounts.mint_b, &accounts.mint_a, &accounts.token_program_b, &accounts.token_program_a)
     };
+    let reserve_in = vault_in.amount;
+    let reserve_out = vault_out.amount;
 
-
-    if raw_output < min_output_amount {
+    // Transfer tokens to the pool
+    token_interface::transfer_checked(
+        CpiContext::new(
+            program_in.to_account_info(),
+            TransferChecked {
+                from: trader_in.to_account_info(),
+                mint: mint_in.to_account_info(),
+                to: vault_in.to_account_info(),
+                authority: accounts.payer.to_account_info(),
+            },
+        ),
+        input,
+        mint_in.decimals,
+    )?;
+
+    // Transfer fee mints credit less than what was sent, price what arrived
+    let received_input = token_extensions::transfer_fee(&mint_in.to_account_info(), input)
+        .map(|transfer_fee| input - transfer_fee)?;
+
+    // Apply trading fee for the treasury and the LPs
+    let actual_pool=&accounts.pool;
+    let swap_amounts = actual_pool.swap_exact_in_with_fee(swap_a, reserve_in, reserve_out, received_input, lp_fee_rate)?;
+    let raw_output = swap_amounts.output;
+    let to_creator_fee = actual_pool.creator_fee(swap_amounts.protocol_fee);
+    let to_treasury_fee = swap_amounts.protocol_fee - to_creator_fee;
+
+    // Slippage is checked on what the trader ends up with
+    let received_output = raw_output - token_extensions::transfer_fee(&mint_out.to_account_info(), raw_output)?;
+    if received_output < min_output_amount {
         return err!(FTRXSwapError::OutputTooSmall);
     }
 
-    // Compute the invariant before the trade
-    let invariant_before_trade = pool_a.amount.checked_mul(pool_b.amount).unwrap();
+    let lp_fee_seed=actual_pool.lp_fee_seed.to_le_bytes();
 
-    // Transfer tokens to the pool
-    let actual_pool=&ctx.accounts.pool;
-    let lp_fee=actual_pool.lp_fee.to_le_bytes();
-    
     let authority_seeds = &[
         actual_pool.mint_a.as_ref(),
         actual_pool.mint_b.as_ref(),
-        actual_pool.admin.as_ref(),
-        lp_fee.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
         &[actual_pool.pool_bump],
     ];
     let signer_seeds = &[&authority_seeds[..]];
-    if swap_a {
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.trader_account_a.to_account_info(),
-                    to: ctx.accounts.pool_account_a.to_account_info(),
-                    authority: ctx.accounts.payer.to_account_info(),
-                },
-            ),
-            input,
-        )?;
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.pool_account_b.to_account_info(),
-                    to: ctx.accounts.trader_account_b.to_account_info(),
-                    authority: ctx.accounts.pool.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            raw_output,
-        )?;
-
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.pool_account_a.to_account_info(),
-                    to: ctx.accounts.treasury_mint_a.to_account_info(),
-                    authority: ctx.accounts.pool.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            to_treasury_fee,
-        )?;
-
-    } else {
-
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.trader_account_b.to_account_info(),
-                    to: ctx.accounts.pool_account_b.to_account_info(),
-                    authority: ctx.accounts.payer.to_account_info(),
-                },
-            ),
-            input,
-        )?;
 
-        token::transfer(
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            program_out.to_account_info(),
+            TransferChecked {
+                from: vault_out.to_account_info(),
+                mint: mint_out.to_account_info(),
+                to: trader_out.to_account_info(),
+                authority: accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        raw_output,
+        mint_out.decimals,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            program_in.to_account_info(),
+            TransferChecked {
+                from: vault_in.to_account_info(),
+                mint: mint_in.to_account_info(),
+                to: treasury_in.to_account_info(),
+                authority: accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        to_treasury_fee,
+        mint_in.decimals,
+    )?;
+
+    if to_creator_fee > 0 {
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.pool_account_a.to_account_info(),
-                    to: ctx.accounts.trader_account_a.to_account_info(),
-                    authority: ctx.accounts.pool.to_account_info(),
+                program_in.to_account_info(),
+                TransferChecked {
+                    from: vault_in.to_account_info(),
+                    mint: mint_in.to_account_info(),
+                    to: creator_in.to_account_info(),
+                    authority: accounts.pool.to_account_info(),
                 },
                 signer_seeds,
             ),
-            raw_output,
+            to_creator_fee,
+            mint_in.decimals,
         )?;
-
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.pool_account_b.to_account_info(),
-                    to: ctx.accounts.treasury_mint_b.to_account_info(),
-                    authority: ctx.accounts.pool.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            to_treasury_fee,
-        )?;
-
-
     }
 
-
     // Verify the invariant still holds
     // Reload accounts because of the CPIs
     // We tolerate if the new invariant is higher because it means a rounding error for LPs
@@ -207,12 +153,93 @@ pub fn simple_swap_exact_in(
     ctx.accounts.pool_account_b.reload()?;
 
 
-    if invariant_before_trade > ctx.accounts.pool_account_a.amount * ctx.accounts.pool_account_b.amount {
+    let invariant_after_trade = ctx.accounts.pool.invariant(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    if invariant_before_trade > invariant_after_trade {
         return err!(FTRXSwapError::InvariantViolated);
     }
 
+    // The move of this trade raises the fee of the next ones
+    let pool = &mut ctx.accounts.pool;
+    if pool.dynamic_fee {
+        pool.accumulate_volatility(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+        msg!(
+            "dynamic lp fee {} volatility accumulator {}",
+            lp_fee_rate,
+            pool.volatility_accumulator
+        );
+    }
+
+    emit!(Swap {
+        pool: ctx.accounts.pool.key(),
+        trader: ctx.accounts.payer.key(),
+        swap_a,
+        input: swap_amounts.input,
+        output: swap_amounts.output,
+        lp_fee: swap_amounts.lp_fee,
+        protocol_fee: swap_amounts.protocol_fee,
+        reserve_a: ctx.accounts.pool_account_a.amount,
+        reserve_b: ctx.accounts.pool_account_b.amount,
+        lp_fee_rate,
+    });
+
+    Ok(())
+}
+
+/// Same as simple_swap_exact_in, rejected once the deadline has passed
+pub fn simple_swap_exact_in_with_deadline(
+    ctx: Context<SimpleSwapExactIn>,
+    swap_a: bool,
+    input_amount: u64,
+    min_output_amount: u64,
+    deadline: Deadline,
+) -> Result<()> {
+    deadline.check()?;
+    simple_swap_exact_in(ctx, swap_a, input_amount, min_output_amount)
+}
+
+/// Same as simple_swap_exact_in, paying and receiving lamports on a NATIVE_MINT
+/// side through the trader wSOL account, closed afterwards when unwrap is set
+pub fn simple_swap_exact_in_native<'info>(
+    ctx: Context<'_, '_, '_, 'info, SimpleSwapExactIn<'info>>,
+    swap_a: bool,
+    input_amount: u64,
+    min_output_amount: u64,
+    unwrap: bool,
+) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    let (mint_in, wsol_in, program_in) = if swap_a {
+        (&accounts.mint_a, &mut accounts.trader_account_a, &accounts.token_program_a)
+    } else {
+        (&accounts.mint_b, &mut accounts.trader_account_b, &accounts.token_program_b)
+    };
 
+    if native_sol::is_native(&mint_in.key()) {
+        native_sol::wrap(
+            accounts.payer.to_account_info(),
+            wsol_in.to_account_info(),
+            input_amount,
+            accounts.system_program.to_account_info(),
+            program_in.to_account_info(),
+        )?;
+        wsol_in.reload()?;
+    }
 
+    simple_swap_exact_in(
+        Context::new(ctx.program_id, &mut *ctx.accounts, ctx.remaining_accounts, ctx.bumps.clone()),
+        swap_a,
+        input_amount,
+        min_output_amount,
+    )?;
+
+    if unwrap {
+        let accounts = &ctx.accounts;
+        if native_sol::is_native(&accounts.mint_a.key()) {
+            native_sol::unwrap(accounts.trader_account_a.to_account_info(), accounts.payer.to_account_info(), accounts.token_program_a.to_account_info())?;
+        }
+        if native_sol::is_native(&accounts.mint_b.key()) {
+            native_sol::unwrap(accounts.trader_account_b.to_account_info(), accounts.payer.to_account_info(), accounts.token_program_b.to_account_info())?;
+        }
+    }
 
     Ok(())
 }
@@ -221,25 +248,54 @@ pub fn simple_swap_exact_in(
 pub struct SimpleSwapExactIn<'info> {
 
     #[account(
+        mut,
         seeds = [
          
             mint_a.key().as_ref(),
             mint_b.key().as_ref(),
-            pool.admin.key().as_ref(),
-            &pool.lp_fee.to_le_bytes(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
         ],
         bump,
        
         has_one = mint_a,
         has_one = mint_b,
+        has_one = amm,
+        constraint = !pool.is_paused(PAUSE_SWAP) @ FTRXSwapError::PoolPaused,
+        constraint = !pool.locked @ FTRXSwapError::PoolLocked,
     )]
     pub pool: Account<'info, SimplePool>,
 
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        constraint = !amm.paused @ FTRXSwapError::AmmPaused,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        mut,
+        seeds = [
+            OBSERVATION_SEED.as_ref(),
+            pool.key().as_ref(),
+        ],
+        bump = observations.bump,
+    )]
+    pub observations: Box<Account<'info, PoolObservations>>,
 
 
-    pub mint_a: Box<Account<'info, Mint>>,
 
-    pub mint_b: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
@@ -251,7 +307,7 @@ pub struct SimpleSwapExactIn<'info> {
         ],
         bump
     )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -263,23 +319,25 @@ pub struct SimpleSwapExactIn<'info> {
         ],
         bump
     )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint_a,
         associated_token::authority = payer,
+        associated_token::token_program = token_program_a,
     )]
-    pub trader_account_a: Box<Account<'info, TokenAccount>>,
+    pub trader_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = mint_b,
         associated_token::authority = payer,
+        associated_token::token_program = token_program_b,
     )]
-    pub trader_account_b: Box<Account<'info, TokenAccount>>,
+    pub trader_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
 
     
@@ -290,12 +348,12 @@ pub struct SimpleSwapExactIn<'info> {
         pool.mint_a.key().as_ref(),
         pool.key().as_ref(),
         TREASURY_SEED.as_ref(),
-        pool.admin.key().as_ref(),
+        pool.admin_seed.as_ref(),
         ],
         bump,
 
       )]
-    pub treasury_mint_a: Box<Account<'info, TokenAccount>>,
+    pub treasury_mint_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(mut,
         token::mint = mint_b,
@@ -304,12 +362,39 @@ pub struct SimpleSwapExactIn<'info> {
         pool.mint_b.key().as_ref(),
         pool.key().as_ref(),
         TREASURY_SEED.as_ref(),
-        pool.admin.key().as_ref(),
+        pool.admin_seed.as_ref(),
         ],
         bump,
    
       )]
-    pub treasury_mint_b: Box<Account<'info, TokenAccount>>,
+    pub treasury_mint_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vaults accruing the creator share of the protocol fees
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
 
 
@@ -318,7 +403,9 @@ pub struct SimpleSwapExactIn<'info> {
     pub payer: Signer<'info>,
 
     /// Solana ecosystem accounts
-    pub token_program: Program<'info, Token>,
+    /// Token programs of mints A and B, legacy or Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
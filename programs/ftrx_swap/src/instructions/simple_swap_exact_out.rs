@@ -0,0 +1,336 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    constants::AMM_SEED,
+    constants::CURVE_CONSTANT_PRODUCT,
+    constants::OBSERVATION_SEED,
+    constants::PAUSE_SWAP,
+    constants::TREASURY_SEED,
+    constants::CREATOR_SEED,
+    errors::*,
+    events::Swap,
+    math,
+    state::{PoolObservations, SimpleAmm, SimplePool},
+    token_extensions,
+};
+
+pub fn simple_swap_exact_out(
+    ctx: Context<SimpleSwapExactOut>,
+    swap_a: bool,
+    output_amount: u64,
+    max_input_amount: u64,
+) -> Result<()> {
+
+    // Accumulate the prices of the reserves before they move
+    let (reserve_a, reserve_b) = (ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+    ctx.accounts.pool.update_price_cumulatives(reserve_a, reserve_b)?;
+    ctx.accounts.observations.record(&ctx.accounts.pool);
+
+    // Dynamic fee pools charge the fee of the decayed volatility
+    ctx.accounts.pool.decay_volatility()?;
+    let lp_fee_rate = ctx.accounts.pool.dynamic_lp_fee();
+
+    // Compute the invariant before the trade
+    let invariant_before_trade = ctx.accounts.pool.invariant(reserve_a, reserve_b)?;
+
+    let accounts = &ctx.accounts;
+    let (trader_in, trader_out, vault_in, vault_out, treasury_in, creator_in, mint_in, mint_out, program_in, program_out) = if swap_a {
+        (&accounts.trader_account_a, &accounts.trader_account_b, &accounts.pool_account_a, &accounts.pool_account_b,
+            &accounts.treasury_mint_a, &accounts.creator_vault_a, &accounts.mint_a, &accounts.mint_b, &accounts.token_program_a, &accounts.token_program_b)
+    } else {
+        (&accounts.trader_account_b, &accounts.trader_account_a, &accounts.pool_account_b, &accounts.pool_account_a,
+            &accounts.treasury_mint_b, &accounts.creator_vault_b, &accounts.mint_b, &accounts.mint_a, &accounts.token_program_b, &accounts.token_program_a)
+    };
+
+    // Transfer fee mints credit less than what was sent, the pool sends
+    // enough for the trader to receive output_amount
+    let raw_output = output_amount
+        .checked_add(token_extensions::inverse_transfer_fee(&mint_out.to_account_info(), output_amount)?)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    // Input needed in the vault for the requested output, fees included
+    // Any output above output_amount due to rounding stays in the pool
+    let actual_pool=&accounts.pool;
+    let swap_amounts = math::swap_exact_out(vault_in.amount, vault_out.amount, raw_output, lp_fee_rate, actual_pool.protocol_fee)?;
+    let to_creator_fee = actual_pool.creator_fee(swap_amounts.protocol_fee);
+    let to_treasury_fee = swap_amounts.protocol_fee - to_creator_fee;
+
+    // Slippage is checked on what the trader ends up paying
+    let input = swap_amounts.input
+        .checked_add(token_extensions::inverse_transfer_fee(&mint_in.to_account_info(), swap_amounts.input)?)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    if input > max_input_amount {
+        return err!(FTRXSwapError::SlippageExceeded);
+    }
+
+    let lp_fee_seed=actual_pool.lp_fee_seed.to_le_bytes();
+
+    let authority_seeds = &[
+        actual_pool.mint_a.as_ref(),
+        actual_pool.mint_b.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
+        &[actual_pool.pool_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    // Transfer tokens to the pool
+    token_interface::transfer_checked(
+        CpiContext::new(
+            program_in.to_account_info(),
+            TransferChecked {
+                from: trader_in.to_account_info(),
+                mint: mint_in.to_account_info(),
+                to: vault_in.to_account_info(),
+                authority: accounts.payer.to_account_info(),
+            },
+        ),
+        input,
+        mint_in.decimals,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            program_out.to_account_info(),
+            TransferChecked {
+                from: vault_out.to_account_info(),
+                mint: mint_out.to_account_info(),
+                to: trader_out.to_account_info(),
+                authority: accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        raw_output,
+        mint_out.decimals,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            program_in.to_account_info(),
+            TransferChecked {
+                from: vault_in.to_account_info(),
+                mint: mint_in.to_account_info(),
+                to: treasury_in.to_account_info(),
+                authority: accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        to_treasury_fee,
+        mint_in.decimals,
+    )?;
+
+    if to_creator_fee > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                program_in.to_account_info(),
+                TransferChecked {
+                    from: vault_in.to_account_info(),
+                    mint: mint_in.to_account_info(),
+                    to: creator_in.to_account_info(),
+                    authority: accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            to_creator_fee,
+            mint_in.decimals,
+        )?;
+    }
+
+    // Verify the invariant still holds
+    // Reload accounts because of the CPIs
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+
+    let invariant_after_trade = ctx.accounts.pool.invariant(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)?;
+    if invariant_before_trade > invariant_after_trade {
+        return err!(FTRXSwapError::InvariantViolated);
+    }
+
+    // The move of this trade raises the fee of the next ones
+    let pool = &mut ctx.accounts.pool;
+    if pool.dynamic_fee {
+        pool.accumulate_volatility(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+        msg!(
+            "dynamic lp fee {} volatility accumulator {}",
+            lp_fee_rate,
+            pool.volatility_accumulator
+        );
+    }
+
+    emit!(Swap {
+        pool: ctx.accounts.pool.key(),
+        trader: ctx.accounts.payer.key(),
+        swap_a,
+        input: swap_amounts.input,
+        // What the trader received, the rounding surplus stays in the pool
+        output: output_amount,
+        lp_fee: swap_amounts.lp_fee,
+        protocol_fee: swap_amounts.protocol_fee,
+        reserve_a: ctx.accounts.pool_account_a.amount,
+        reserve_b: ctx.accounts.pool_account_b.amount,
+        lp_fee_rate,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SimpleSwapExactOut<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+            mint_a.key().as_ref(),
+            mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        constraint = !pool.is_paused(PAUSE_SWAP) @ FTRXSwapError::PoolPaused,
+        constraint = !pool.locked @ FTRXSwapError::PoolLocked,
+        constraint = pool.curve == CURVE_CONSTANT_PRODUCT @ FTRXSwapError::UnsupportedCurve,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        constraint = !amm.paused @ FTRXSwapError::AmmPaused,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        mut,
+        seeds = [
+            OBSERVATION_SEED.as_ref(),
+            pool.key().as_ref(),
+        ],
+        bump = observations.bump,
+    )]
+    pub observations: Box<Account<'info, PoolObservations>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_a,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program_a,
+    )]
+    pub trader_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_b,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program_b,
+    )]
+    pub trader_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vaults accruing the creator share of the protocol fees
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    /// Token programs of mints A and B, legacy or Token-2022
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,379 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_2022::Token2022,
+    token_interface::{self, Mint, TokenAccount, TransferChecked},
+};
+
+use crate::{
+    constants::{AMM_SEED, PAUSE_SWAP},
+    errors::*,
+    events::Swap,
+    math,
+    state::{PoolObservations, SimpleAmm, SimplePool},
+    token_extensions,
+};
+
+/// Accounts expected in remaining_accounts for each hop, in this order:
+/// pool, mint_a, mint_b, pool_account_a, pool_account_b, treasury_mint_a,
+/// treasury_mint_b, creator_vault_a, creator_vault_b, observations
+const ACCOUNTS_PER_HOP: usize = 10;
+
+struct Hop<'info> {
+    pool: Account<'info, SimplePool>,
+    mint_a: Box<InterfaceAccount<'info, Mint>>,
+    mint_b: Box<InterfaceAccount<'info, Mint>>,
+    pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    treasury_mint_a: AccountInfo<'info>,
+    treasury_mint_b: AccountInfo<'info>,
+    creator_vault_a: AccountInfo<'info>,
+    creator_vault_b: AccountInfo<'info>,
+    observations: Box<Account<'info, PoolObservations>>,
+    swap_a: bool,
+    swap_amounts: math::SwapAmounts,
+    lp_fee_rate: u16,
+    invariant_before_trade: u128,
+}
+
+impl<'info> Hop<'info> {
+    fn mint_in(&self) -> &InterfaceAccount<'info, Mint> {
+        if self.swap_a {
+            &self.mint_a
+        } else {
+            &self.mint_b
+        }
+    }
+
+    fn mint_out(&self) -> &InterfaceAccount<'info, Mint> {
+        if self.swap_a {
+            &self.mint_b
+        } else {
+            &self.mint_a
+        }
+    }
+
+    fn vault_in(&self) -> AccountInfo<'info> {
+        if self.swap_a {
+            self.pool_account_a.to_account_info()
+        } else {
+            self.pool_account_b.to_account_info()
+        }
+    }
+
+    fn vault_out(&self) -> AccountInfo<'info> {
+        if self.swap_a {
+            self.pool_account_b.to_account_info()
+        } else {
+            self.pool_account_a.to_account_info()
+        }
+    }
+
+    fn treasury_in(&self) -> AccountInfo<'info> {
+        if self.swap_a {
+            self.treasury_mint_a.clone()
+        } else {
+            self.treasury_mint_b.clone()
+        }
+    }
+
+    fn creator_vault_in(&self) -> AccountInfo<'info> {
+        if self.swap_a {
+            self.creator_vault_a.clone()
+        } else {
+            self.creator_vault_b.clone()
+        }
+    }
+}
+
+pub fn simple_swap_routed<'info>(
+    ctx: Context<'_, '_, '_, 'info, SimpleSwapRouted<'info>>,
+    input_amount: u64,
+    min_output_amount: u64,
+) -> Result<()> {
+    let remaining_accounts = ctx.remaining_accounts;
+    let hop_count = remaining_accounts.len() / ACCOUNTS_PER_HOP;
+    if hop_count == 0 || hop_count * ACCOUNTS_PER_HOP != remaining_accounts.len() {
+        return err!(FTRXSwapError::InvalidRoute);
+    }
+
+    // Prevent depositing assets the depositor does not own
+    let input = if input_amount > ctx.accounts.trader_source.amount {
+        ctx.accounts.trader_source.amount
+    } else {
+        input_amount
+    };
+
+    // Chain the exact in math over every hop, each pool charging its own fees
+    let mut hops: Vec<Hop> = Vec::with_capacity(hop_count);
+    let mut current_mint = ctx.accounts.trader_source.mint;
+    let mut current_amount = input;
+
+    for hop_accounts in remaining_accounts.chunks(ACCOUNTS_PER_HOP) {
+        let mut pool: Account<SimplePool> = Account::try_from(&hop_accounts[0])?;
+
+        if pool.amm != ctx.accounts.amm.key() {
+            return err!(FTRXSwapError::InvalidRoute);
+        }
+
+        if pool.is_paused(PAUSE_SWAP) {
+            return err!(FTRXSwapError::PoolPaused);
+        }
+
+        if pool.locked {
+            return err!(FTRXSwapError::PoolLocked);
+        }
+
+        // A pool used twice would be priced on stale reserves
+        if hops.iter().any(|hop| hop.pool.key() == pool.key()) {
+            return err!(FTRXSwapError::InvalidRoute);
+        }
+
+        let mint_a: Box<InterfaceAccount<Mint>> = Box::new(InterfaceAccount::try_from(&hop_accounts[1])?);
+        let mint_b: Box<InterfaceAccount<Mint>> = Box::new(InterfaceAccount::try_from(&hop_accounts[2])?);
+        let pool_account_a: Box<InterfaceAccount<TokenAccount>> = Box::new(InterfaceAccount::try_from(&hop_accounts[3])?);
+        let pool_account_b: Box<InterfaceAccount<TokenAccount>> = Box::new(InterfaceAccount::try_from(&hop_accounts[4])?);
+        let treasury_mint_a = hop_accounts[5].clone();
+        let treasury_mint_b = hop_accounts[6].clone();
+        let creator_vault_a = hop_accounts[7].clone();
+        let creator_vault_b = hop_accounts[8].clone();
+        let mut observations: Box<Account<PoolObservations>> = Box::new(Account::try_from(&hop_accounts[9])?);
+
+        if mint_a.key() != pool.mint_a
+            || mint_b.key() != pool.mint_b
+            || pool_account_a.key() != pool.vault_mint_a
+            || pool_account_b.key() != pool.vault_mint_b
+            || treasury_mint_a.key() != pool.treasury_mint_a
+            || treasury_mint_b.key() != pool.treasury_mint_b
+            || creator_vault_a.key() != pool.creator_vault_a
+            || creator_vault_b.key() != pool.creator_vault_b
+            || observations.pool != pool.key()
+        {
+            return err!(FTRXSwapError::InvalidRoute);
+        }
+
+        // Accumulate the prices of the reserves before they move
+        pool.update_price_cumulatives(pool_account_a.amount, pool_account_b.amount)?;
+        observations.record(&pool);
+
+        let swap_a = if current_mint == pool.mint_a {
+            true
+        } else if current_mint == pool.mint_b {
+            false
+        } else {
+            return err!(FTRXSwapError::InvalidMint);
+        };
+
+        // Dynamic fee pools charge the fee of the decayed volatility
+        pool.decay_volatility()?;
+        let lp_fee_rate = pool.dynamic_lp_fee();
+
+        // Transfer fee mints credit the vault less than what was sent
+        let mint_in = if swap_a { &mint_a } else { &mint_b };
+        let received_amount = current_amount - token_extensions::transfer_fee(&mint_in.to_account_info(), current_amount)?;
+
+        let swap_amounts = if swap_a {
+            pool.swap_exact_in_with_fee(true, pool_account_a.amount, pool_account_b.amount, received_amount, lp_fee_rate)?
+        } else {
+            pool.swap_exact_in_with_fee(false, pool_account_b.amount, pool_account_a.amount, received_amount, lp_fee_rate)?
+        };
+
+        // Compute the invariant before the trade
+        let invariant_before_trade = pool.invariant(pool_account_a.amount, pool_account_b.amount)?;
+
+        current_mint = if swap_a { pool.mint_b } else { pool.mint_a };
+        current_amount = swap_amounts.output;
+
+        hops.push(Hop {
+            pool,
+            mint_a,
+            mint_b,
+            pool_account_a,
+            pool_account_b,
+            treasury_mint_a,
+            treasury_mint_b,
+            creator_vault_a,
+            creator_vault_b,
+            observations,
+            swap_a,
+            swap_amounts,
+            lp_fee_rate,
+            invariant_before_trade,
+        });
+    }
+
+    if current_mint != ctx.accounts.trader_destination.mint {
+        return err!(FTRXSwapError::InvalidMint);
+    }
+
+    // Slippage is only checked on what the trader receives from the final leg
+    let last_mint = hops[hop_count - 1].mint_out().to_account_info();
+    let received_output = current_amount - token_extensions::transfer_fee(&last_mint, current_amount)?;
+    if received_output < min_output_amount {
+        return err!(FTRXSwapError::OutputTooSmall);
+    }
+
+    // Each mint is moved by the token program owning it
+    let token_program_of = |mint: &InterfaceAccount<Mint>| {
+        if *mint.to_account_info().owner == ctx.accounts.token_program_2022.key() {
+            ctx.accounts.token_program_2022.to_account_info()
+        } else {
+            ctx.accounts.token_program.to_account_info()
+        }
+    };
+
+    // Transfer the input to the first pool
+    let first_mint = hops[0].mint_in();
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program_of(first_mint),
+            TransferChecked {
+                from: ctx.accounts.trader_source.to_account_info(),
+                mint: first_mint.to_account_info(),
+                to: hops[0].vault_in(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        input,
+        first_mint.decimals,
+    )?;
+
+    // Each pool sends its treasury and creator cuts and forwards its output to the next
+    // pool's vault, the last one pays the trader
+    for (index, hop) in hops.iter().enumerate() {
+        let actual_pool = &hop.pool;
+        let lp_fee_seed = actual_pool.lp_fee_seed.to_le_bytes();
+
+        let authority_seeds = &[
+            actual_pool.mint_a.as_ref(),
+            actual_pool.mint_b.as_ref(),
+            actual_pool.admin_seed.as_ref(),
+            lp_fee_seed.as_ref(),
+            &[actual_pool.pool_bump],
+        ];
+        let signer_seeds = &[&authority_seeds[..]];
+
+        let creator_fee = actual_pool.creator_fee(hop.swap_amounts.protocol_fee);
+        let destination = match hops.get(index + 1) {
+            Some(next_hop) => next_hop.vault_in(),
+            None => ctx.accounts.trader_destination.to_account_info(),
+        };
+
+        let (mint_in, mint_out) = (hop.mint_in(), hop.mint_out());
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program_of(mint_out),
+                TransferChecked {
+                    from: hop.vault_out(),
+                    mint: mint_out.to_account_info(),
+                    to: destination,
+                    authority: hop.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            hop.swap_amounts.output,
+            mint_out.decimals,
+        )?;
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program_of(mint_in),
+                TransferChecked {
+                    from: hop.vault_in(),
+                    mint: mint_in.to_account_info(),
+                    to: hop.treasury_in(),
+                    authority: hop.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            hop.swap_amounts.protocol_fee - creator_fee,
+            mint_in.decimals,
+        )?;
+
+        if creator_fee > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    token_program_of(mint_in),
+                    TransferChecked {
+                        from: hop.vault_in(),
+                        mint: mint_in.to_account_info(),
+                        to: hop.creator_vault_in(),
+                        authority: hop.pool.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                creator_fee,
+                mint_in.decimals,
+            )?;
+        }
+    }
+
+    // Verify the invariant still holds for every pool
+    // Reload accounts because of the CPIs
+    for hop in hops.iter_mut() {
+        hop.pool_account_a.reload()?;
+        hop.pool_account_b.reload()?;
+
+        let invariant_after_trade = hop.pool.invariant(hop.pool_account_a.amount, hop.pool_account_b.amount)?;
+
+        if hop.invariant_before_trade > invariant_after_trade {
+            return err!(FTRXSwapError::InvariantViolated);
+        }
+
+        // The move of this trade raises the fee of the next ones
+        hop.pool.accumulate_volatility(hop.pool_account_a.amount, hop.pool_account_b.amount);
+
+        // Accounts outside the context are not written back by Anchor
+        hop.pool.exit(ctx.program_id)?;
+        hop.observations.exit(ctx.program_id)?;
+
+        emit!(Swap {
+            pool: hop.pool.key(),
+            trader: ctx.accounts.payer.key(),
+            swap_a: hop.swap_a,
+            input: hop.swap_amounts.input,
+            output: hop.swap_amounts.output,
+            lp_fee: hop.swap_amounts.lp_fee,
+            protocol_fee: hop.swap_amounts.protocol_fee,
+            reserve_a: hop.pool_account_a.amount,
+            reserve_b: hop.pool_account_b.amount,
+            lp_fee_rate: hop.lp_fee_rate,
+        });
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SimpleSwapRouted<'info> {
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        constraint = !amm.paused @ FTRXSwapError::AmmPaused,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    /// Token account holding the input of the first hop
+    #[account(
+        mut,
+        token::authority = payer,
+    )]
+    pub trader_source: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token account receiving the output of the last hop
+    #[account(mut)]
+    pub trader_destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    /// Both token programs, each hop moving its mints with the one owning them
+    pub token_program: Program<'info, Token>,
+    pub token_program_2022: Program<'info, Token2022>,
+}
@@ -1,23 +1,32 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Burn, Mint, Token, TokenAccount, Transfer},
+    token_interface::{self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 use fixed::types::I64F64;
-use fixed_sqrt::FixedSqrt;
 
 use crate::{
-    constants::{AUTHORITY_SEED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY},
+    constants::{AMM_SEED, CURVE_CONCENTRATED, LIQUIDITY_SEED, MINIMUM_LIQUIDITY, OBSERVATION_SEED, PAUSE_WITHDRAW},
     errors::FTRXSwapError,
-    state::SimplePool,
+    events::LiquidityWithdrawn,
+    math,
+    native_sol,
+    state::{Deadline, PoolObservations, SimpleAmm, SimplePool},
+    token_extensions,
 };
 
 pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, amount: u64, amount_expected_a: u64, amount_expected_b: u64) -> Result<()> {
+
+    // Accumulate the prices of the reserves before they move
+    let (reserve_a, reserve_b) = (ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+    ctx.accounts.pool.update_price_cumulatives(reserve_a, reserve_b)?;
+    ctx.accounts.observations.record(&ctx.accounts.pool);
+
    
 
 
     let actual_pool=&ctx.accounts.pool;
-    let lp_fee=actual_pool.lp_fee.to_le_bytes();
+    let lp_fee_seed=actual_pool.lp_fee_seed.to_le_bytes();
     
     let pool_a = &ctx.accounts.pool_account_a;
     let pool_b = &ctx.accounts.pool_account_b;
@@ -30,8 +39,8 @@ pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, amount: u64, amount_e
     let authority_seeds = &[
         actual_pool.mint_a.as_ref(),
         actual_pool.mint_b.as_ref(),
-        actual_pool.admin.as_ref(),
-        lp_fee.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
         &[actual_pool.pool_bump],
     ];
     let signer_seeds = &[&authority_seeds[..]];
@@ -45,55 +54,43 @@ pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, amount: u64, amount_e
 
     
     // Transfer tokens from the pool
-    let amount_a = I64F64::from_num(amount)
-        .checked_mul(I64F64::from_num(ctx.accounts.pool_account_a.amount))
-        .unwrap()
-        .checked_div(I64F64::from_num(
-            mint_liquidity_supply_before 
-        ))
-        .unwrap()
-        .floor()
-        .to_num::<u64>();
-    token::transfer(
+    let amount_a = math::withdraw_amount(amount, ctx.accounts.pool_account_a.amount, mint_liquidity_supply_before)?;
+    token_interface::transfer_checked(
         CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.token_program_a.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.pool_account_a.to_account_info(),
+                mint: ctx.accounts.mint_a.to_account_info(),
                 to: ctx.accounts.depositor_account_a.to_account_info(),
                 authority: ctx.accounts.pool.to_account_info(),
             },
             signer_seeds,
         ),
         amount_a,
+        ctx.accounts.mint_a.decimals,
     )?;
 
-    let amount_b = I64F64::from_num(amount)
-        .checked_mul(I64F64::from_num(ctx.accounts.pool_account_b.amount))
-        .unwrap()
-        .checked_div(I64F64::from_num(
-            mint_liquidity_supply_before,
-        ))
-        .unwrap()
-        .floor()
-        .to_num::<u64>();
+    let amount_b = math::withdraw_amount(amount, ctx.accounts.pool_account_b.amount, mint_liquidity_supply_before)?;
 
 
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.token_program_b.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.pool_account_b.to_account_info(),
+                mint: ctx.accounts.mint_b.to_account_info(),
                 to: ctx.accounts.depositor_account_b.to_account_info(),
                 authority: ctx.accounts.pool.to_account_info(),
             },
             signer_seeds,
         ),
         amount_b,
+        ctx.accounts.mint_b.decimals,
     )?;
 
     // Burn the liquidity tokens
     // It will fail if the amount is invalid
-    token::burn(
+    token_interface::burn(
         CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             Burn {
@@ -108,11 +105,15 @@ pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, amount: u64, amount_e
 
 
 
-    if amount_expected_a>amount_a{
+    // Slippage is checked on what the depositor ends up with
+    let received_a = amount_a - token_extensions::transfer_fee(&ctx.accounts.mint_a.to_account_info(), amount_a)?;
+    let received_b = amount_b - token_extensions::transfer_fee(&ctx.accounts.mint_b.to_account_info(), amount_b)?;
+
+    if amount_expected_a>received_a{
         return err!(FTRXSwapError::SlippageExceeded);
     }
 
-    if amount_expected_b>amount_b{
+    if amount_expected_b>received_b{
         return err!(FTRXSwapError::SlippageExceeded);
     }
 
@@ -140,7 +141,57 @@ pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, amount: u64, amount_e
         ratio_token_b_check
     );
 
-   
+    ctx.accounts.mint_liquidity.reload()?;
+
+    emit!(LiquidityWithdrawn {
+        pool: ctx.accounts.pool.key(),
+        owner: ctx.accounts.payer.key(),
+        amount_a,
+        amount_b,
+        lp_burned: amount,
+        lp_supply: ctx.accounts.mint_liquidity.supply,
+    });
+
+    Ok(())
+}
+
+/// Same as withdraw_liquidity, rejected once the deadline has passed
+pub fn withdraw_liquidity_with_deadline(
+    ctx: Context<WithdrawLiquidity>,
+    amount: u64,
+    amount_expected_a: u64,
+    amount_expected_b: u64,
+    deadline: Deadline,
+) -> Result<()> {
+    deadline.check()?;
+    withdraw_liquidity(ctx, amount, amount_expected_a, amount_expected_b)
+}
+
+/// Same as withdraw_liquidity, the wSOL account of a NATIVE_MINT side being
+/// closed afterwards when unwrap is set so it is received as lamports
+pub fn withdraw_liquidity_native<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawLiquidity<'info>>,
+    amount: u64,
+    amount_expected_a: u64,
+    amount_expected_b: u64,
+    unwrap: bool,
+) -> Result<()> {
+    withdraw_liquidity(
+        Context::new(ctx.program_id, &mut *ctx.accounts, ctx.remaining_accounts, ctx.bumps.clone()),
+        amount,
+        amount_expected_a,
+        amount_expected_b,
+    )?;
+
+    if unwrap {
+        let accounts = &ctx.accounts;
+        if native_sol::is_native(&accounts.mint_a.key()) {
+            native_sol::unwrap(accounts.depositor_account_a.to_account_info(), accounts.payer.to_account_info(), accounts.token_program_a.to_account_info())?;
+        }
+        if native_sol::is_native(&accounts.mint_b.key()) {
+            native_sol::unwrap(accounts.depositor_account_b.to_account_info(), accounts.payer.to_account_info(), accounts.token_program_b.to_account_info())?;
+        }
+    }
 
     Ok(())
 }
@@ -149,20 +200,44 @@ pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, amount: u64, amount_e
 pub struct WithdrawLiquidity<'info> {
 
     #[account(
+        mut,
         seeds = [
        
         mint_a.key().as_ref(),
         mint_b.key().as_ref(),
-        pool.admin.key().as_ref(),
-        &pool.lp_fee.to_le_bytes(),
+        pool.admin_seed.as_ref(),
+        &pool.lp_fee_seed.to_le_bytes(),
 
         ],
         bump,
         has_one = mint_a,
         has_one = mint_b,
+        has_one = amm,
+        constraint = !pool.is_paused(PAUSE_WITHDRAW) @ FTRXSwapError::PoolPaused,
+        constraint = !pool.locked @ FTRXSwapError::PoolLocked,
+        constraint = pool.curve != CURVE_CONCENTRATED @ FTRXSwapError::UnsupportedCurve,
     )]
     pub pool: Account<'info, SimplePool>,
 
+    /// Not checked for the global pause, LPs can always exit
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        mut,
+        seeds = [
+            OBSERVATION_SEED.as_ref(),
+            pool.key().as_ref(),
+        ],
+        bump = observations.bump,
+    )]
+    pub observations: Box<Account<'info, PoolObservations>>,
+
 
 
     #[account(
@@ -171,19 +246,25 @@ pub struct WithdrawLiquidity<'info> {
         
         mint_a.key().as_ref(),
         mint_b.key().as_ref(),
-        pool.admin.key().as_ref(),
+        pool.admin_seed.as_ref(),
+        &pool.lp_fee_seed.to_le_bytes(),
         LIQUIDITY_SEED.as_ref(),
 
         ],
         bump,
+        mint::token_program = token_program,
     )]
-    pub mint_liquidity: Box<Account<'info, Mint>>,
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
 
-    #[account(mut)]
-    pub mint_a: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
 
-    #[account(mut)]
-    pub mint_b: Box<Account<'info, Mint>>,
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         mut,
@@ -195,7 +276,7 @@ pub struct WithdrawLiquidity<'info> {
         ],
         bump
     )]
-    pub pool_account_a: Box<Account<'info, TokenAccount>>,
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -207,35 +288,43 @@ pub struct WithdrawLiquidity<'info> {
         ],
         bump
     )]
-    pub pool_account_b: Box<Account<'info, TokenAccount>>,
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         associated_token::mint = mint_liquidity,
         associated_token::authority = payer,
+        associated_token::token_program = token_program,
     )]
-    pub depositor_account_liquidity: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
-        mut,
+        init_if_needed,
+        payer = payer,
         associated_token::mint = mint_a,
         associated_token::authority = payer,
+        associated_token::token_program = token_program_a,
     )]
-    pub depositor_account_a: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
-        mut,
+        init_if_needed,
+        payer = payer,
         associated_token::mint = mint_b,
         associated_token::authority = payer,
+        associated_token::token_program = token_program_b,
     )]
-    pub depositor_account_b: Box<Account<'info, TokenAccount>>,
+    pub depositor_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// The account paying for all rents
     #[account(mut)]
     pub payer: Signer<'info>,
 
     /// Solana ecosystem accounts
-    pub token_program: Program<'info, Token>,
+    /// Token programs of the LP mint and of mints A and B, legacy or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
@@ -0,0 +1,394 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use fixed::types::I64F64;
+
+use crate::{
+    constants::{AMM_SEED, CREATOR_SEED, CURVE_CONSTANT_PRODUCT, LIQUIDITY_SEED, MINIMUM_LIQUIDITY, OBSERVATION_SEED, PAUSE_SWAP, PAUSE_WITHDRAW, TREASURY_SEED},
+    errors::FTRXSwapError,
+    events::LiquidityWithdrawn,
+    math,
+    state::{PoolObservations, SimpleAmm, SimplePool},
+    token_extensions,
+};
+
+pub fn withdraw_single_sided(
+    ctx: Context<WithdrawSingleSided>,
+    lp_amount: u64,
+    want_a: bool,
+    min_out: u64,
+) -> Result<()> {
+
+    // Accumulate the prices of the reserves before they move
+    let (reserve_a, reserve_b) = (ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+    ctx.accounts.pool.update_price_cumulatives(reserve_a, reserve_b)?;
+    ctx.accounts.observations.record(&ctx.accounts.pool);
+
+
+    let expected_mint = if want_a { ctx.accounts.mint_a.key() } else { ctx.accounts.mint_b.key() };
+    if ctx.accounts.depositor_destination.mint != expected_mint {
+        return err!(FTRXSwapError::InvalidMint);
+    }
+
+    // Dynamic fee pools charge the fee of the decayed volatility
+    ctx.accounts.pool.decay_volatility()?;
+    let lp_fee_rate = ctx.accounts.pool.dynamic_lp_fee();
+
+    let actual_pool=&ctx.accounts.pool;
+
+    let (reserve_wanted, reserve_unwanted) = if want_a {
+        (ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount)
+    } else {
+        (ctx.accounts.pool_account_b.amount, ctx.accounts.pool_account_a.amount)
+    };
+
+    let  mint_liquidity_supply_before = ctx.accounts.mint_liquidity.supply + MINIMUM_LIQUIDITY;
+
+    // Pro-rata share of both sides, same rounding as withdraw_liquidity
+    let share_wanted = math::withdraw_amount(lp_amount, reserve_wanted, mint_liquidity_supply_before)?;
+    let share_unwanted = math::withdraw_amount(lp_amount, reserve_unwanted, mint_liquidity_supply_before)?;
+
+    // Checks : we want to have liquidity_reduction/liquidity_before>amount_withdrawn/amount_before on both sides
+    let ratio_liquidity_check=I64F64::from_num(lp_amount).checked_div(I64F64::from_num(mint_liquidity_supply_before)).unwrap();
+    let ratio_wanted_check=I64F64::from_num(share_wanted).checked_div(I64F64::from_num(reserve_wanted)).unwrap();
+    let ratio_unwanted_check=I64F64::from_num(share_unwanted).checked_div(I64F64::from_num(reserve_unwanted)).unwrap();
+    if ratio_liquidity_check<ratio_wanted_check || ratio_liquidity_check<ratio_unwanted_check{
+        return err!(FTRXSwapError::InconsistentPriceRatioLiquidity);
+    }
+
+    // Sell the unwanted share back to the remaining reserves, fees included
+    let reserve_wanted_after_withdraw = reserve_wanted - share_wanted;
+    let reserve_unwanted_after_withdraw = reserve_unwanted - share_unwanted;
+
+    let swap_amounts = if share_unwanted > 0 {
+        math::swap_exact_in(
+            reserve_unwanted_after_withdraw,
+            reserve_wanted_after_withdraw,
+            share_unwanted,
+            lp_fee_rate,
+            actual_pool.protocol_fee,
+        )?
+    } else {
+        math::SwapAmounts::default()
+    };
+
+    let output = share_wanted
+        .checked_add(swap_amounts.output)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    let (mint_wanted, mint_unwanted, program_wanted, program_unwanted) = if want_a {
+        (&ctx.accounts.mint_a, &ctx.accounts.mint_b, &ctx.accounts.token_program_a, &ctx.accounts.token_program_b)
+    } else {
+        (&ctx.accounts.mint_b, &ctx.accounts.mint_a, &ctx.accounts.token_program_b, &ctx.accounts.token_program_a)
+    };
+
+    // Slippage is checked on what the owner ends up with
+    let received_output = output - token_extensions::transfer_fee(&mint_wanted.to_account_info(), output)?;
+    if received_output < min_out {
+        return err!(FTRXSwapError::SlippageExceeded);
+    }
+
+    // Compute the invariant the swap starts from
+    let invariant_before_trade = (reserve_wanted_after_withdraw as u128) * (reserve_unwanted_after_withdraw as u128);
+
+    let lp_fee_seed=actual_pool.lp_fee_seed.to_le_bytes();
+
+    let authority_seeds = &[
+        actual_pool.mint_a.as_ref(),
+        actual_pool.mint_b.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
+        &[actual_pool.pool_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    let (vault_wanted, vault_unwanted, treasury_unwanted, creator_unwanted) = if want_a {
+        (
+            ctx.accounts.pool_account_a.to_account_info(),
+            ctx.accounts.pool_account_b.to_account_info(),
+            ctx.accounts.treasury_mint_b.to_account_info(),
+            ctx.accounts.creator_vault_b.to_account_info(),
+        )
+    } else {
+        (
+            ctx.accounts.pool_account_b.to_account_info(),
+            ctx.accounts.pool_account_a.to_account_info(),
+            ctx.accounts.treasury_mint_a.to_account_info(),
+            ctx.accounts.creator_vault_a.to_account_info(),
+        )
+    };
+    let creator_fee = actual_pool.creator_fee(swap_amounts.protocol_fee);
+
+    // Burn the liquidity tokens
+    // It will fail if the amount is invalid
+    token_interface::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.mint_liquidity.to_account_info(),
+                from: ctx.accounts.depositor_account_liquidity.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        lp_amount,
+    )?;
+
+    // Transfer the wanted share and the swap output from the pool
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            program_wanted.to_account_info(),
+            TransferChecked {
+                from: vault_wanted,
+                mint: mint_wanted.to_account_info(),
+                to: ctx.accounts.depositor_destination.to_account_info(),
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        output,
+        mint_wanted.decimals,
+    )?;
+
+    // The unwanted share stays in the vault apart from the treasury cut
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            program_unwanted.to_account_info(),
+            TransferChecked {
+                from: vault_unwanted.clone(),
+                mint: mint_unwanted.to_account_info(),
+                to: treasury_unwanted,
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        swap_amounts.protocol_fee - creator_fee,
+        mint_unwanted.decimals,
+    )?;
+
+    if creator_fee > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                program_unwanted.to_account_info(),
+                TransferChecked {
+                    from: vault_unwanted,
+                    mint: mint_unwanted.to_account_info(),
+                    to: creator_unwanted,
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            creator_fee,
+            mint_unwanted.decimals,
+        )?;
+    }
+
+    // Verify the invariant still holds for the swap part
+    // Reload accounts because of the CPIs
+    ctx.accounts.pool_account_a.reload()?;
+    ctx.accounts.pool_account_b.reload()?;
+    ctx.accounts.mint_liquidity.reload()?;
+
+    let invariant_after_trade = (ctx.accounts.pool_account_a.amount as u128) * (ctx.accounts.pool_account_b.amount as u128);
+    if invariant_before_trade > invariant_after_trade {
+        return err!(FTRXSwapError::InvariantViolated);
+    }
+
+    // The move of the internal swap raises the fee of the next trades
+    let pool = &mut ctx.accounts.pool;
+    if pool.dynamic_fee {
+        pool.accumulate_volatility(ctx.accounts.pool_account_a.amount, ctx.accounts.pool_account_b.amount);
+        msg!(
+            "dynamic lp fee {} volatility accumulator {}",
+            lp_fee_rate,
+            pool.volatility_accumulator
+        );
+    }
+
+    msg!(
+        " liquidity ratio {} share {} swapped {} output {}",
+        ratio_liquidity_check,
+        share_wanted,
+        share_unwanted,
+        output
+    );
+
+    emit!(LiquidityWithdrawn {
+        pool: ctx.accounts.pool.key(),
+        owner: ctx.accounts.payer.key(),
+        amount_a: if want_a { output } else { 0 },
+        amount_b: if want_a { 0 } else { output },
+        lp_burned: lp_amount,
+        lp_supply: ctx.accounts.mint_liquidity.supply,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSingleSided<'info> {
+
+    #[account(
+        mut,
+        seeds = [
+        mint_a.key().as_ref(),
+        mint_b.key().as_ref(),
+        pool.admin_seed.as_ref(),
+        &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = mint_a,
+        has_one = mint_b,
+        has_one = amm,
+        constraint = !pool.is_paused(PAUSE_WITHDRAW) @ FTRXSwapError::PoolPaused,
+        constraint = !pool.is_paused(PAUSE_SWAP) @ FTRXSwapError::PoolPaused,
+        constraint = !pool.locked @ FTRXSwapError::PoolLocked,
+        constraint = pool.curve == CURVE_CONSTANT_PRODUCT @ FTRXSwapError::UnsupportedCurve,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        constraint = !amm.paused @ FTRXSwapError::AmmPaused,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        mut,
+        seeds = [
+            OBSERVATION_SEED.as_ref(),
+            pool.key().as_ref(),
+        ],
+        bump = observations.bump,
+    )]
+    pub observations: Box<Account<'info, PoolObservations>>,
+
+    #[account(
+        mut,
+        seeds = [
+        mint_a.key().as_ref(),
+        mint_b.key().as_ref(),
+        pool.admin_seed.as_ref(),
+        &pool.lp_fee_seed.to_le_bytes(),
+        LIQUIDITY_SEED.as_ref(),
+        ],
+        bump,
+        mint::token_program = token_program,
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_a,
+    )]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program_b,
+    )]
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        TREASURY_SEED.as_ref(),
+        pool.admin_seed.as_ref(),
+        ],
+        bump,
+      )]
+    pub treasury_mint_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vaults accruing the creator share of the protocol fees
+    #[account(mut,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [
+        pool.mint_a.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [
+        pool.mint_b.key().as_ref(),
+        pool.key().as_ref(),
+        CREATOR_SEED.as_ref(),
+        pool.creator.as_ref(),
+        ],
+        bump,
+      )]
+    pub creator_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_liquidity,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub depositor_account_liquidity: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token account receiving the whole withdrawal, of mint a if want_a
+    #[account(mut)]
+    pub depositor_destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The account paying for all rents
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    /// Token programs of the LP mint and of mints A and B, legacy or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_a: Interface<'info, TokenInterface>,
+    pub token_program_b: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{AMM_SEED, FEE_TIER_SEED},
+    errors::*,
+    events::FeeTierUpdated,
+    state::{FeeTier, SimpleAmm},
+};
+
+pub fn update_fee_tier(ctx: Context<UpdateFeeTier>, protocol_fee: u16, creator_fee_share: u16, enabled: bool) -> Result<()> {
+    let fee_tier = &mut ctx.accounts.fee_tier;
+    ctx.accounts.amm.validate_fee_tier(fee_tier.lp_fee, protocol_fee, creator_fee_share)?;
+
+    // Only pools created from now on are affected, existing ones keep their fees
+    fee_tier.protocol_fee = protocol_fee;
+    fee_tier.creator_fee_share = creator_fee_share;
+    fee_tier.enabled = enabled;
+
+    emit!(FeeTierUpdated {
+        fee_tier: fee_tier.key(),
+        lp_fee: fee_tier.lp_fee,
+        protocol_fee,
+        creator_fee_share,
+        enabled,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeTier<'info> {
+
+    #[account(
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        has_one = admin @ FTRXSwapError::InvalidAdmin,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    #[account(
+        mut,
+        seeds = [
+            FEE_TIER_SEED.as_bytes(),
+            &fee_tier.lp_fee.to_le_bytes(),
+        ],
+        bump = fee_tier.bump,
+        has_one = amm,
+    )]
+    pub fee_tier: Account<'info, FeeTier>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::{self, Metadata, UpdateMetadataAccountsV2},
+    token_interface::Mint,
+};
+use mpl_token_metadata::state::{DataV2, MAX_URI_LENGTH};
+
+use crate::{
+    constants::{LIQUIDITY_SEED, LP_SYMBOL, METADATA_SEED},
+    errors::*,
+    events::LpMetadataUriUpdated,
+    state::SimplePool,
+    token_metadata,
+};
+
+pub fn update_lp_metadata_uri(ctx: Context<UpdateLpMetadataUri>, uri: String) -> Result<()> {
+    if uri.len() > MAX_URI_LENGTH {
+        return err!(FTRXSwapError::InvalidUri);
+    }
+
+    let actual_pool=&ctx.accounts.pool;
+    let lp_fee_seed=actual_pool.lp_fee_seed.to_le_bytes();
+
+    let authority_seeds = &[
+        actual_pool.mint_a.as_ref(),
+        actual_pool.mint_b.as_ref(),
+        actual_pool.admin_seed.as_ref(),
+        lp_fee_seed.as_ref(),
+        &[actual_pool.pool_bump],
+    ];
+    let signer_seeds = &[&authority_seeds[..]];
+
+    // The name and symbol stay the ones given at creation, only the URI changes
+    let name = token_metadata::name_and_symbol(&ctx.accounts.lp_metadata)
+        .map(|(name, _)| name)
+        .unwrap_or_else(|| actual_pool.lp_token_name(None, None));
+    metadata::update_metadata_accounts_v2(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_metadata_program.to_account_info(),
+            UpdateMetadataAccountsV2 {
+                metadata: ctx.accounts.lp_metadata.to_account_info(),
+                update_authority: ctx.accounts.pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        None,
+        Some(DataV2 {
+            name,
+            symbol: LP_SYMBOL.to_string(),
+            uri: uri.clone(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        }),
+        None,
+        None,
+    )?;
+
+    emit!(LpMetadataUriUpdated {
+        pool: ctx.accounts.pool.key(),
+        mint_liquidity: ctx.accounts.mint_liquidity.key(),
+        uri,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateLpMetadataUri<'info> {
+
+    #[account(
+        seeds = [
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+        ],
+        bump,
+        has_one = admin @ FTRXSwapError::InvalidAdmin,
+    )]
+    pub pool: Account<'info, SimplePool>,
+
+    #[account(
+        seeds = [
+            pool.mint_a.key().as_ref(),
+            pool.mint_b.key().as_ref(),
+            pool.admin_seed.as_ref(),
+            &pool.lp_fee_seed.to_le_bytes(),
+            LIQUIDITY_SEED.as_ref(),
+        ],
+        bump,
+    )]
+    pub mint_liquidity: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Metadata of the LP mint, owned by the token metadata program
+    #[account(
+        mut,
+        seeds = [
+            METADATA_SEED.as_bytes(),
+            token_metadata_program.key().as_ref(),
+            mint_liquidity.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub lp_metadata: UncheckedAccount<'info>,
+
+    pub admin: Signer<'info>,
+
+    /// Solana ecosystem accounts
+    pub token_metadata_program: Program<'info, Metadata>,
+}
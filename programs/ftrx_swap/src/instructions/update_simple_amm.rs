@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::AMM_SEED,
+    errors::*,
+    state::SimpleAmm,
+};
+
+pub fn update_amm(
+    ctx: Context<UpdateAmm>,
+    paused: bool,
+    min_lp_fee: u16,
+    max_lp_fee: u16,
+    max_protocol_fee: u16,
+) -> Result<()> {
+    let amm = &mut ctx.accounts.amm;
+    amm.paused = paused;
+    amm.min_lp_fee = min_lp_fee;
+    amm.max_lp_fee = max_lp_fee;
+    amm.max_protocol_fee = max_protocol_fee;
+
+    // Fee bounds only apply to pools created from now on
+    amm.validate_fees()
+}
+
+#[derive(Accounts)]
+pub struct UpdateAmm<'info> {
+    #[account(
+        mut,
+        seeds = [
+            AMM_SEED.as_ref()
+        ],
+        bump = amm.bump,
+        has_one = admin @ FTRXSwapError::InvalidAdmin,
+    )]
+    pub amm: Account<'info, SimpleAmm>,
+
+    pub admin: Signer<'info>,
+}
@@ -1,9 +1,19 @@
 use anchor_lang::prelude::*;
 
-mod constants;
-mod errors;
+pub mod clmm_math;
+pub mod constants;
+pub mod errors;
+pub mod events;
 mod instructions;
-mod state;
+pub mod math;
+mod native_sol;
+pub mod stable_math;
+pub mod state;
+pub mod token_extensions;
+mod token_metadata;
+pub mod weighted_math;
+
+use state::Deadline;
 
 pub use instructions::*;
 
@@ -16,10 +26,69 @@ pub mod ftrx_swap {
 
 
 
+    pub fn create_amm(ctx: Context<CreateAmm>,min_lp_fee:u16,max_lp_fee:u16,max_protocol_fee:u16) -> Result<()> {
+        instructions::create_amm(ctx,min_lp_fee,max_lp_fee,max_protocol_fee)
+    }
+
+    pub fn update_amm(ctx: Context<UpdateAmm>,paused:bool,min_lp_fee:u16,max_lp_fee:u16,max_protocol_fee:u16) -> Result<()> {
+        instructions::update_amm(ctx,paused,min_lp_fee,max_lp_fee,max_protocol_fee)
+    }
+
+    pub fn create_fee_tier(ctx: Context<CreateFeeTier>,lp_fee:u16,protocol_fee:u16,creator_fee_share:u16) -> Result<()> {
+        instructions::create_fee_tier(ctx,lp_fee,protocol_fee,creator_fee_share)
+    }
+
+    pub fn update_fee_tier(ctx: Context<UpdateFeeTier>,protocol_fee:u16,creator_fee_share:u16,enabled:bool) -> Result<()> {
+        instructions::update_fee_tier(ctx,protocol_fee,creator_fee_share,enabled)
+    }
+
     pub fn create_pool(ctx: Context<CreatePool>,lp_fee:u16,bump_pool:u8,bump_vault_a:u8,bump_vault_b:u8,bump_treas_a:u8,bump_treas_b:u8) -> Result<()> {
         instructions::create_pool(ctx,lp_fee,bump_pool,bump_vault_a,bump_vault_b,bump_treas_a,bump_treas_b)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stable_pool<'info>(ctx: Context<'_, '_, '_, 'info, CreatePool<'info>>,lp_fee:u16,amp:u64,bump_pool:u8,bump_vault_a:u8,bump_vault_b:u8,bump_treas_a:u8,bump_treas_b:u8) -> Result<()> {
+        instructions::create_stable_pool(ctx,lp_fee,amp,bump_pool,bump_vault_a,bump_vault_b,bump_treas_a,bump_treas_b)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_weighted_pool<'info>(ctx: Context<'_, '_, '_, 'info, CreatePool<'info>>,lp_fee:u16,weight_a:u64,bump_pool:u8,bump_vault_a:u8,bump_vault_b:u8,bump_treas_a:u8,bump_treas_b:u8) -> Result<()> {
+        instructions::create_weighted_pool(ctx,lp_fee,weight_a,bump_pool,bump_vault_a,bump_vault_b,bump_treas_a,bump_treas_b)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_concentrated_pool<'info>(ctx: Context<'_, '_, '_, 'info, CreatePool<'info>>,lp_fee:u16,tick_spacing:u16,sqrt_price_x64:u128,bump_pool:u8,bump_vault_a:u8,bump_vault_b:u8,bump_treas_a:u8,bump_treas_b:u8) -> Result<()> {
+        instructions::create_concentrated_pool(ctx,lp_fee,tick_spacing,sqrt_price_x64,bump_pool,bump_vault_a,bump_vault_b,bump_treas_a,bump_treas_b)
+    }
+
+    pub fn initialize_tick_array(ctx: Context<InitializeTickArray>,start_tick_index: i32) -> Result<()> {
+        instructions::initialize_tick_array(ctx,start_tick_index)
+    }
+
+    pub fn open_position(ctx: Context<OpenPosition>,tick_lower: i32,tick_upper: i32) -> Result<()> {
+        instructions::open_position(ctx,tick_lower,tick_upper)
+    }
+
+    pub fn increase_liquidity(ctx: Context<ModifyPosition>,liquidity: u128,max_amount_a: u64,max_amount_b: u64) -> Result<()> {
+        instructions::increase_liquidity(ctx,liquidity,max_amount_a,max_amount_b)
+    }
+
+    pub fn decrease_liquidity(ctx: Context<ModifyPosition>,liquidity: u128,min_amount_a: u64,min_amount_b: u64) -> Result<()> {
+        instructions::decrease_liquidity(ctx,liquidity,min_amount_a,min_amount_b)
+    }
+
+    pub fn collect_position_fees(ctx: Context<ModifyPosition>) -> Result<()> {
+        instructions::collect_position_fees(ctx)
+    }
+
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        instructions::close_position(ctx)
+    }
+
+    pub fn concentrated_swap<'info>(ctx: Context<'_, '_, '_, 'info, ConcentratedSwap<'info>>,a_to_b: bool,input_amount: u64,min_output_amount: u64)-> Result<()> {
+        instructions::concentrated_swap(ctx,a_to_b,input_amount,min_output_amount)
+    }
+
     pub fn deposit_liquidity(
         ctx: Context<DepositLiquidity>,
         amount_a: u64,
@@ -29,18 +98,118 @@ pub mod ftrx_swap {
         instructions::deposit_liquidity(ctx, amount_a, amount_b,expected_lp_token)
     }
 
+    pub fn deposit_liquidity_with_deadline(
+        ctx: Context<DepositLiquidity>,
+        amount_a: u64,
+        amount_b: u64,
+        expected_lp_token:u64,
+        deadline: Deadline
+    ) -> Result<()> {
+        instructions::deposit_liquidity_with_deadline(ctx, amount_a, amount_b,expected_lp_token,deadline)
+    }
+
+    pub fn withdraw_liquidity_native<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawLiquidity<'info>>, amount: u64, amount_expected_a: u64, amount_expected_b: u64, unwrap: bool) -> Result<()> {
+        instructions::withdraw_liquidity_native(ctx, amount,amount_expected_a,amount_expected_b,unwrap)
+    }
+
+    pub fn deposit_single_sided(ctx: Context<DepositSingleSided>, swap_a: bool, amount_in: u64, min_lp_out: u64) -> Result<()> {
+        instructions::deposit_single_sided(ctx, swap_a, amount_in, min_lp_out)
+    }
+
+    pub fn deposit_liquidity_native<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositLiquidity<'info>>,
+        amount_a: u64,
+        amount_b: u64,
+        expected_lp_token:u64,
+        unwrap: bool
+    ) -> Result<()> {
+        instructions::deposit_liquidity_native(ctx, amount_a, amount_b,expected_lp_token,unwrap)
+    }
+
     pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, amount: u64, amount_expected_a: u64, amount_expected_b: u64) -> Result<()> {
         instructions::withdraw_liquidity(ctx, amount,amount_expected_a,amount_expected_b)
     }
 
+    pub fn withdraw_liquidity_with_deadline(ctx: Context<WithdrawLiquidity>, amount: u64, amount_expected_a: u64, amount_expected_b: u64, deadline: Deadline) -> Result<()> {
+        instructions::withdraw_liquidity_with_deadline(ctx, amount,amount_expected_a,amount_expected_b,deadline)
+    }
+
+    pub fn withdraw_single_sided(ctx: Context<WithdrawSingleSided>, lp_amount: u64, want_a: bool, min_out: u64) -> Result<()> {
+        instructions::withdraw_single_sided(ctx, lp_amount, want_a, min_out)
+    }
+
     pub fn simple_swap_exact_in(ctx: Context<SimpleSwapExactIn>,swap_a: bool,input_amount: u64,min_output_amount: u64)-> Result<()> {
         instructions::simple_swap_exact_in(ctx,swap_a,input_amount,min_output_amount)
     }
 
+    pub fn simple_swap_exact_in_with_deadline(ctx: Context<SimpleSwapExactIn>,swap_a: bool,input_amount: u64,min_output_amount: u64,deadline: Deadline)-> Result<()> {
+        instructions::simple_swap_exact_in_with_deadline(ctx,swap_a,input_amount,min_output_amount,deadline)
+    }
+
+    pub fn simple_swap_exact_in_native<'info>(ctx: Context<'_, '_, '_, 'info, SimpleSwapExactIn<'info>>,swap_a: bool,input_amount: u64,min_output_amount: u64,unwrap: bool)-> Result<()> {
+        instructions::simple_swap_exact_in_native(ctx,swap_a,input_amount,min_output_amount,unwrap)
+    }
+
+    pub fn simple_swap_exact_out(ctx: Context<SimpleSwapExactOut>,swap_a: bool,output_amount: u64,max_input_amount: u64)-> Result<()> {
+        instructions::simple_swap_exact_out(ctx,swap_a,output_amount,max_input_amount)
+    }
+
+    pub fn simple_swap_routed<'info>(ctx: Context<'_, '_, '_, 'info, SimpleSwapRouted<'info>>,input_amount: u64,min_output_amount: u64)-> Result<()> {
+        instructions::simple_swap_routed(ctx,input_amount,min_output_amount)
+    }
+
+    pub fn simple_flash_swap<'info>(ctx: Context<'_, '_, '_, 'info, SimpleFlashSwap<'info>>,swap_a: bool,output_amount: u64,data: Vec<u8>)-> Result<()> {
+        instructions::simple_flash_swap(ctx,swap_a,output_amount,data)
+    }
+
     pub fn admin_gets_treasury(ctx: Context<AdminGetsTreasury>,amount_a: u64,amount_b: u64)-> Result<()> {
         instructions::admin_gets_treasury(ctx,amount_a,amount_b)
     }
 
+    pub fn creator_collect_fees(ctx: Context<CreatorCollectFees>,amount_a: u64,amount_b: u64)-> Result<()> {
+        instructions::creator_collect_fees(ctx,amount_a,amount_b)
+    }
+
+    pub fn flash_borrow(ctx: Context<FlashBorrow>,borrow_a: bool,amount: u64)-> Result<()> {
+        instructions::flash_borrow(ctx,borrow_a,amount)
+    }
+
+    pub fn flash_repay(ctx: Context<FlashRepay>)-> Result<()> {
+        instructions::flash_repay(ctx)
+    }
+
+    pub fn set_pool_fees(ctx: Context<SetPoolFees>,lp_fee: u16,protocol_fee: u16)-> Result<()> {
+        instructions::set_pool_fees(ctx,lp_fee,protocol_fee)
+    }
+
+    pub fn set_pool_dynamic_fee(ctx: Context<SetPoolDynamicFee>,dynamic_fee: bool,max_dynamic_lp_fee: u16)-> Result<()> {
+        instructions::set_pool_dynamic_fee(ctx,dynamic_fee,max_dynamic_lp_fee)
+    }
+
+    pub fn update_lp_metadata_uri(ctx: Context<UpdateLpMetadataUri>,uri: String)-> Result<()> {
+        instructions::update_lp_metadata_uri(ctx,uri)
+    }
+
+    pub fn set_pool_paused_flags(ctx: Context<SetPoolPausedFlags>,paused_flags: u8)-> Result<()> {
+        instructions::set_pool_paused_flags(ctx,paused_flags)
+    }
+
+    pub fn propose_admin(ctx: Context<ProposeAdmin>,new_admin: Pubkey)-> Result<()> {
+        instructions::propose_admin(ctx,new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>)-> Result<()> {
+        instructions::accept_admin(ctx)
+    }
+
+    pub fn cancel_admin_proposal(ctx: Context<CancelAdminProposal>)-> Result<()> {
+        instructions::cancel_admin_proposal(ctx)
+    }
+
+    pub fn migrate_pool(ctx: Context<MigratePool>)-> Result<()> {
+        instructions::migrate_pool(ctx)
+    }
+
 
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
         Ok(())
@@ -0,0 +1,271 @@
+use anchor_lang::prelude::*;
+use fixed::types::I64F64;
+use fixed_sqrt::FixedSqrt;
+
+use crate::{constants::FEE_MULTIPLIER, errors::FTRXSwapError};
+
+/// Amounts moved by a single swap against a pool
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapAmounts {
+    /// Amount taken from the trader, fees included
+    pub input: u64,
+    /// Amount the pool releases for that input
+    pub output: u64,
+    /// Part of the input left in the vault for the LPs
+    pub lp_fee: u64,
+    /// Part of the input sent to the treasury
+    pub protocol_fee: u64,
+}
+
+/// Constant product swap for a given input, the LP fee is added to the
+/// input reserve before computing the new invariant
+pub fn swap_exact_in(
+    reserve_in: u64,
+    reserve_out: u64,
+    input: u64,
+    lp_fee: u16,
+    protocol_fee: u16,
+) -> Result<SwapAmounts> {
+    let to_treasury_fee = input
+        .checked_mul(protocol_fee as u64)
+        .ok_or(FTRXSwapError::MathOverflow)?
+        / FEE_MULTIPLIER;
+    let lp_fee_amount = input
+        .checked_mul(lp_fee as u64)
+        .ok_or(FTRXSwapError::MathOverflow)?
+        / FEE_MULTIPLIER;
+    let taxed_input = input
+        .checked_sub(to_treasury_fee)
+        .and_then(|amount| amount.checked_sub(lp_fee_amount))
+        .ok_or(FTRXSwapError::InvalidFee)?;
+
+    let new_reserve_in_with_lp_fees = reserve_in
+        .checked_add(lp_fee_amount)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    let new_k_after_fees = new_reserve_in_with_lp_fees
+        .checked_mul(reserve_out)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    let new_reserve_in = new_reserve_in_with_lp_fees
+        .checked_add(taxed_input)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    // Rounding the new output reserve up keeps the rounding error in the pool
+    let theoretical_new_reserve_out = I64F64::checked_from_num(new_k_after_fees)
+        .and_then(|k| k.checked_div(I64F64::checked_from_num(new_reserve_in)?))
+        .ok_or(FTRXSwapError::MathOverflow)?
+        .ceil()
+        .to_num::<u64>();
+
+    let output = reserve_out
+        .checked_sub(theoretical_new_reserve_out)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    Ok(SwapAmounts {
+        input,
+        output,
+        lp_fee: lp_fee_amount,
+        protocol_fee: to_treasury_fee,
+    })
+}
+
+/// An input for which `swap_exact_in` releases at least `output`
+///
+/// It is not searched for the smallest one: when the estimate for `output`
+/// falls short because of rounding, the estimate for `output + 1` is used, so
+/// the input can exceed the smallest sufficient one by the price of one unit
+/// of output, about `reserve_in / (reserve_out - output)` rounded up
+pub fn swap_exact_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    output: u64,
+    lp_fee: u16,
+    protocol_fee: u16,
+) -> Result<SwapAmounts> {
+    if output == 0 || output >= reserve_out {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+
+    // Closed form of the exact in formula without the fee floors and the
+    // ceil on the new output reserve, first for the requested output and
+    // then for one more unit which always absorbs the rounding
+    let estimate = exact_out_estimate(reserve_in, reserve_out, output, lp_fee, protocol_fee)?;
+    let amounts = swap_exact_in(reserve_in, reserve_out, estimate, lp_fee, protocol_fee)?;
+    if amounts.output >= output {
+        return Ok(amounts);
+    }
+
+    let estimate = exact_out_estimate(reserve_in, reserve_out, output + 1, lp_fee, protocol_fee)?;
+    let amounts = swap_exact_in(reserve_in, reserve_out, estimate, lp_fee, protocol_fee)?;
+    if amounts.output < output {
+        return err!(FTRXSwapError::MathOverflow);
+    }
+
+    Ok(amounts)
+}
+
+// Solves output * (reserve_in + lp_fee) <= taxed_input * (reserve_out - output)
+// for the gross input, rounded up
+fn exact_out_estimate(
+    reserve_in: u64,
+    reserve_out: u64,
+    output: u64,
+    lp_fee: u16,
+    protocol_fee: u16,
+) -> Result<u64> {
+    if output >= reserve_out {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+
+    let fee_multiplier = FEE_MULTIPLIER as u128;
+    let taxed_share = fee_multiplier
+        .checked_sub(lp_fee as u128 + protocol_fee as u128)
+        .ok_or(FTRXSwapError::InvalidFee)?;
+
+    let numerator = (output as u128)
+        .checked_mul(reserve_in as u128)
+        .and_then(|value| value.checked_mul(fee_multiplier))
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    let denominator = taxed_share
+        .checked_mul((reserve_out - output) as u128)
+        .and_then(|value| value.checked_sub((output as u128) * (lp_fee as u128)))
+        .filter(|value| *value > 0)
+        .ok_or(FTRXSwapError::InsufficientLiquidity)?;
+
+    let input = numerator
+        .checked_add(denominator - 1)
+        .ok_or(FTRXSwapError::MathOverflow)?
+        / denominator;
+
+    u64::try_from(input).map_err(|_| error!(FTRXSwapError::MathOverflow))
+}
+
+/// LP and protocol fees owed on a flash loan, rounded up so that no loan is
+/// free
+pub fn flash_loan_fees(amount: u64, lp_fee: u16, protocol_fee: u16) -> Result<(u64, u64)> {
+    let fee = |rate: u16| -> Result<u64> {
+        let fee = ((amount as u128) * (rate as u128))
+            .checked_add(FEE_MULTIPLIER as u128 - 1)
+            .ok_or(FTRXSwapError::MathOverflow)?
+            / FEE_MULTIPLIER as u128;
+        u64::try_from(fee).map_err(|_| error!(FTRXSwapError::MathOverflow))
+    };
+    Ok((fee(lp_fee)?, fee(protocol_fee)?))
+}
+
+/// Part of a single sided deposit to swap so that the remainder matches the
+/// pool ratio after the swap
+///
+/// With g the taxed share of the input, r the share staying in the pool and
+/// l the LP fee share, swapping s out of x against a reserve a balances the
+/// deposit when (g*r + l)*s^2 + (a*(1 + g) - x*l)*s - x*a = 0, solved here
+/// relative to the reserve to stay in I64F64 range
+pub fn single_sided_swap_amount(
+    reserve_in: u64,
+    amount_in: u64,
+    lp_fee: u16,
+    protocol_fee: u16,
+) -> Result<u64> {
+    if reserve_in == 0 {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+
+    let fee_multiplier = I64F64::from_num(FEE_MULTIPLIER);
+    let taxed_share = I64F64::from_num(FEE_MULTIPLIER - lp_fee as u64 - protocol_fee as u64) / fee_multiplier;
+    let kept_share = I64F64::from_num(FEE_MULTIPLIER - protocol_fee as u64) / fee_multiplier;
+    let lp_share = I64F64::from_num(lp_fee) / fee_multiplier;
+
+    let reserve = I64F64::from_num(reserve_in);
+    let relative_amount = I64F64::from_num(amount_in)
+        .checked_div(reserve)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    let quadratic = taxed_share * kept_share + lp_share;
+    let linear = I64F64::ONE + taxed_share - relative_amount * lp_share;
+
+    let discriminant = linear
+        .checked_mul(linear)
+        .and_then(|value| value.checked_add(quadratic.checked_mul(relative_amount)?.checked_mul(I64F64::from_num(4))?))
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    let relative_swap = (discriminant.sqrt() - linear)
+        .checked_div(quadratic * I64F64::from_num(2))
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    let swap_amount = relative_swap
+        .checked_mul(reserve)
+        .ok_or(FTRXSwapError::MathOverflow)?
+        .floor()
+        .to_num::<u64>();
+
+    Ok(swap_amount.min(amount_in))
+}
+
+/// Amounts of a deposit matched to the price of the reserves, the side of
+/// the larger reserve follows the other one and is rounded up for the pool
+pub fn balanced_deposit_amounts(reserve_a: u64, reserve_b: u64, amount_a: u64, amount_b: u64) -> Result<(u64, u64)> {
+    // For precision, the ratio is evaluated as a/b if a>b, and b/a otherwise
+    let (larger, smaller) = if reserve_a > reserve_b { (reserve_a, reserve_b) } else { (reserve_b, reserve_a) };
+    let ratio = I64F64::from_num(larger)
+        .checked_div(I64F64::from_num(smaller))
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    let follow = |amount: u64| -> Result<u64> {
+        Ok(I64F64::from_num(amount)
+            .checked_mul(ratio)
+            .ok_or(FTRXSwapError::MathOverflow)?
+            .ceil()
+            .to_num::<u64>())
+    };
+
+    if reserve_a > reserve_b {
+        Ok((follow(amount_b)?, amount_b))
+    } else {
+        Ok((amount_a, follow(amount_a)?))
+    }
+}
+
+/// LP tokens minted for amounts received by a constant product pool, the
+/// square root of their product on the first deposit, then the smaller
+/// share of the reserves, both sides being used as transfer fees can break
+/// the deposited ratio
+///
+/// The first deposit is returned before MINIMUM_LIQUIDITY is locked
+pub fn deposit_liquidity(
+    reserve_a: u64,
+    reserve_b: u64,
+    amount_a: u64,
+    amount_b: u64,
+    lp_supply: u64,
+) -> Result<u64> {
+    if reserve_a == 0 && reserve_b == 0 {
+        return Ok(I64F64::from_num(amount_a)
+            .checked_mul(I64F64::from_num(amount_b))
+            .ok_or(FTRXSwapError::MathOverflow)?
+            .sqrt()
+            .floor()
+            .to_num::<u64>());
+    }
+
+    let share = |amount: u64, reserve: u64| -> Result<u64> {
+        Ok(I64F64::from_num(lp_supply)
+            .checked_mul(I64F64::from_num(amount))
+            .and_then(|value| value.checked_div(I64F64::from_num(reserve)))
+            .ok_or(FTRXSwapError::MathOverflow)?
+            .floor()
+            .to_num::<u64>())
+    };
+    Ok(share(amount_a, reserve_a)?.min(share(amount_b, reserve_b)?))
+}
+
+/// Part of a reserve paid out for burning lp_amount out of lp_supply,
+/// rounded down for the pool
+pub fn withdraw_amount(lp_amount: u64, reserve: u64, lp_supply: u64) -> Result<u64> {
+    Ok(I64F64::from_num(lp_amount)
+        .checked_mul(I64F64::from_num(reserve))
+        .and_then(|value| value.checked_div(I64F64::from_num(lp_supply)))
+        .ok_or(FTRXSwapError::MathOverflow)?
+        .floor()
+        .to_num::<u64>())
+}
@@ -0,0 +1,57 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    token::spl_token::native_mint,
+    token_interface::{self, CloseAccount, SyncNative},
+};
+
+pub fn is_native(mint: &Pubkey) -> bool {
+    *mint == native_mint::ID
+}
+
+/// Moves lamports into a wSOL token account and credits them as tokens
+pub fn wrap<'info>(
+    from: AccountInfo<'info>,
+    wsol_account: AccountInfo<'info>,
+    amount: u64,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    system_program::transfer(
+        CpiContext::new(
+            system_program,
+            system_program::Transfer {
+                from,
+                to: wsol_account.clone(),
+            },
+        ),
+        amount,
+    )?;
+
+    token_interface::sync_native(CpiContext::new(
+        token_program,
+        SyncNative {
+            account: wsol_account,
+        },
+    ))
+}
+
+/// Closes a wSOL token account, its tokens and rent going back to the owner
+/// as lamports
+pub fn unwrap<'info>(
+    wsol_account: AccountInfo<'info>,
+    owner: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    token_interface::close_account(CpiContext::new(
+        token_program,
+        CloseAccount {
+            account: wsol_account,
+            destination: owner.clone(),
+            authority: owner,
+        },
+    ))
+}
@@ -0,0 +1,283 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{FEE_MULTIPLIER, STABLE_MAX_ITERATIONS},
+    errors::FTRXSwapError,
+    math::SwapAmounts,
+};
+
+// Kept apart from the anchor prelude, whose Result the macro would pick up
+#[allow(clippy::all)]
+mod wide {
+    uint::construct_uint! {
+        /// Wide enough for the D^3 terms of two u64 reserves
+        pub struct U256(4);
+    }
+}
+pub use wide::U256;
+
+// A * n^n for the n = 2 coins of a pool
+fn amp_times_coins(amp: u64) -> U256 {
+    U256::from(amp) * U256::from(4u8)
+}
+
+fn to_u128(value: U256) -> Result<u128> {
+    if value.bits() > 128 {
+        return err!(FTRXSwapError::MathOverflow);
+    }
+    Ok(value.low_u128())
+}
+
+// Newton steps stop once they move by one unit at most
+fn converged(value: U256, previous: U256) -> bool {
+    if value > previous {
+        value - previous <= U256::one()
+    } else {
+        previous - value <= U256::one()
+    }
+}
+
+/// StableSwap invariant D of two reserves, the root of
+/// A*n^n*(x + y) + D = A*n^n*D + D^(n+1) / (n^n*x*y)
+pub fn compute_d(amp: u64, reserve_a: u64, reserve_b: u64) -> Result<u128> {
+    let sum = U256::from(reserve_a) + U256::from(reserve_b);
+    if sum.is_zero() {
+        return Ok(0);
+    }
+    if reserve_a == 0 || reserve_b == 0 {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+
+    let ann = amp_times_coins(amp);
+    let product = U256::from(reserve_a) * U256::from(reserve_b) * U256::from(4u8);
+
+    let mut d = sum;
+    for _ in 0..STABLE_MAX_ITERATIONS {
+        // D^(n+1) / (n^n*x*y) in a single division to keep precision on
+        // unbalanced reserves
+        let d_p = d
+            .checked_mul(d)
+            .and_then(|value| value.checked_mul(d))
+            .map(|value| value / product)
+            .ok_or(FTRXSwapError::MathOverflow)?;
+
+        let previous = d;
+        let numerator = ann
+            .checked_mul(sum)
+            .and_then(|value| value.checked_add(d_p.checked_mul(U256::from(2u8))?))
+            .and_then(|value| value.checked_mul(d))
+            .ok_or(FTRXSwapError::MathOverflow)?;
+        let denominator = (ann - U256::one())
+            .checked_mul(d)
+            .and_then(|value| value.checked_add(d_p.checked_mul(U256::from(3u8))?))
+            .ok_or(FTRXSwapError::MathOverflow)?;
+        d = numerator / denominator;
+
+        if converged(d, previous) {
+            return to_u128(d);
+        }
+    }
+
+    err!(FTRXSwapError::CurveDidNotConverge)
+}
+
+/// Reserve y on the other side once one side holds x, for a given D, the
+/// root of y^2 + (x + D/(A*n^n) - D)*y = D^(n+1) / (n^(2n)*x*A)
+pub fn compute_y(amp: u64, reserve_x: u128, d: u128) -> Result<u128> {
+    if reserve_x == 0 {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+
+    let ann = amp_times_coins(amp);
+    let (d, reserve_x) = (U256::from(d), U256::from(reserve_x));
+
+    let c = d
+        .checked_mul(d)
+        .and_then(|value| value.checked_mul(d))
+        .map(|value| value / (reserve_x * ann * U256::from(4u8)))
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    let b = reserve_x + d / ann;
+
+    let mut y = d;
+    for _ in 0..STABLE_MAX_ITERATIONS {
+        let previous = y;
+        let numerator = y
+            .checked_mul(y)
+            .and_then(|value| value.checked_add(c))
+            .ok_or(FTRXSwapError::MathOverflow)?;
+        let denominator = (y * U256::from(2u8) + b)
+            .checked_sub(d)
+            .filter(|value| !value.is_zero())
+            .ok_or(FTRXSwapError::MathOverflow)?;
+        y = numerator / denominator;
+
+        if converged(y, previous) {
+            return to_u128(y);
+        }
+    }
+
+    err!(FTRXSwapError::CurveDidNotConverge)
+}
+
+/// StableSwap counterpart of `math::swap_exact_in`, with the same fee split:
+/// the LP fee joins the input reserve before D is computed
+pub fn swap_exact_in(
+    amp: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    input: u64,
+    lp_fee: u16,
+    protocol_fee: u16,
+) -> Result<SwapAmounts> {
+    let to_treasury_fee = input
+        .checked_mul(protocol_fee as u64)
+        .ok_or(FTRXSwapError::MathOverflow)?
+        / FEE_MULTIPLIER;
+    let lp_fee_amount = input
+        .checked_mul(lp_fee as u64)
+        .ok_or(FTRXSwapError::MathOverflow)?
+        / FEE_MULTIPLIER;
+    let taxed_input = input
+        .checked_sub(to_treasury_fee)
+        .and_then(|amount| amount.checked_sub(lp_fee_amount))
+        .ok_or(FTRXSwapError::InvalidFee)?;
+
+    let new_reserve_in_with_lp_fees = reserve_in
+        .checked_add(lp_fee_amount)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    let d = compute_d(amp, new_reserve_in_with_lp_fees, reserve_out)?;
+
+    let new_reserve_in = new_reserve_in_with_lp_fees
+        .checked_add(taxed_input)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    // One more unit stays in the pool to cover the precision of the solver
+    let theoretical_new_reserve_out = compute_y(amp, new_reserve_in as u128, d)?
+        .checked_add(1)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+    let output = (reserve_out as u128).saturating_sub(theoretical_new_reserve_out) as u64;
+
+    Ok(SwapAmounts {
+        input,
+        output,
+        lp_fee: lp_fee_amount,
+        protocol_fee: to_treasury_fee,
+    })
+}
+
+/// LP tokens minted for a possibly imbalanced deposit, `supply` including the
+/// locked MINIMUM_LIQUIDITY
+///
+/// As in Curve, each side pays half the LP fee on its distance to the
+/// balanced deposit, so that depositing and withdrawing is no cheaper than
+/// swapping
+pub fn deposit_liquidity(
+    amp: u64,
+    reserve_a: u64,
+    reserve_b: u64,
+    amount_a: u64,
+    amount_b: u64,
+    supply: u64,
+    lp_fee: u16,
+) -> Result<u64> {
+    let new_reserve_a = reserve_a.checked_add(amount_a).ok_or(FTRXSwapError::MathOverflow)?;
+    let new_reserve_b = reserve_b.checked_add(amount_b).ok_or(FTRXSwapError::MathOverflow)?;
+
+    let d_before = compute_d(amp, reserve_a, reserve_b)?;
+    let d_after = compute_d(amp, new_reserve_a, new_reserve_b)?;
+
+    // The first deposit mints D itself
+    if d_before == 0 {
+        return u64::try_from(d_after).map_err(|_| error!(FTRXSwapError::MathOverflow));
+    }
+    if d_after <= d_before {
+        return Ok(0);
+    }
+
+    let imbalance_fee = |reserve: u64, new_reserve: u64| -> Result<u64> {
+        let ideal_reserve = (U256::from(d_after) * U256::from(reserve) / U256::from(d_before)).low_u128();
+        let difference = (new_reserve as u128).abs_diff(ideal_reserve);
+        let fee = (difference * lp_fee as u128)
+            .checked_add(2 * FEE_MULTIPLIER as u128 - 1)
+            .ok_or(FTRXSwapError::MathOverflow)?
+            / (2 * FEE_MULTIPLIER as u128);
+        new_reserve
+            .checked_sub(u64::try_from(fee).map_err(|_| error!(FTRXSwapError::MathOverflow))?)
+            .ok_or(error!(FTRXSwapError::DepositTooSmall))
+    };
+    let d_after_fees = compute_d(
+        amp,
+        imbalance_fee(reserve_a, new_reserve_a)?,
+        imbalance_fee(reserve_b, new_reserve_b)?,
+    )?;
+    if d_after_fees <= d_before {
+        return Ok(0);
+    }
+
+    let liquidity = U256::from(supply) * U256::from(d_after_fees - d_before) / U256::from(d_before);
+    u64::try_from(to_u128(liquidity)?).map_err(|_| error!(FTRXSwapError::MathOverflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{MAX_AMP, MIN_AMP};
+
+    #[test]
+    fn balanced_reserves_have_their_sum_as_invariant() {
+        for amp in [MIN_AMP, 100, MAX_AMP] {
+            let d = compute_d(amp, 1_000_000, 1_000_000).unwrap();
+            assert!(d.abs_diff(2_000_000) <= 1);
+        }
+    }
+
+    #[test]
+    fn newton_converges_on_extreme_reserves() {
+        let reserves = [
+            (1_000_000, u64::MAX / 2),
+            (1_000, 1_000_000_000_000_000),
+            (u64::MAX / 2, u64::MAX / 2),
+            (123_456_789, 987_654_321_012),
+        ];
+        for amp in [MIN_AMP, 10, 100, 1_000, MAX_AMP] {
+            for (reserve_a, reserve_b) in reserves {
+                let d = compute_d(amp, reserve_a, reserve_b).unwrap();
+                // D lies between the constant product and constant sum invariants
+                assert!(d <= reserve_a as u128 + reserve_b as u128 + 1);
+                assert!(d as f64 >= 2.0 * (reserve_a as f64 * reserve_b as f64).sqrt() * (1.0 - 1e-12));
+
+                // Solving back for either reserve lands within a unit of the root of D,
+                // which the integer D only places that close to the original reserves
+                let y = compute_y(amp, reserve_a as u128, d).unwrap() as u64;
+                assert!(compute_d(amp, reserve_a, y - 1).unwrap() <= d + 1);
+                assert!(compute_d(amp, reserve_a, y + 1).unwrap() + 1 >= d);
+                assert!(y.abs_diff(reserve_b) <= 1 + reserve_b / 1_000_000_000);
+                let x = compute_y(amp, reserve_b as u128, d).unwrap() as u64;
+                assert!(compute_d(amp, x - 1, reserve_b).unwrap() <= d + 1);
+                assert!(compute_d(amp, x + 1, reserve_b).unwrap() + 1 >= d);
+                assert!(x.abs_diff(reserve_a) <= 1 + reserve_a / 1_000_000_000);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_side_is_rejected() {
+        assert_eq!(compute_d(100, 0, 0).unwrap(), 0);
+        assert!(compute_d(100, 0, 1_000).is_err());
+        assert!(compute_y(100, 0, 1_000).is_err());
+    }
+
+    #[test]
+    fn swap_keeps_the_invariant() {
+        for amp in [MIN_AMP, 100, MAX_AMP] {
+            let (reserve_in, reserve_out, input) = (1_000_000_000, 1_000_000_000, 1_000_000);
+            let amounts = swap_exact_in(amp, reserve_in, reserve_out, input, 30, 0).unwrap();
+            assert!(amounts.output < input);
+
+            let d_before = compute_d(amp, reserve_in, reserve_out).unwrap();
+            let d_after = compute_d(amp, reserve_in + input, reserve_out - amounts.output).unwrap();
+            assert!(d_after >= d_before);
+        }
+    }
+}
@@ -1,7 +1,91 @@
 use anchor_lang::prelude::*;
 
+use crate::{
+    clmm_math,
+    constants::{CURVE_CONCENTRATED, CURVE_STABLE, CURVE_WEIGHTED, FEE_MULTIPLIER, LP_SYMBOL, MAX_TICK, MIN_TICK, OBSERVATION_COUNT, TICK_ARRAY_SIZE, VOLATILITY_CEILING, VOLATILITY_DECAY_PERIOD},
+    errors::FTRXSwapError,
+    math::{self, SwapAmounts},
+    stable_math,
+    weighted_math,
+};
 
 
+#[account]
+#[derive(Default)]
+pub struct SimpleAmm {
+    /// Protocol admin, allowed to update this config
+    pub admin: Pubkey,
+
+    /// Stops every pool of the program when set, balanced withdrawals
+    /// excepted
+    pub paused: bool,
+
+    /// Bounds on the fees pools can be created with, out of FEE_MULTIPLIER
+    pub min_lp_fee: u16,
+    pub max_lp_fee: u16,
+    pub max_protocol_fee: u16,
+
+    pub bump: u8,
+}
+
+impl SimpleAmm {
+    pub const LEN: usize = 8 + 32 + 1 + 2*3 + 1;
+
+    /// Fee bounds must be ordered and leave something to swap
+    pub fn validate_fees(&self) -> Result<()> {
+        if self.min_lp_fee > self.max_lp_fee
+            || self.max_lp_fee as u64 + self.max_protocol_fee as u64 >= FEE_MULTIPLIER
+        {
+            return err!(FTRXSwapError::InvalidFee);
+        }
+        Ok(())
+    }
+
+    /// Fees of a tier must lie within the bounds of the config
+    pub fn validate_fee_tier(&self, lp_fee: u16, protocol_fee: u16, creator_fee_share: u16) -> Result<()> {
+        if lp_fee < self.min_lp_fee
+            || lp_fee > self.max_lp_fee
+            || protocol_fee > self.max_protocol_fee
+            || lp_fee as u64 + protocol_fee as u64 >= FEE_MULTIPLIER
+            || creator_fee_share as u64 > FEE_MULTIPLIER
+        {
+            return err!(FTRXSwapError::InvalidFee);
+        }
+        Ok(())
+    }
+}
+
+
+/// LP fee pools can be created with, making a pool per pair, admin and tier
+/// the canonical one, enumerable as the program accounts of this type
+#[account]
+#[derive(Default)]
+pub struct FeeTier {
+    pub amm: Pubkey,
+
+    /// LP fee of the tier and of its pools, out of FEE_MULTIPLIER
+    pub lp_fee: u16,
+
+    /// Protocol fee given to the new pools of the tier, out of FEE_MULTIPLIER
+    pub protocol_fee: u16,
+
+    /// Cleared by the AMM admin to stop new pools in the tier
+    pub enabled: bool,
+
+    pub bump: u8,
+
+    /// Share of the protocol fee given to the creators of the new pools of
+    /// the tier, out of FEE_MULTIPLIER
+    pub creator_fee_share: u16,
+}
+
+impl FeeTier {
+    pub const LEN: usize = 8 + 32 + 2*2 + 1 + 1 + 2;
+}
+
+
+/// The fields of LegacySimplePool come first in their original order, later
+/// ones are appended so that migrate_pool can extend the legacy accounts
 #[account]
 #[derive(Default)]
 pub struct SimplePool {
@@ -29,8 +113,510 @@ pub struct SimplePool {
     pub mint_b: Pubkey,
     pub vault_mint_b: Pubkey,
     pub treasury_mint_b: Pubkey,
+
+    /// Global config the pool was created under
+    pub amm: Pubkey,
+
+    /// Admin the pool and treasury addresses were derived with, kept across handovers
+    pub admin_seed: Pubkey,
+
+    /// Admin nominated by the current one, default when there is none
+    pub pending_admin: Pubkey,
+
+    /// LP fee the pool address was derived with, kept when the fees change
+    pub lp_fee_seed: u16,
+
+    /// PAUSE_SWAP, PAUSE_DEPOSIT and PAUSE_WITHDRAW bits set by the admin
+    pub paused_flags: u8,
+
+    /// Time weighted sums of the B per A and A per B prices, Q64.64 wrapping
+    pub price_a_cumulative: u128,
+    pub price_b_cumulative: u128,
+
+    /// Unix timestamp the cumulative prices were last updated at
+    pub last_update_timestamp: i64,
+
+    /// Set while a flash loan is outstanding, blocks every other operation
+    /// moving the reserves
+    pub locked: bool,
+
+    /// Side, amount and vault balance before the outstanding flash loan
+    pub flash_loan_a: bool,
+    pub flash_loan_amount: u64,
+    pub flash_loan_reserve_before: u64,
+
+    /// CURVE_CONSTANT_PRODUCT, CURVE_STABLE, CURVE_WEIGHTED or CURVE_CONCENTRATED
+    pub curve: u8,
+
+    /// Amplification coefficient of stable pools
+    pub amp: u64,
+
+    /// Normalized weights of weighted pools, out of WEIGHT_MULTIPLIER
+    pub weight_a: u64,
+    pub weight_b: u64,
+
+    /// Concentrated pools only: spacing between usable ticks
+    pub tick_spacing: u16,
+
+    /// Q64.64 square root of the B per A price and the tick it lies in
+    pub sqrt_price_x64: u128,
+    pub tick_current: i32,
+
+    /// Liquidity of the positions whose range holds the current tick
+    pub liquidity: u128,
+
+    /// LP fees earned per unit of liquidity since creation, Q64.64 wrapping
+    pub fee_growth_global_a_x64: u128,
+    pub fee_growth_global_b_x64: u128,
+
+    /// Dynamic fee mode, every swap then charges an LP fee from lp_fee up
+    /// to max_dynamic_lp_fee as the volatility accumulator grows
+    pub dynamic_fee: bool,
+    pub max_dynamic_lp_fee: u16,
+
+    /// Decaying sum of the relative price moves between swaps, out of FEE_MULTIPLIER
+    pub volatility_accumulator: u64,
+
+    /// B per A reserve ratio after the last swap, Q64.64
+    pub volatility_reference_price_x64: u128,
+
+    /// Unix timestamp the accumulator was last decayed to
+    pub volatility_last_timestamp: i64,
+
+    /// Share of the protocol fee accrued to the creator vaults instead of
+    /// the treasury, out of FEE_MULTIPLIER
+    pub creator_fee_share: u16,
+    pub creator_vault_a: Pubkey,
+    pub creator_vault_b: Pubkey,
+
+    /// Set along with locked while a flash swap calls its receiver, so that
+    /// flash_repay cannot settle it as a flash loan
+    pub flash_swap_locked: bool,
 }
 
 impl SimplePool {
+    pub const LEN: usize = 8+ 5 + 8*2 + 32*11 + 1 + 16*2 + 8 + 2 + 8*2 + 1 + 8 + 8*2 + 2 + 16 + 4 + 16 + 16*2 + 1 + 2 + 8 + 16 + 8 + 2 + 32*2 + 1;
+
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.paused_flags & flag != 0
+    }
+
+    /// Name of the LP token in its metadata, each mint being shown by its
+    /// symbol or else by the first characters of its address, e.g.
+    /// FTRX-LP SOL/EPjF
+    pub fn lp_token_name(&self, symbol_a: Option<String>, symbol_b: Option<String>) -> String {
+        let symbol_a = symbol_a.unwrap_or_else(|| self.mint_a.to_string()[..4].to_string());
+        let symbol_b = symbol_b.unwrap_or_else(|| self.mint_b.to_string()[..4].to_string());
+        format!("{} {}/{}", LP_SYMBOL, symbol_a, symbol_b)
+    }
+
+    /// Swap of a given input priced on the pool curve, selling token A if swap_a
+    pub fn swap_exact_in(&self, swap_a: bool, reserve_in: u64, reserve_out: u64, input: u64) -> Result<SwapAmounts> {
+        self.swap_exact_in_with_fee(swap_a, reserve_in, reserve_out, input, self.lp_fee)
+    }
+
+    /// Same as swap_exact_in, charging lp_fee instead of the pool one
+    pub fn swap_exact_in_with_fee(&self, swap_a: bool, reserve_in: u64, reserve_out: u64, input: u64, lp_fee: u16) -> Result<SwapAmounts> {
+        match self.curve {
+            // Concentrated liquidity is not priced on the reserves
+            CURVE_CONCENTRATED => err!(FTRXSwapError::UnsupportedCurve),
+            CURVE_STABLE => stable_math::swap_exact_in(self.amp, reserve_in, reserve_out, input, lp_fee, self.protocol_fee),
+            CURVE_WEIGHTED => {
+                let (weight_in, weight_out) = if swap_a { (self.weight_a, self.weight_b) } else { (self.weight_b, self.weight_a) };
+                weighted_math::swap_exact_in(weight_in, weight_out, reserve_in, reserve_out, input, lp_fee, self.protocol_fee)
+            }
+            _ => math::swap_exact_in(reserve_in, reserve_out, input, lp_fee, self.protocol_fee),
+        }
+    }
+
+    /// Creator cut of a protocol fee, the treasury getting the rest
+    pub fn creator_fee(&self, protocol_fee: u64) -> u64 {
+        (protocol_fee as u128 * self.creator_fee_share as u128 / FEE_MULTIPLIER as u128) as u64
+    }
+
+    /// Halves the volatility accumulator for each decay period elapsed
+    pub fn decay_volatility(&mut self) -> Result<()> {
+        if !self.dynamic_fee {
+            return Ok(());
+        }
+        self.decay_volatility_to(Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+
+    /// Same as decay_volatility up to a given unix timestamp
+    pub fn decay_volatility_to(&mut self, timestamp: i64) {
+        if !self.dynamic_fee {
+            return;
+        }
+        let periods = timestamp.saturating_sub(self.volatility_last_timestamp) / VOLATILITY_DECAY_PERIOD;
+
+        if periods > 0 {
+            self.volatility_accumulator = self.volatility_accumulator
+                .checked_shr(periods.min(u32::MAX as i64) as u32)
+                .unwrap_or(0);
+            // Partial periods carry over to the next decay
+            self.volatility_last_timestamp += periods * VOLATILITY_DECAY_PERIOD;
+        }
+    }
+
+    /// LP fee of the next trade, scaled from lp_fee to max_dynamic_lp_fee
+    /// with the volatility accumulator in dynamic fee mode
+    pub fn dynamic_lp_fee(&self) -> u16 {
+        if !self.dynamic_fee {
+            return self.lp_fee;
+        }
+        let fee_range = self.max_dynamic_lp_fee.saturating_sub(self.lp_fee) as u64;
+        let volatility = self.volatility_accumulator.min(VOLATILITY_CEILING);
+        self.lp_fee + (fee_range * volatility / VOLATILITY_CEILING) as u16
+    }
+
+    /// Adds the relative move of the reserve ratio since the last swap to
+    /// the volatility accumulator, to be called once the reserves moved
+    pub fn accumulate_volatility(&mut self, reserve_a: u64, reserve_b: u64) {
+        if !self.dynamic_fee {
+            return;
+        }
+        let Some(price) = ((reserve_b as u128) << 64).checked_div(reserve_a as u128) else {
+            return;
+        };
+
+        // Nothing to compare the first swap with
+        let reference = self.volatility_reference_price_x64;
+        if let Some(price_move) = price
+            .abs_diff(reference)
+            .saturating_mul(FEE_MULTIPLIER as u128)
+            .checked_div(reference)
+        {
+            self.volatility_accumulator = self.volatility_accumulator
+                .saturating_add(price_move.min(u64::MAX as u128) as u64);
+        }
+        self.volatility_reference_price_x64 = price;
+    }
+
+    /// Invariant of the pool curve, which no trade may lower
+    pub fn invariant(&self, reserve_a: u64, reserve_b: u64) -> Result<u128> {
+        match self.curve {
+            CURVE_CONCENTRATED => err!(FTRXSwapError::UnsupportedCurve),
+            CURVE_STABLE => stable_math::compute_d(self.amp, reserve_a, reserve_b),
+            // Nonnegative for non empty reserves, so the bits order the same way
+            CURVE_WEIGHTED => weighted_math::ln_invariant(self.weight_a, self.weight_b, reserve_a, reserve_b)
+                .map(|invariant| invariant.to_bits() as u128),
+            _ => Ok(reserve_a as u128 * reserve_b as u128),
+        }
+    }
+
+    /// Accumulates the prices held by the reserves since the last update,
+    /// to be called before the reserves move
+    pub fn update_price_cumulatives(&mut self, reserve_a: u64, reserve_b: u64) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let elapsed = timestamp.saturating_sub(self.last_update_timestamp);
+
+        if elapsed > 0 && reserve_a > 0 && reserve_b > 0 {
+            // Overflows are expected, consumers only use differences
+            let price_a = ((reserve_b as u128) << 64) / reserve_a as u128;
+            let price_b = ((reserve_a as u128) << 64) / reserve_b as u128;
+            self.price_a_cumulative = self.price_a_cumulative.wrapping_add(price_a.wrapping_mul(elapsed as u128));
+            self.price_b_cumulative = self.price_b_cumulative.wrapping_add(price_b.wrapping_mul(elapsed as u128));
+        }
+
+        if elapsed > 0 {
+            self.last_update_timestamp = timestamp;
+        }
+        Ok(())
+    }
+}
+
+
+/// SimplePool as created before the AMM config, read by migrate_pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct LegacySimplePool {
+    pub pool_bump:u8,
+    pub vault_a_bump:u8,
+    pub vault_b_bump:u8,
+    pub treas_a_bump:u8,
+    pub treas_b_bump:u8,
+    pub creator: Pubkey,
+    pub admin: Pubkey,
+    pub lp_fee: u16,
+    pub protocol_fee: u16,
+    pub mint_a: Pubkey,
+    pub vault_mint_a: Pubkey,
+    pub treasury_mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub vault_mint_b: Pubkey,
+    pub treasury_mint_b: Pubkey,
+}
+
+impl LegacySimplePool {
+    /// Space the legacy accounts were allocated with
     pub const LEN: usize = 8+ 5 + 8*2 + 32*8;
-}
\ No newline at end of file
+}
+
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Observation {
+    pub timestamp: i64,
+    pub price_a_cumulative: u128,
+    pub price_b_cumulative: u128,
+}
+
+impl Observation {
+    pub const LEN: usize = 8 + 16*2;
+}
+
+/// Ring buffer of the pool cumulative prices, a TWAP over any window it
+/// covers is the difference of two observations over their time difference
+#[account]
+pub struct PoolObservations {
+    pub pool: Pubkey,
+
+    /// Slot of the most recent observation
+    pub index: u16,
+
+    pub observations: [Observation; OBSERVATION_COUNT],
+
+    pub bump: u8,
+}
+
+impl PoolObservations {
+    pub const LEN: usize = 8 + 32 + 2 + Observation::LEN * OBSERVATION_COUNT + 1;
+
+    /// Stores the pool cumulative prices, at most once per timestamp
+    pub fn record(&mut self, pool: &SimplePool) {
+        let latest = self.observations[self.index as usize];
+        if latest.timestamp == pool.last_update_timestamp {
+            return;
+        }
+
+        self.index = ((self.index as usize + 1) % OBSERVATION_COUNT) as u16;
+        self.observations[self.index as usize] = Observation {
+            timestamp: pool.last_update_timestamp,
+            price_a_cumulative: pool.price_a_cumulative,
+            price_b_cumulative: pool.price_b_cumulative,
+        };
+    }
+}
+
+
+/// Liquidity referencing a tick of a concentrated pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tick {
+    /// Set while some position has this tick as a bound
+    pub initialized: bool,
+
+    /// Liquidity added when the price crosses the tick upwards
+    pub liquidity_net: i128,
+
+    /// Liquidity of the positions bounded by the tick
+    pub liquidity_gross: u128,
+
+    /// Fee growth on the other side of the tick from the current one
+    pub fee_growth_outside_a_x64: u128,
+    pub fee_growth_outside_b_x64: u128,
+}
+
+impl Tick {
+    pub const LEN: usize = 1 + 16*2 + 16*2;
+
+    /// Adds liquidity_delta to the positions bounded by the tick, as their
+    /// lower bound or else their upper bound
+    pub fn update(&mut self, tick_index: i32, pool: &SimplePool, liquidity_delta: i128, lower: bool) -> Result<()> {
+        let liquidity_gross = if liquidity_delta >= 0 {
+            self.liquidity_gross.checked_add(liquidity_delta as u128)
+        } else {
+            self.liquidity_gross.checked_sub(liquidity_delta.unsigned_abs())
+        }
+        .ok_or(FTRXSwapError::MathOverflow)?;
+
+        if liquidity_gross == 0 {
+            *self = Tick::default();
+            return Ok(());
+        }
+
+        // By convention all the growth so far happened below a tick the
+        // price is at or above
+        if self.liquidity_gross == 0 && pool.tick_current >= tick_index {
+            self.fee_growth_outside_a_x64 = pool.fee_growth_global_a_x64;
+            self.fee_growth_outside_b_x64 = pool.fee_growth_global_b_x64;
+        }
+
+        let net_delta = if lower { liquidity_delta } else { -liquidity_delta };
+        self.liquidity_net = self.liquidity_net
+            .checked_add(net_delta)
+            .ok_or(FTRXSwapError::MathOverflow)?;
+        self.liquidity_gross = liquidity_gross;
+        self.initialized = true;
+        Ok(())
+    }
+
+    /// Flips the outside fee growth as the price crosses the tick, returning
+    /// the liquidity added when crossing upwards
+    pub fn cross(&mut self, fee_growth_global_a_x64: u128, fee_growth_global_b_x64: u128) -> i128 {
+        self.fee_growth_outside_a_x64 = fee_growth_global_a_x64.wrapping_sub(self.fee_growth_outside_a_x64);
+        self.fee_growth_outside_b_x64 = fee_growth_global_b_x64.wrapping_sub(self.fee_growth_outside_b_x64);
+        self.liquidity_net
+    }
+}
+
+/// TICK_ARRAY_SIZE consecutive usable ticks of a concentrated pool, from
+/// start_tick_index on
+#[account]
+pub struct TickArray {
+    pub pool: Pubkey,
+
+    /// Multiple of tick_spacing * TICK_ARRAY_SIZE
+    pub start_tick_index: i32,
+
+    pub ticks: [Tick; TICK_ARRAY_SIZE],
+
+    pub bump: u8,
+}
+
+impl TickArray {
+    pub const LEN: usize = 8 + 32 + 4 + Tick::LEN * TICK_ARRAY_SIZE + 1;
+
+    /// Number of ticks one array spans
+    pub fn span(tick_spacing: u16) -> i32 {
+        tick_spacing as i32 * TICK_ARRAY_SIZE as i32
+    }
+
+    /// Start of the array holding a tick
+    pub fn start_index_for(tick_index: i32, tick_spacing: u16) -> i32 {
+        let span = Self::span(tick_spacing);
+        tick_index.div_euclid(span) * span
+    }
+
+    /// Start indexes of arrays holding usable ticks
+    pub fn is_valid_start(start_tick_index: i32, tick_spacing: u16) -> bool {
+        let span = Self::span(tick_spacing);
+        start_tick_index.rem_euclid(span) == 0
+            && start_tick_index <= MAX_TICK
+            && start_tick_index + span > MIN_TICK
+    }
+
+    /// Whether the array covers tick_index, usable or not
+    pub fn contains(&self, tick_index: i32, tick_spacing: u16) -> bool {
+        tick_index >= self.start_tick_index && tick_index < self.start_tick_index + Self::span(tick_spacing)
+    }
+
+    /// Slot of a usable tick in the array
+    pub fn offset(&self, tick_index: i32, tick_spacing: u16) -> Result<usize> {
+        if tick_index % tick_spacing as i32 != 0 {
+            return err!(FTRXSwapError::InvalidTick);
+        }
+        if !self.contains(tick_index, tick_spacing) {
+            return err!(FTRXSwapError::InvalidTickArray);
+        }
+        Ok(((tick_index - self.start_tick_index) / tick_spacing as i32) as usize)
+    }
+
+    pub fn tick(&self, tick_index: i32, tick_spacing: u16) -> Result<&Tick> {
+        Ok(&self.ticks[self.offset(tick_index, tick_spacing)?])
+    }
+
+    pub fn tick_mut(&mut self, tick_index: i32, tick_spacing: u16) -> Result<&mut Tick> {
+        let offset = self.offset(tick_index, tick_spacing)?;
+        Ok(&mut self.ticks[offset])
+    }
+}
+
+/// Liquidity an owner provides to a concentrated pool on a tick range
+#[account]
+#[derive(Default)]
+pub struct Position {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+
+    /// Range [tick_lower, tick_upper) the liquidity is active on
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+
+    pub liquidity: u128,
+
+    /// Fee growth inside the range when the fees owed were last updated
+    pub fee_growth_inside_a_last_x64: u128,
+    pub fee_growth_inside_b_last_x64: u128,
+
+    /// Fees earned and not collected yet
+    pub fees_owed_a: u64,
+    pub fees_owed_b: u64,
+
+    pub bump: u8,
+}
+
+impl Position {
+    pub const LEN: usize = 8 + 32*2 + 4*2 + 16 + 16*2 + 8*2 + 1;
+
+    /// Credits the fees earned by the current liquidity up to the given
+    /// fee growth inside the range
+    pub fn update_fees(&mut self, fee_growth_inside_a_x64: u128, fee_growth_inside_b_x64: u128) -> Result<()> {
+        let earned_a = clmm_math::fees_earned(
+            fee_growth_inside_a_x64.wrapping_sub(self.fee_growth_inside_a_last_x64),
+            self.liquidity,
+        )?;
+        let earned_b = clmm_math::fees_earned(
+            fee_growth_inside_b_x64.wrapping_sub(self.fee_growth_inside_b_last_x64),
+            self.liquidity,
+        )?;
+
+        self.fees_owed_a = self.fees_owed_a.checked_add(earned_a).ok_or(FTRXSwapError::MathOverflow)?;
+        self.fees_owed_b = self.fees_owed_b.checked_add(earned_b).ok_or(FTRXSwapError::MathOverflow)?;
+        self.fee_growth_inside_a_last_x64 = fee_growth_inside_a_x64;
+        self.fee_growth_inside_b_last_x64 = fee_growth_inside_b_x64;
+        Ok(())
+    }
+}
+
+
+/// Point in time after which an instruction is rejected
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Deadline {
+    /// Compared to the unix timestamp of the Clock sysvar
+    Timestamp(i64),
+    /// Compared to the slot of the Clock sysvar
+    Slot(u64),
+}
+
+impl Deadline {
+    pub fn check(&self) -> Result<()> {
+        let clock = Clock::get()?;
+        let exceeded = match *self {
+            Deadline::Timestamp(timestamp) => clock.unix_timestamp > timestamp,
+            Deadline::Slot(slot) => clock.slot > slot,
+        };
+        if exceeded {
+            return err!(FTRXSwapError::DeadlineExceeded);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_layout_is_a_prefix() {
+        let pool = SimplePool {
+            pool_bump: 1,
+            treas_b_bump: 5,
+            creator: Pubkey::new_unique(),
+            admin: Pubkey::new_unique(),
+            lp_fee: 30,
+            protocol_fee: 1,
+            mint_a: Pubkey::new_unique(),
+            treasury_mint_b: Pubkey::new_unique(),
+            amm: Pubkey::new_unique(),
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        pool.try_serialize(&mut data).unwrap();
+        assert!(data.len() <= SimplePool::LEN);
+
+        let legacy = LegacySimplePool::deserialize(&mut &data[8..]).unwrap();
+        assert_eq!((legacy.pool_bump, legacy.treas_b_bump), (1, 5));
+        assert_eq!((legacy.creator, legacy.admin), (pool.creator, pool.admin));
+        assert_eq!((legacy.lp_fee, legacy.protocol_fee), (30, 1));
+        assert_eq!((legacy.mint_a, legacy.treasury_mint_b), (pool.mint_a, pool.treasury_mint_b));
+    }
+}
@@ -0,0 +1,118 @@
+use anchor_lang::{prelude::*, solana_program::program_pack::Pack};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, ExtensionType},
+    pod::pod_from_bytes,
+    state::{Account, Mint},
+};
+
+use crate::errors::FTRXSwapError;
+
+/// Extensions of later Token-2022 versions than the one this program is
+/// built with, by type number
+const METADATA_POINTER: u16 = 18;
+const TOKEN_METADATA: u16 = 19;
+const GROUP_POINTER: u16 = 20;
+const TOKEN_GROUP: u16 = 21;
+const GROUP_MEMBER_POINTER: u16 = 22;
+const TOKEN_GROUP_MEMBER: u16 = 23;
+
+/// Token-2022 mint extensions a pool can hold, the others could block the
+/// vaults or move their funds without the pool signing. Transfer fees are
+/// accounted for, the rest only describe the mint
+const SUPPORTED_MINT_EXTENSIONS: [u16; 9] = [
+    ExtensionType::TransferFeeConfig as u16,
+    ExtensionType::InterestBearingConfig as u16,
+    ExtensionType::MintCloseAuthority as u16,
+    METADATA_POINTER,
+    TOKEN_METADATA,
+    GROUP_POINTER,
+    TOKEN_GROUP,
+    GROUP_MEMBER_POINTER,
+    TOKEN_GROUP_MEMBER,
+];
+
+/// Type numbers and values of the extensions of a Token-2022 mint, read
+/// from the raw TLV data so that extensions unknown to this version of the
+/// token program do not fail the parsing
+fn mint_extensions(data: &[u8]) -> Result<Vec<(u16, &[u8])>> {
+    let mut extensions = vec![];
+    if data.len() <= Mint::LEN {
+        return Ok(extensions);
+    }
+
+    // The extensions follow the account type, after the base mint padded
+    // to the size of a token account
+    let mut index = Account::LEN + 1;
+    while index + 4 <= data.len() {
+        let extension_type = u16::from_le_bytes([data[index], data[index + 1]]);
+        let length = u16::from_le_bytes([data[index + 2], data[index + 3]]) as usize;
+        // Nothing is written after an uninitialized entry
+        if extension_type == ExtensionType::Uninitialized as u16 {
+            break;
+        }
+        let value = data
+            .get(index + 4..index + 4 + length)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        extensions.push((extension_type, value));
+        index += 4 + length;
+    }
+    Ok(extensions)
+}
+
+/// Rejects Token-2022 mints using extensions the pool does not support
+pub fn validate_mint_extensions(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    for (extension_type, _) in mint_extensions(&data)? {
+        if !SUPPORTED_MINT_EXTENSIONS.contains(&extension_type) {
+            return err!(FTRXSwapError::UnsupportedMintExtension);
+        }
+    }
+    Ok(())
+}
+
+/// Transfer fee settings of a Token-2022 mint, from its account data
+pub fn mint_transfer_fee_config(data: &[u8]) -> Result<Option<TransferFeeConfig>> {
+    let config = mint_extensions(data)?
+        .into_iter()
+        .find(|(extension_type, _)| *extension_type == ExtensionType::TransferFeeConfig as u16)
+        .map(|(_, value)| pod_from_bytes::<TransferFeeConfig>(value).copied())
+        .transpose()?;
+    Ok(config)
+}
+
+fn transfer_fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(None);
+    }
+
+    let data = mint.try_borrow_data()?;
+    mint_transfer_fee_config(&data)
+}
+
+/// Fee withheld by the mint when `amount` is transferred in the current epoch
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let fee = match transfer_fee_config(mint)? {
+        Some(config) => config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(FTRXSwapError::MathOverflow)?,
+        None => 0,
+    };
+    Ok(fee)
+}
+
+/// Fee withheld by the mint on the transfer crediting exactly `amount` in
+/// the current epoch
+pub fn inverse_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let fee = match transfer_fee_config(mint)? {
+        Some(config) => config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(FTRXSwapError::MathOverflow)?,
+        None => 0,
+    };
+    Ok(fee)
+}
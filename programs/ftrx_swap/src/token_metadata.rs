@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use mpl_token_metadata::state::Key;
+
+/// Name and symbol of a mint in its Metaplex metadata, None when the
+/// account holds none
+pub fn name_and_symbol(metadata: &AccountInfo) -> Option<(String, String)> {
+    if *metadata.owner != mpl_token_metadata::ID {
+        return None;
+    }
+
+    let data = metadata.try_borrow_data().ok()?;
+    if data.first() != Some(&(Key::MetadataV1 as u8)) {
+        return None;
+    }
+
+    // The key, update authority and mint come first, the strings are padded
+    // with zeros by the metadata program
+    let mut fields = data.get(1 + 32 + 32..)?;
+    let name = String::deserialize(&mut fields).ok()?;
+    let symbol = String::deserialize(&mut fields).ok()?;
+    Some((
+        name.trim_end_matches('\0').to_string(),
+        symbol.trim_end_matches('\0').to_string(),
+    ))
+}
+
+/// Symbol of a mint in its Metaplex metadata, None when it has none
+pub fn symbol(metadata: &AccountInfo) -> Option<String> {
+    name_and_symbol(metadata)
+        .map(|(_, symbol)| symbol.trim().to_string())
+        .filter(|symbol| !symbol.is_empty())
+}
@@ -0,0 +1,215 @@
+use anchor_lang::prelude::*;
+use fixed::types::I64F64;
+
+use crate::{
+    constants::{FEE_MULTIPLIER, WEIGHT_MULTIPLIER},
+    errors::FTRXSwapError,
+    math::SwapAmounts,
+};
+
+/// Natural logarithm, from x = m * 2^k with m in [1, 2) and the series
+/// ln(m) = 2 * (z + z^3/3 + z^5/5 + ...) where z = (m - 1) / (m + 1) < 1/3
+pub fn ln(x: I64F64) -> Result<I64F64> {
+    let exponent = x
+        .checked_int_log2()
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    let mantissa = if exponent >= 0 { x >> exponent as u32 } else { x << (-exponent) as u32 };
+
+    let z = (mantissa - I64F64::ONE) / (mantissa + I64F64::ONE);
+    let z_squared = z * z;
+
+    // Each term is at most a ninth of the previous one, they vanish before
+    // the denominator grows large
+    let mut term = z;
+    let mut sum = z;
+    let mut denominator = 1;
+    while term != I64F64::ZERO {
+        term *= z_squared;
+        denominator += 2;
+        sum += term / I64F64::from_num(denominator);
+    }
+
+    Ok(sum * 2 + I64F64::LN_2 * I64F64::from_num(exponent))
+}
+
+/// Exponential, from y = k * ln(2) + r with r in [0, ln(2)) and the Taylor
+/// series of e^r, zero below the I64F64 resolution
+pub fn exp(y: I64F64) -> Result<I64F64> {
+    let exponent = (y / I64F64::LN_2).floor();
+    if exponent < -64 {
+        return Ok(I64F64::ZERO);
+    }
+    // 2^62 leaves room for e^r < 2 without reaching the sign bit
+    if exponent > 61 {
+        return err!(FTRXSwapError::MathOverflow);
+    }
+    let remainder = y - exponent * I64F64::LN_2;
+
+    let mut term = I64F64::ONE;
+    let mut sum = I64F64::ONE;
+    let mut index = 0;
+    while term != I64F64::ZERO {
+        index += 1;
+        term = term * remainder / I64F64::from_num(index);
+        sum += term;
+    }
+
+    let exponent = exponent.to_num::<i32>();
+    Ok(if exponent >= 0 { sum << exponent as u32 } else { sum >> (-exponent) as u32 })
+}
+
+/// base^exponent for a positive base
+pub fn pow(base: I64F64, exponent: I64F64) -> Result<I64F64> {
+    let log = ln(base)?
+        .checked_mul(exponent)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    exp(log)
+}
+
+/// Logarithm of the weighted product x^wa * y^wb, which orders pool states
+/// the same way as the product itself
+pub fn ln_invariant(weight_a: u64, weight_b: u64, reserve_a: u64, reserve_b: u64) -> Result<I64F64> {
+    if reserve_a == 0 || reserve_b == 0 {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+    let weight_multiplier = I64F64::from_num(WEIGHT_MULTIPLIER);
+    let weighted_a = ln(I64F64::from_num(reserve_a))? * I64F64::from_num(weight_a) / weight_multiplier;
+    let weighted_b = ln(I64F64::from_num(reserve_b))? * I64F64::from_num(weight_b) / weight_multiplier;
+    Ok(weighted_a + weighted_b)
+}
+
+/// Weighted geometric mean of the first deposit, the LP supply it mints
+pub fn initial_liquidity(weight_a: u64, weight_b: u64, amount_a: u64, amount_b: u64) -> Result<u64> {
+    let liquidity = exp(ln_invariant(weight_a, weight_b, amount_a, amount_b)?)?;
+    liquidity
+        .floor()
+        .checked_to_num::<u64>()
+        .ok_or(error!(FTRXSwapError::MathOverflow))
+}
+
+/// Weighted product counterpart of `math::swap_exact_in`, with the same fee
+/// split: the LP fee joins the input reserve before the trade is priced
+///
+/// new_reserve_out = reserve_out * (reserve_in / new_reserve_in)^(weight_in / weight_out)
+#[allow(clippy::too_many_arguments)]
+pub fn swap_exact_in(
+    weight_in: u64,
+    weight_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    input: u64,
+    lp_fee: u16,
+    protocol_fee: u16,
+) -> Result<SwapAmounts> {
+    let to_treasury_fee = input
+        .checked_mul(protocol_fee as u64)
+        .ok_or(FTRXSwapError::MathOverflow)?
+        / FEE_MULTIPLIER;
+    let lp_fee_amount = input
+        .checked_mul(lp_fee as u64)
+        .ok_or(FTRXSwapError::MathOverflow)?
+        / FEE_MULTIPLIER;
+    let taxed_input = input
+        .checked_sub(to_treasury_fee)
+        .and_then(|amount| amount.checked_sub(lp_fee_amount))
+        .ok_or(FTRXSwapError::InvalidFee)?;
+
+    let new_reserve_in_with_lp_fees = reserve_in
+        .checked_add(lp_fee_amount)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    let new_reserve_in = new_reserve_in_with_lp_fees
+        .checked_add(taxed_input)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    if new_reserve_in_with_lp_fees == 0 {
+        return err!(FTRXSwapError::InsufficientLiquidity);
+    }
+
+    let base = I64F64::from_num(new_reserve_in_with_lp_fees) / I64F64::from_num(new_reserve_in);
+    let factor = pow(base, I64F64::from_num(weight_in) / I64F64::from_num(weight_out))?;
+
+    // Rounding the new output reserve up, plus a margin well above the
+    // precision of pow, keeps the rounding error in the pool
+    let theoretical_new_reserve_out = I64F64::from_num(reserve_out)
+        .checked_mul(factor)
+        .ok_or(FTRXSwapError::MathOverflow)?
+        .ceil()
+        .to_num::<u64>()
+        .saturating_add((reserve_out >> 40) + 1);
+
+    let output = reserve_out.saturating_sub(theoretical_new_reserve_out);
+
+    Ok(SwapAmounts {
+        input,
+        output,
+        lp_fee: lp_fee_amount,
+        protocol_fee: to_treasury_fee,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::MIN_WEIGHT;
+
+    fn assert_close(value: I64F64, expected: f64, relative: f64) {
+        let value = value.to_num::<f64>();
+        assert!(
+            (value - expected).abs() <= expected.abs() * relative + 1e-15,
+            "{value} instead of {expected}"
+        );
+    }
+
+    #[test]
+    fn ln_matches_on_the_whole_range() {
+        for x in [1e-12, 1e-6, 0.5, 1.0, 1.5, 2.0, 10.0, 12345.678, 1e9, 1e18] {
+            // Compared on the I64F64 value, small inputs lose digits in the conversion
+            let x = I64F64::from_num(x);
+            assert_close(ln(x).unwrap(), x.to_num::<f64>().ln(), 1e-12);
+        }
+        assert_eq!(ln(I64F64::ONE).unwrap(), I64F64::ZERO);
+        assert!(ln(I64F64::ZERO).is_err());
+    }
+
+    #[test]
+    fn exp_matches_on_the_whole_range() {
+        for y in [-40.0_f64, -10.0, -1.0, -1e-9, 0.0, 1e-9, 0.5, 1.0, 10.0, 40.0] {
+            assert_close(exp(I64F64::from_num(y)).unwrap(), y.exp(), 1e-12);
+        }
+        assert_eq!(exp(I64F64::from_num(-100)).unwrap(), I64F64::ZERO);
+        assert!(exp(I64F64::from_num(50)).is_err());
+    }
+
+    #[test]
+    fn pow_matches_at_extreme_weights_and_ratios() {
+        // Swap exponents go from MIN_WEIGHT / (1 - MIN_WEIGHT) to its inverse
+        let lightest = MIN_WEIGHT as f64 / (WEIGHT_MULTIPLIER - MIN_WEIGHT) as f64;
+        for exponent in [lightest, 0.25, 1.0, 4.0, 1.0 / lightest] {
+            for base in [1e-6, 0.01, 0.5, 0.999_999, 1.0] {
+                let (base, exponent) = (I64F64::from_num(base), I64F64::from_num(exponent));
+                let expected = base.to_num::<f64>().powf(exponent.to_num::<f64>());
+                assert_close(pow(base, exponent).unwrap(), expected, 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn swap_rounds_for_the_pool_at_extreme_weights() {
+        let heaviest = WEIGHT_MULTIPLIER - MIN_WEIGHT;
+        for (weight_in, weight_out) in [(MIN_WEIGHT, heaviest), (heaviest, MIN_WEIGHT), (500_000, 500_000)] {
+            for (reserve_in, reserve_out, input) in [(1_000_000_000, 1_000_000_000, 1_000_000), (1_000, 1_000_000_000_000, 1_000_000), (1_000_000_000_000, 1_000, 1)] {
+                let amounts = swap_exact_in(weight_in, weight_out, reserve_in, reserve_out, input, 0, 0).unwrap();
+
+                let exponent = weight_in as f64 / weight_out as f64;
+                let ratio = reserve_in as f64 / (reserve_in + input) as f64;
+                let exact = reserve_out as f64 * (1.0 - ratio.powf(exponent));
+                assert!(amounts.output as f64 <= exact);
+                assert!(amounts.output as f64 >= exact - 2.0 - (reserve_out >> 39) as f64);
+
+                // The weighted product never decreases
+                let before = ln_invariant(weight_in, weight_out, reserve_in, reserve_out).unwrap();
+                let after = ln_invariant(weight_in, weight_out, reserve_in + input, reserve_out - amounts.output).unwrap();
+                assert!(after >= before);
+            }
+        }
+    }
+}
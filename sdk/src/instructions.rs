@@ -0,0 +1,898 @@
+//! Builders for every instruction of the program, the accounts being
+//! derived from PoolKeys and UserKeys
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        system_program, sysvar,
+    },
+    Id, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{associated_token::AssociatedToken, metadata::Metadata, token::Token, token_2022::Token2022};
+use ftrx_swap::{accounts, instruction, state::{Deadline, TickArray}};
+
+use crate::{pda, PoolKeys, UserKeys};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ftrx_swap::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn build_with_remaining(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut ix = build(accounts, data);
+    ix.accounts.extend(remaining_accounts);
+    ix
+}
+
+/// create_amm, payer being the upgrade authority of the program
+pub fn create_amm(
+    admin: Pubkey,
+    payer: Pubkey,
+    min_lp_fee: u16,
+    max_lp_fee: u16,
+    max_protocol_fee: u16,
+) -> Instruction {
+    build(
+        accounts::CreateAmm {
+            amm: pda::amm().0,
+            admin,
+            program: ftrx_swap::ID,
+            program_data: pda::program_data().0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateAmm { min_lp_fee, max_lp_fee, max_protocol_fee },
+    )
+}
+
+/// update_amm
+pub fn update_amm(
+    admin: Pubkey,
+    paused: bool,
+    min_lp_fee: u16,
+    max_lp_fee: u16,
+    max_protocol_fee: u16,
+) -> Instruction {
+    build(
+        accounts::UpdateAmm { amm: pda::amm().0, admin },
+        instruction::UpdateAmm { paused, min_lp_fee, max_lp_fee, max_protocol_fee },
+    )
+}
+
+/// create_fee_tier
+pub fn create_fee_tier(admin: Pubkey, payer: Pubkey, lp_fee: u16, protocol_fee: u16, creator_fee_share: u16) -> Instruction {
+    build(
+        accounts::CreateFeeTier {
+            amm: pda::amm().0,
+            fee_tier: pda::fee_tier(lp_fee).0,
+            admin,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::CreateFeeTier { lp_fee, protocol_fee, creator_fee_share },
+    )
+}
+
+/// update_fee_tier
+pub fn update_fee_tier(admin: Pubkey, lp_fee: u16, protocol_fee: u16, creator_fee_share: u16, enabled: bool) -> Instruction {
+    build(
+        accounts::UpdateFeeTier { amm: pda::amm().0, fee_tier: pda::fee_tier(lp_fee).0, admin },
+        instruction::UpdateFeeTier { protocol_fee, creator_fee_share, enabled },
+    )
+}
+
+fn create_pool_accounts(keys: &PoolKeys) -> accounts::CreatePool {
+    accounts::CreatePool {
+        pool: keys.pool,
+        amm: keys.amm,
+        fee_tier: keys.fee_tier,
+        observations: keys.observations,
+        admin: keys.admin_seed,
+        mint_liquidity: keys.mint_liquidity,
+        lp_metadata: keys.lp_metadata,
+        metadata_a: pda::metadata(&keys.mint_a).0,
+        metadata_b: pda::metadata(&keys.mint_b).0,
+        mint_a: keys.mint_a,
+        mint_b: keys.mint_b,
+        pool_account_a: keys.pool_account_a,
+        pool_account_b: keys.pool_account_b,
+        treasury_mint_a: keys.treasury_mint_a,
+        treasury_mint_b: keys.treasury_mint_b,
+        creator_vault_a: keys.creator_vault_a,
+        creator_vault_b: keys.creator_vault_b,
+        payer: keys.creator,
+        token_program: Token::id(),
+        token_program_a: keys.token_program_a,
+        token_program_b: keys.token_program_b,
+        associated_token_program: AssociatedToken::id(),
+        token_metadata_program: Metadata::id(),
+        system_program: system_program::ID,
+        rent: sysvar::rent::ID,
+    }
+}
+
+/// Bumps stored by the pool at creation: pool, vaults and treasuries
+fn create_pool_bumps(keys: &PoolKeys) -> (u8, u8, u8, u8, u8) {
+    (
+        pda::pool(&keys.mint_a, &keys.mint_b, &keys.admin_seed, keys.lp_fee_seed).1,
+        pda::pool_vault(&keys.mint_a, &keys.pool).1,
+        pda::pool_vault(&keys.mint_b, &keys.pool).1,
+        pda::treasury(&keys.mint_a, &keys.pool, &keys.admin_seed).1,
+        pda::treasury(&keys.mint_b, &keys.pool, &keys.admin_seed).1,
+    )
+}
+
+/// create_pool, the creator of the keys paying for the accounts
+pub fn create_pool(keys: &PoolKeys) -> Instruction {
+    let (bump_pool, bump_vault_a, bump_vault_b, bump_treas_a, bump_treas_b) = create_pool_bumps(keys);
+    build(
+        create_pool_accounts(keys),
+        instruction::CreatePool {
+            lp_fee: keys.lp_fee_seed,
+            bump_pool,
+            bump_vault_a,
+            bump_vault_b,
+            bump_treas_a,
+            bump_treas_b,
+        },
+    )
+}
+
+/// create_stable_pool with amplification coefficient amp
+pub fn create_stable_pool(keys: &PoolKeys, amp: u64) -> Instruction {
+    let (bump_pool, bump_vault_a, bump_vault_b, bump_treas_a, bump_treas_b) = create_pool_bumps(keys);
+    build(
+        create_pool_accounts(keys),
+        instruction::CreateStablePool {
+            lp_fee: keys.lp_fee_seed,
+            amp,
+            bump_pool,
+            bump_vault_a,
+            bump_vault_b,
+            bump_treas_a,
+            bump_treas_b,
+        },
+    )
+}
+
+/// create_weighted_pool, token B getting the remaining weight
+pub fn create_weighted_pool(keys: &PoolKeys, weight_a: u64) -> Instruction {
+    let (bump_pool, bump_vault_a, bump_vault_b, bump_treas_a, bump_treas_b) = create_pool_bumps(keys);
+    build(
+        create_pool_accounts(keys),
+        instruction::CreateWeightedPool {
+            lp_fee: keys.lp_fee_seed,
+            weight_a,
+            bump_pool,
+            bump_vault_a,
+            bump_vault_b,
+            bump_treas_a,
+            bump_treas_b,
+        },
+    )
+}
+
+/// create_concentrated_pool starting at sqrt_price_x64
+pub fn create_concentrated_pool(keys: &PoolKeys, tick_spacing: u16, sqrt_price_x64: u128) -> Instruction {
+    let (bump_pool, bump_vault_a, bump_vault_b, bump_treas_a, bump_treas_b) = create_pool_bumps(keys);
+    build(
+        create_pool_accounts(keys),
+        instruction::CreateConcentratedPool {
+            lp_fee: keys.lp_fee_seed,
+            tick_spacing,
+            sqrt_price_x64,
+            bump_pool,
+            bump_vault_a,
+            bump_vault_b,
+            bump_treas_a,
+            bump_treas_b,
+        },
+    )
+}
+
+/// initialize_tick_array
+pub fn initialize_tick_array(keys: &PoolKeys, payer: Pubkey, start_tick_index: i32) -> Instruction {
+    build(
+        accounts::InitializeTickArray {
+            pool: keys.pool,
+            tick_array: pda::tick_array(&keys.pool, start_tick_index).0,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeTickArray { start_tick_index },
+    )
+}
+
+/// open_position
+pub fn open_position(keys: &PoolKeys, owner: Pubkey, payer: Pubkey, tick_lower: i32, tick_upper: i32) -> Instruction {
+    build(
+        accounts::OpenPosition {
+            pool: keys.pool,
+            position: pda::position(&keys.pool, &owner, tick_lower, tick_upper).0,
+            owner,
+            payer,
+            system_program: system_program::ID,
+        },
+        instruction::OpenPosition { tick_lower, tick_upper },
+    )
+}
+
+fn modify_position_accounts(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    tick_lower: i32,
+    tick_upper: i32,
+    tick_spacing: u16,
+) -> accounts::ModifyPosition {
+    accounts::ModifyPosition {
+        pool: keys.pool,
+        amm: keys.amm,
+        position: pda::position(&keys.pool, &user.owner, tick_lower, tick_upper).0,
+        tick_array_lower: pda::tick_array(&keys.pool, TickArray::start_index_for(tick_lower, tick_spacing)).0,
+        tick_array_upper: pda::tick_array(&keys.pool, TickArray::start_index_for(tick_upper, tick_spacing)).0,
+        mint_a: keys.mint_a,
+        mint_b: keys.mint_b,
+        pool_account_a: keys.pool_account_a,
+        pool_account_b: keys.pool_account_b,
+        owner_account_a: user.token_a,
+        owner_account_b: user.token_b,
+        owner: user.owner,
+        token_program_a: keys.token_program_a,
+        token_program_b: keys.token_program_b,
+        associated_token_program: AssociatedToken::id(),
+        system_program: system_program::ID,
+    }
+}
+
+/// increase_liquidity of the position of user between tick_lower and tick_upper
+#[allow(clippy::too_many_arguments)]
+pub fn increase_liquidity(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    tick_lower: i32,
+    tick_upper: i32,
+    tick_spacing: u16,
+    liquidity: u128,
+    max_amount_a: u64,
+    max_amount_b: u64,
+) -> Instruction {
+    build(
+        modify_position_accounts(keys, user, tick_lower, tick_upper, tick_spacing),
+        instruction::IncreaseLiquidity { liquidity, max_amount_a, max_amount_b },
+    )
+}
+
+/// decrease_liquidity of the position of user between tick_lower and tick_upper
+#[allow(clippy::too_many_arguments)]
+pub fn decrease_liquidity(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    tick_lower: i32,
+    tick_upper: i32,
+    tick_spacing: u16,
+    liquidity: u128,
+    min_amount_a: u64,
+    min_amount_b: u64,
+) -> Instruction {
+    build(
+        modify_position_accounts(keys, user, tick_lower, tick_upper, tick_spacing),
+        instruction::DecreaseLiquidity { liquidity, min_amount_a, min_amount_b },
+    )
+}
+
+/// collect_position_fees of the position of user between tick_lower and tick_upper
+pub fn collect_position_fees(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    tick_lower: i32,
+    tick_upper: i32,
+    tick_spacing: u16,
+) -> Instruction {
+    build(
+        modify_position_accounts(keys, user, tick_lower, tick_upper, tick_spacing),
+        instruction::CollectPositionFees {},
+    )
+}
+
+/// close_position
+pub fn close_position(keys: &PoolKeys, owner: Pubkey, tick_lower: i32, tick_upper: i32) -> Instruction {
+    build(
+        accounts::ClosePosition { position: pda::position(&keys.pool, &owner, tick_lower, tick_upper).0, owner },
+        instruction::ClosePosition {},
+    )
+}
+
+/// concentrated_swap, tick_arrays being contiguous in the direction of the
+/// swap and the first one holding the current tick
+pub fn concentrated_swap(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    a_to_b: bool,
+    input_amount: u64,
+    min_output_amount: u64,
+    tick_arrays: &[Pubkey],
+) -> Instruction {
+    build_with_remaining(
+        accounts::ConcentratedSwap {
+            pool: keys.pool,
+            amm: keys.amm,
+            mint_a: keys.mint_a,
+            mint_b: keys.mint_b,
+            pool_account_a: keys.pool_account_a,
+            pool_account_b: keys.pool_account_b,
+            trader_account_a: user.token_a,
+            trader_account_b: user.token_b,
+            treasury_mint_a: keys.treasury_mint_a,
+            treasury_mint_b: keys.treasury_mint_b,
+            creator_vault_a: keys.creator_vault_a,
+            creator_vault_b: keys.creator_vault_b,
+            payer: user.owner,
+            token_program_a: keys.token_program_a,
+            token_program_b: keys.token_program_b,
+            associated_token_program: AssociatedToken::id(),
+            system_program: system_program::ID,
+        },
+        instruction::ConcentratedSwap { a_to_b, input_amount, min_output_amount },
+        tick_arrays.iter().map(|tick_array| AccountMeta::new(*tick_array, false)).collect(),
+    )
+}
+
+fn deposit_accounts(keys: &PoolKeys, user: &UserKeys) -> accounts::DepositLiquidity {
+    accounts::DepositLiquidity {
+        pool: keys.pool,
+        amm: keys.amm,
+        observations: keys.observations,
+        depositor: user.owner,
+        mint_liquidity: keys.mint_liquidity,
+        mint_a: keys.mint_a,
+        mint_b: keys.mint_b,
+        pool_account_a: keys.pool_account_a,
+        pool_account_b: keys.pool_account_b,
+        depositor_account_liquidity: user.liquidity,
+        depositor_account_a: user.token_a,
+        depositor_account_b: user.token_b,
+        payer: user.owner,
+        token_program: Token::id(),
+        token_program_a: keys.token_program_a,
+        token_program_b: keys.token_program_b,
+        associated_token_program: AssociatedToken::id(),
+        system_program: system_program::ID,
+    }
+}
+
+/// deposit_liquidity
+pub fn deposit_liquidity(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    amount_a: u64,
+    amount_b: u64,
+    expected_lp_token: u64,
+) -> Instruction {
+    build(
+        deposit_accounts(keys, user),
+        instruction::DepositLiquidity { amount_a, amount_b, expected_lp_token },
+    )
+}
+
+/// deposit_liquidity_with_deadline
+pub fn deposit_liquidity_with_deadline(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    amount_a: u64,
+    amount_b: u64,
+    expected_lp_token: u64,
+    deadline: Deadline,
+) -> Instruction {
+    build(
+        deposit_accounts(keys, user),
+        instruction::DepositLiquidityWithDeadline { amount_a, amount_b, expected_lp_token, deadline },
+    )
+}
+
+/// deposit_liquidity_native, wrapping lamports into the wSOL account of user
+pub fn deposit_liquidity_native(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    amount_a: u64,
+    amount_b: u64,
+    expected_lp_token: u64,
+    unwrap: bool,
+) -> Instruction {
+    build(
+        deposit_accounts(keys, user),
+        instruction::DepositLiquidityNative { amount_a, amount_b, expected_lp_token, unwrap },
+    )
+}
+
+/// deposit_single_sided of amount_in of token A if swap_a, token B otherwise
+pub fn deposit_single_sided(keys: &PoolKeys, user: &UserKeys, swap_a: bool, amount_in: u64, min_lp_out: u64) -> Instruction {
+    build(
+        accounts::DepositSingleSided {
+            pool: keys.pool,
+            amm: keys.amm,
+            observations: keys.observations,
+            depositor: user.owner,
+            mint_liquidity: keys.mint_liquidity,
+            mint_a: keys.mint_a,
+            mint_b: keys.mint_b,
+            pool_account_a: keys.pool_account_a,
+            pool_account_b: keys.pool_account_b,
+            treasury_mint_a: keys.treasury_mint_a,
+            treasury_mint_b: keys.treasury_mint_b,
+            creator_vault_a: keys.creator_vault_a,
+            creator_vault_b: keys.creator_vault_b,
+            depositor_account_liquidity: user.liquidity,
+            depositor_source: if swap_a { user.token_a } else { user.token_b },
+            payer: user.owner,
+            token_program: Token::id(),
+            token_program_a: keys.token_program_a,
+            token_program_b: keys.token_program_b,
+            associated_token_program: AssociatedToken::id(),
+            system_program: system_program::ID,
+        },
+        instruction::DepositSingleSided { swap_a, amount_in, min_lp_out },
+    )
+}
+
+fn withdraw_accounts(keys: &PoolKeys, user: &UserKeys) -> accounts::WithdrawLiquidity {
+    accounts::WithdrawLiquidity {
+        pool: keys.pool,
+        amm: keys.amm,
+        observations: keys.observations,
+        mint_liquidity: keys.mint_liquidity,
+        mint_a: keys.mint_a,
+        mint_b: keys.mint_b,
+        pool_account_a: keys.pool_account_a,
+        pool_account_b: keys.pool_account_b,
+        depositor_account_liquidity: user.liquidity,
+        depositor_account_a: user.token_a,
+        depositor_account_b: user.token_b,
+        payer: user.owner,
+        token_program: Token::id(),
+        token_program_a: keys.token_program_a,
+        token_program_b: keys.token_program_b,
+        associated_token_program: AssociatedToken::id(),
+        system_program: system_program::ID,
+    }
+}
+
+/// withdraw_liquidity
+pub fn withdraw_liquidity(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    amount: u64,
+    amount_expected_a: u64,
+    amount_expected_b: u64,
+) -> Instruction {
+    build(
+        withdraw_accounts(keys, user),
+        instruction::WithdrawLiquidity { amount, amount_expected_a, amount_expected_b },
+    )
+}
+
+/// withdraw_liquidity_with_deadline
+pub fn withdraw_liquidity_with_deadline(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    amount: u64,
+    amount_expected_a: u64,
+    amount_expected_b: u64,
+    deadline: Deadline,
+) -> Instruction {
+    build(
+        withdraw_accounts(keys, user),
+        instruction::WithdrawLiquidityWithDeadline { amount, amount_expected_a, amount_expected_b, deadline },
+    )
+}
+
+/// withdraw_liquidity_native, closing the wSOL account of user if unwrap
+pub fn withdraw_liquidity_native(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    amount: u64,
+    amount_expected_a: u64,
+    amount_expected_b: u64,
+    unwrap: bool,
+) -> Instruction {
+    build(
+        withdraw_accounts(keys, user),
+        instruction::WithdrawLiquidityNative { amount, amount_expected_a, amount_expected_b, unwrap },
+    )
+}
+
+/// withdraw_single_sided of lp_amount into token A if want_a, token B otherwise
+pub fn withdraw_single_sided(keys: &PoolKeys, user: &UserKeys, lp_amount: u64, want_a: bool, min_out: u64) -> Instruction {
+    build(
+        accounts::WithdrawSingleSided {
+            pool: keys.pool,
+            amm: keys.amm,
+            observations: keys.observations,
+            mint_liquidity: keys.mint_liquidity,
+            mint_a: keys.mint_a,
+            mint_b: keys.mint_b,
+            pool_account_a: keys.pool_account_a,
+            pool_account_b: keys.pool_account_b,
+            treasury_mint_a: keys.treasury_mint_a,
+            treasury_mint_b: keys.treasury_mint_b,
+            creator_vault_a: keys.creator_vault_a,
+            creator_vault_b: keys.creator_vault_b,
+            depositor_account_liquidity: user.liquidity,
+            depositor_destination: if want_a { user.token_a } else { user.token_b },
+            payer: user.owner,
+            token_program: Token::id(),
+            token_program_a: keys.token_program_a,
+            token_program_b: keys.token_program_b,
+            associated_token_program: AssociatedToken::id(),
+            system_program: system_program::ID,
+        },
+        instruction::WithdrawSingleSided { lp_amount, want_a, min_out },
+    )
+}
+
+fn swap_exact_in_accounts(keys: &PoolKeys, user: &UserKeys) -> accounts::SimpleSwapExactIn {
+    accounts::SimpleSwapExactIn {
+        pool: keys.pool,
+        amm: keys.amm,
+        observations: keys.observations,
+        mint_a: keys.mint_a,
+        mint_b: keys.mint_b,
+        pool_account_a: keys.pool_account_a,
+        pool_account_b: keys.pool_account_b,
+        trader_account_a: user.token_a,
+        trader_account_b: user.token_b,
+        treasury_mint_a: keys.treasury_mint_a,
+        treasury_mint_b: keys.treasury_mint_b,
+        creator_vault_a: keys.creator_vault_a,
+        creator_vault_b: keys.creator_vault_b,
+        payer: user.owner,
+        token_program_a: keys.token_program_a,
+        token_program_b: keys.token_program_b,
+        associated_token_program: AssociatedToken::id(),
+        system_program: system_program::ID,
+    }
+}
+
+/// simple_swap_exact_in, selling token A if swap_a
+pub fn simple_swap_exact_in(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    swap_a: bool,
+    input_amount: u64,
+    min_output_amount: u64,
+) -> Instruction {
+    build(
+        swap_exact_in_accounts(keys, user),
+        instruction::SimpleSwapExactIn { swap_a, input_amount, min_output_amount },
+    )
+}
+
+/// simple_swap_exact_in_with_deadline, selling token A if swap_a
+pub fn simple_swap_exact_in_with_deadline(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    swap_a: bool,
+    input_amount: u64,
+    min_output_amount: u64,
+    deadline: Deadline,
+) -> Instruction {
+    build(
+        swap_exact_in_accounts(keys, user),
+        instruction::SimpleSwapExactInWithDeadline { swap_a, input_amount, min_output_amount, deadline },
+    )
+}
+
+/// simple_swap_exact_in_native, selling token A if swap_a
+pub fn simple_swap_exact_in_native(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    swap_a: bool,
+    input_amount: u64,
+    min_output_amount: u64,
+    unwrap: bool,
+) -> Instruction {
+    build(
+        swap_exact_in_accounts(keys, user),
+        instruction::SimpleSwapExactInNative { swap_a, input_amount, min_output_amount, unwrap },
+    )
+}
+
+/// simple_swap_exact_out, buying token B if swap_a
+pub fn simple_swap_exact_out(
+    keys: &PoolKeys,
+    user: &UserKeys,
+    swap_a: bool,
+    output_amount: u64,
+    max_input_amount: u64,
+) -> Instruction {
+    build(
+        accounts::SimpleSwapExactOut {
+            pool: keys.pool,
+            amm: keys.amm,
+            observations: keys.observations,
+            mint_a: keys.mint_a,
+            mint_b: keys.mint_b,
+            pool_account_a: keys.pool_account_a,
+            pool_account_b: keys.pool_account_b,
+            trader_account_a: user.token_a,
+            trader_account_b: user.token_b,
+            treasury_mint_a: keys.treasury_mint_a,
+            treasury_mint_b: keys.treasury_mint_b,
+            creator_vault_a: keys.creator_vault_a,
+            creator_vault_b: keys.creator_vault_b,
+            payer: user.owner,
+            token_program_a: keys.token_program_a,
+            token_program_b: keys.token_program_b,
+            associated_token_program: AssociatedToken::id(),
+            system_program: system_program::ID,
+        },
+        instruction::SimpleSwapExactOut { swap_a, output_amount, max_input_amount },
+    )
+}
+
+/// simple_swap_routed through hops, in the order the input goes through them
+pub fn simple_swap_routed(
+    hops: &[PoolKeys],
+    payer: Pubkey,
+    trader_source: Pubkey,
+    trader_destination: Pubkey,
+    input_amount: u64,
+    min_output_amount: u64,
+) -> Instruction {
+    let remaining_accounts = hops
+        .iter()
+        .flat_map(|hop| {
+            [
+                AccountMeta::new(hop.pool, false),
+                AccountMeta::new_readonly(hop.mint_a, false),
+                AccountMeta::new_readonly(hop.mint_b, false),
+                AccountMeta::new(hop.pool_account_a, false),
+                AccountMeta::new(hop.pool_account_b, false),
+                AccountMeta::new(hop.treasury_mint_a, false),
+                AccountMeta::new(hop.treasury_mint_b, false),
+                AccountMeta::new(hop.creator_vault_a, false),
+                AccountMeta::new(hop.creator_vault_b, false),
+                AccountMeta::new(hop.observations, false),
+            ]
+        })
+        .collect();
+    build_with_remaining(
+        accounts::SimpleSwapRouted {
+            amm: pda::amm().0,
+            trader_source,
+            trader_destination,
+            payer,
+            token_program: Token::id(),
+            token_program_2022: Token2022::id(),
+        },
+        instruction::SimpleSwapRouted { input_amount, min_output_amount },
+        remaining_accounts,
+    )
+}
+
+/// simple_flash_swap of output_amount, buying token B if swap_a, the
+/// receiver program being called back with data and remaining_accounts
+#[allow(clippy::too_many_arguments)]
+pub fn simple_flash_swap(
+    keys: &PoolKeys,
+    payer: Pubkey,
+    trader_destination: Pubkey,
+    receiver_program: Pubkey,
+    swap_a: bool,
+    output_amount: u64,
+    data: Vec<u8>,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    build_with_remaining(
+        accounts::SimpleFlashSwap {
+            pool: keys.pool,
+            amm: keys.amm,
+            observations: keys.observations,
+            mint_a: keys.mint_a,
+            mint_b: keys.mint_b,
+            pool_account_a: keys.pool_account_a,
+            pool_account_b: keys.pool_account_b,
+            treasury_mint_a: keys.treasury_mint_a,
+            treasury_mint_b: keys.treasury_mint_b,
+            creator_vault_a: keys.creator_vault_a,
+            creator_vault_b: keys.creator_vault_b,
+            trader_destination,
+            receiver_program,
+            payer,
+            token_program_a: keys.token_program_a,
+            token_program_b: keys.token_program_b,
+        },
+        instruction::SimpleFlashSwap { swap_a, output_amount, data },
+        remaining_accounts,
+    )
+}
+
+/// admin_gets_treasury, a side without destination must have nothing to send
+pub fn admin_gets_treasury(
+    keys: &PoolKeys,
+    admin: Pubkey,
+    destination_a: Option<Pubkey>,
+    destination_b: Option<Pubkey>,
+    amount_a: u64,
+    amount_b: u64,
+) -> Instruction {
+    build(
+        accounts::AdminGetsTreasury {
+            pool: keys.pool,
+            depositor_account_a: destination_a,
+            depositor_account_b: destination_b,
+            treasury_mint_a: keys.treasury_mint_a,
+            treasury_mint_b: keys.treasury_mint_b,
+            mint_a: keys.mint_a,
+            mint_b: keys.mint_b,
+            admin,
+            token_program_a: keys.token_program_a,
+            token_program_b: keys.token_program_b,
+        },
+        instruction::AdminGetsTreasury { amount_a, amount_b },
+    )
+}
+
+/// creator_collect_fees, a side without destination must have nothing to send
+pub fn creator_collect_fees(
+    keys: &PoolKeys,
+    destination_a: Option<Pubkey>,
+    destination_b: Option<Pubkey>,
+    amount_a: u64,
+    amount_b: u64,
+) -> Instruction {
+    build(
+        accounts::CreatorCollectFees {
+            pool: keys.pool,
+            depositor_account_a: destination_a,
+            depositor_account_b: destination_b,
+            creator_vault_a: keys.creator_vault_a,
+            creator_vault_b: keys.creator_vault_b,
+            mint_a: keys.mint_a,
+            mint_b: keys.mint_b,
+            creator: keys.creator,
+            token_program_a: keys.token_program_a,
+            token_program_b: keys.token_program_b,
+        },
+        instruction::CreatorCollectFees { amount_a, amount_b },
+    )
+}
+
+/// flash_borrow of amount of token A if borrow_a, flash_repay must follow
+/// in the same transaction
+pub fn flash_borrow(keys: &PoolKeys, borrower: Pubkey, borrower_account: Pubkey, borrow_a: bool, amount: u64) -> Instruction {
+    build(
+        accounts::FlashBorrow {
+            pool: keys.pool,
+            amm: keys.amm,
+            mint_a: keys.mint_a,
+            mint_b: keys.mint_b,
+            pool_account_a: keys.pool_account_a,
+            pool_account_b: keys.pool_account_b,
+            borrower_account,
+            borrower,
+            instructions: sysvar::instructions::ID,
+            token_program_a: keys.token_program_a,
+            token_program_b: keys.token_program_b,
+        },
+        instruction::FlashBorrow { borrow_a, amount },
+    )
+}
+
+/// flash_repay of the pending loan with its fees
+pub fn flash_repay(keys: &PoolKeys, borrower: Pubkey, repayer_account: Pubkey) -> Instruction {
+    build(
+        accounts::FlashRepay {
+            pool: keys.pool,
+            mint_a: keys.mint_a,
+            mint_b: keys.mint_b,
+            pool_account_a: keys.pool_account_a,
+            pool_account_b: keys.pool_account_b,
+            treasury_mint_a: keys.treasury_mint_a,
+            treasury_mint_b: keys.treasury_mint_b,
+            creator_vault_a: keys.creator_vault_a,
+            creator_vault_b: keys.creator_vault_b,
+            repayer_account,
+            borrower,
+            token_program_a: keys.token_program_a,
+            token_program_b: keys.token_program_b,
+        },
+        instruction::FlashRepay {},
+    )
+}
+
+/// set_pool_fees
+pub fn set_pool_fees(keys: &PoolKeys, admin: Pubkey, lp_fee: u16, protocol_fee: u16) -> Instruction {
+    build(
+        accounts::SetPoolFees { pool: keys.pool, amm: keys.amm, admin },
+        instruction::SetPoolFees { lp_fee, protocol_fee },
+    )
+}
+
+/// set_pool_dynamic_fee
+pub fn set_pool_dynamic_fee(keys: &PoolKeys, admin: Pubkey, dynamic_fee: bool, max_dynamic_lp_fee: u16) -> Instruction {
+    build(
+        accounts::SetPoolDynamicFee { pool: keys.pool, amm: keys.amm, admin },
+        instruction::SetPoolDynamicFee { dynamic_fee, max_dynamic_lp_fee },
+    )
+}
+
+/// update_lp_metadata_uri
+pub fn update_lp_metadata_uri(keys: &PoolKeys, admin: Pubkey, uri: String) -> Instruction {
+    build(
+        accounts::UpdateLpMetadataUri {
+            pool: keys.pool,
+            mint_liquidity: keys.mint_liquidity,
+            lp_metadata: keys.lp_metadata,
+            admin,
+            token_metadata_program: Metadata::id(),
+        },
+        instruction::UpdateLpMetadataUri { uri },
+    )
+}
+
+/// set_pool_paused_flags
+pub fn set_pool_paused_flags(keys: &PoolKeys, admin: Pubkey, paused_flags: u8) -> Instruction {
+    build(
+        accounts::SetPoolPausedFlags { pool: keys.pool, admin },
+        instruction::SetPoolPausedFlags { paused_flags },
+    )
+}
+
+/// propose_admin
+pub fn propose_admin(keys: &PoolKeys, admin: Pubkey, new_admin: Pubkey) -> Instruction {
+    build(
+        accounts::ProposeAdmin { pool: keys.pool, admin },
+        instruction::ProposeAdmin { new_admin },
+    )
+}
+
+/// accept_admin
+pub fn accept_admin(keys: &PoolKeys, pending_admin: Pubkey) -> Instruction {
+    build(
+        accounts::AcceptAdmin { pool: keys.pool, pending_admin },
+        instruction::AcceptAdmin {},
+    )
+}
+
+/// cancel_admin_proposal
+pub fn cancel_admin_proposal(keys: &PoolKeys, admin: Pubkey) -> Instruction {
+    build(
+        accounts::CancelAdminProposal { pool: keys.pool, admin },
+        instruction::CancelAdminProposal {},
+    )
+}
+
+/// initialize
+pub fn initialize() -> Instruction {
+    build(accounts::Initialize {}, instruction::Initialize {})
+}
+
+/// migrate_pool of a pool created in the legacy layout, keys being derived
+/// from its admin, creator and LP fee
+pub fn migrate_pool(keys: &PoolKeys, payer: Pubkey) -> Instruction {
+    build(
+        accounts::MigratePool {
+            pool: keys.pool,
+            amm: keys.amm,
+            observations: keys.observations,
+            mint_a: keys.mint_a,
+            mint_b: keys.mint_b,
+            creator: keys.creator,
+            creator_vault_a: keys.creator_vault_a,
+            creator_vault_b: keys.creator_vault_b,
+            payer,
+            token_program_a: keys.token_program_a,
+            token_program_b: keys.token_program_b,
+            system_program: system_program::ID,
+        },
+        instruction::MigratePool {},
+    )
+}
@@ -0,0 +1,137 @@
+//! Off-chain helpers for the ftrx_swap program: PDA derivation, account
+//! deserialization, instruction builders and quotes sharing the on-chain math
+
+use anchor_lang::{prelude::Pubkey, AccountDeserialize, Id, Result};
+use anchor_spl::{associated_token::get_associated_token_address_with_program_id, token::Token};
+
+pub use ftrx_swap::{
+    constants, errors::FTRXSwapError,
+    state::{Deadline, FeeTier, PoolObservations, Position, SimpleAmm, SimplePool, TickArray},
+    ID,
+};
+
+pub mod instructions;
+pub mod pda;
+pub mod quote;
+
+/// SimplePool from the data of its account, discriminator included
+pub fn deserialize_pool(mut data: &[u8]) -> Result<SimplePool> {
+    SimplePool::try_deserialize(&mut data)
+}
+
+/// SimpleAmm from the data of its account, discriminator included
+pub fn deserialize_amm(mut data: &[u8]) -> Result<SimpleAmm> {
+    SimpleAmm::try_deserialize(&mut data)
+}
+
+/// Addresses of the accounts of a pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolKeys {
+    pub pool: Pubkey,
+    pub amm: Pubkey,
+    pub fee_tier: Pubkey,
+    pub observations: Pubkey,
+    /// Admin the pool was created with, its addresses stay derived from it
+    pub admin_seed: Pubkey,
+    pub creator: Pubkey,
+    pub lp_fee_seed: u16,
+    pub mint_liquidity: Pubkey,
+    pub lp_metadata: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub pool_account_a: Pubkey,
+    pub pool_account_b: Pubkey,
+    pub treasury_mint_a: Pubkey,
+    pub treasury_mint_b: Pubkey,
+    pub creator_vault_a: Pubkey,
+    pub creator_vault_b: Pubkey,
+    /// Token programs of mints A and B, legacy or Token-2022
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+}
+
+impl PoolKeys {
+    /// Keys of the pool created by creator, admin being the AMM admin and
+    /// mint_a sorting before mint_b
+    pub fn derive(
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        admin: Pubkey,
+        creator: Pubkey,
+        lp_fee: u16,
+        token_program_a: Pubkey,
+        token_program_b: Pubkey,
+    ) -> Self {
+        let pool = pda::pool(&mint_a, &mint_b, &admin, lp_fee).0;
+        let mint_liquidity = pda::mint_liquidity(&mint_a, &mint_b, &admin, lp_fee).0;
+        PoolKeys {
+            pool,
+            amm: pda::amm().0,
+            fee_tier: pda::fee_tier(lp_fee).0,
+            observations: pda::observations(&pool).0,
+            admin_seed: admin,
+            creator,
+            lp_fee_seed: lp_fee,
+            mint_liquidity,
+            lp_metadata: pda::metadata(&mint_liquidity).0,
+            mint_a,
+            mint_b,
+            pool_account_a: pda::pool_vault(&mint_a, &pool).0,
+            pool_account_b: pda::pool_vault(&mint_b, &pool).0,
+            treasury_mint_a: pda::treasury(&mint_a, &pool, &admin).0,
+            treasury_mint_b: pda::treasury(&mint_b, &pool, &admin).0,
+            creator_vault_a: pda::creator_vault(&mint_a, &pool, &creator).0,
+            creator_vault_b: pda::creator_vault(&mint_b, &pool, &creator).0,
+            token_program_a,
+            token_program_b,
+        }
+    }
+
+    /// Keys of a deployed pool, the token programs being the owners of its
+    /// mints
+    pub fn from_pool(pool: Pubkey, state: &SimplePool, token_program_a: Pubkey, token_program_b: Pubkey) -> Self {
+        let mint_liquidity = pda::mint_liquidity(&state.mint_a, &state.mint_b, &state.admin_seed, state.lp_fee_seed).0;
+        PoolKeys {
+            pool,
+            amm: state.amm,
+            fee_tier: pda::fee_tier(state.lp_fee_seed).0,
+            observations: pda::observations(&pool).0,
+            admin_seed: state.admin_seed,
+            creator: state.creator,
+            lp_fee_seed: state.lp_fee_seed,
+            mint_liquidity,
+            lp_metadata: pda::metadata(&mint_liquidity).0,
+            mint_a: state.mint_a,
+            mint_b: state.mint_b,
+            pool_account_a: state.vault_mint_a,
+            pool_account_b: state.vault_mint_b,
+            treasury_mint_a: state.treasury_mint_a,
+            treasury_mint_b: state.treasury_mint_b,
+            creator_vault_a: state.creator_vault_a,
+            creator_vault_b: state.creator_vault_b,
+            token_program_a,
+            token_program_b,
+        }
+    }
+}
+
+/// Token accounts a user trades and provides liquidity with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserKeys {
+    pub owner: Pubkey,
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub liquidity: Pubkey,
+}
+
+impl UserKeys {
+    /// Associated token accounts of owner for the mints of a pool
+    pub fn associated(owner: Pubkey, keys: &PoolKeys) -> Self {
+        UserKeys {
+            owner,
+            token_a: get_associated_token_address_with_program_id(&owner, &keys.mint_a, &keys.token_program_a),
+            token_b: get_associated_token_address_with_program_id(&owner, &keys.mint_b, &keys.token_program_b),
+            liquidity: get_associated_token_address_with_program_id(&owner, &keys.mint_liquidity, &Token::id()),
+        }
+    }
+}
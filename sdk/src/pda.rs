@@ -0,0 +1,102 @@
+//! Program derived addresses of the ftrx_swap accounts, each returned with
+//! its canonical bump
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::bpf_loader_upgradeable,
+    Id,
+};
+use anchor_spl::metadata::Metadata;
+use ftrx_swap::constants::{
+    AMM_SEED, CREATOR_SEED, FEE_TIER_SEED, LIQUIDITY_SEED, METADATA_SEED, OBSERVATION_SEED, POSITION_SEED,
+    TICK_ARRAY_SEED, TREASURY_SEED,
+};
+
+/// Global AMM config, [AMM_SEED]
+pub fn amm() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AMM_SEED.as_bytes()], &ftrx_swap::ID)
+}
+
+/// Program data account of the upgradeable program, checked by create_amm
+pub fn program_data() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ftrx_swap::ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+/// Fee tier of an LP fee, [FEE_TIER_SEED, lp_fee LE]
+pub fn fee_tier(lp_fee: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_TIER_SEED.as_bytes(), &lp_fee.to_le_bytes()], &ftrx_swap::ID)
+}
+
+/// Pool, [mint_a, mint_b, admin, lp_fee LE], mint_a sorting before mint_b
+/// and admin being the AMM admin when the pool was created
+pub fn pool(mint_a: &Pubkey, mint_b: &Pubkey, admin: &Pubkey, lp_fee: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[mint_a.as_ref(), mint_b.as_ref(), admin.as_ref(), &lp_fee.to_le_bytes()],
+        &ftrx_swap::ID,
+    )
+}
+
+/// LP mint, [mint_a, mint_b, admin, lp_fee LE, LIQUIDITY_SEED], one per pool
+pub fn mint_liquidity(mint_a: &Pubkey, mint_b: &Pubkey, admin: &Pubkey, lp_fee: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[mint_a.as_ref(), mint_b.as_ref(), admin.as_ref(), &lp_fee.to_le_bytes(), LIQUIDITY_SEED.as_bytes()],
+        &ftrx_swap::ID,
+    )
+}
+
+/// Metaplex metadata of a mint, the LP mint or the mints of a pool
+pub fn metadata(mint: &Pubkey) -> (Pubkey, u8) {
+    let metadata_program = Metadata::id();
+    Pubkey::find_program_address(
+        &[METADATA_SEED.as_bytes(), metadata_program.as_ref(), mint.as_ref()],
+        &metadata_program,
+    )
+}
+
+/// Reserve vault of a mint, [mint, pool]
+pub fn pool_vault(mint: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[mint.as_ref(), pool.as_ref()], &ftrx_swap::ID)
+}
+
+/// Treasury of a mint, [mint, pool, TREASURY_SEED, admin]
+pub fn treasury(mint: &Pubkey, pool: &Pubkey, admin: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[mint.as_ref(), pool.as_ref(), TREASURY_SEED.as_bytes(), admin.as_ref()],
+        &ftrx_swap::ID,
+    )
+}
+
+/// Creator fee vault of a mint, [mint, pool, CREATOR_SEED, creator]
+pub fn creator_vault(mint: &Pubkey, pool: &Pubkey, creator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[mint.as_ref(), pool.as_ref(), CREATOR_SEED.as_bytes(), creator.as_ref()],
+        &ftrx_swap::ID,
+    )
+}
+
+/// Price observations of a pool, [OBSERVATION_SEED, pool]
+pub fn observations(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OBSERVATION_SEED.as_bytes(), pool.as_ref()], &ftrx_swap::ID)
+}
+
+/// Tick array of a concentrated pool, [TICK_ARRAY_SEED, pool, start_tick_index LE]
+pub fn tick_array(pool: &Pubkey, start_tick_index: i32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[TICK_ARRAY_SEED.as_bytes(), pool.as_ref(), &start_tick_index.to_le_bytes()],
+        &ftrx_swap::ID,
+    )
+}
+
+/// Concentrated position, [POSITION_SEED, pool, owner, tick_lower LE, tick_upper LE]
+pub fn position(pool: &Pubkey, owner: &Pubkey, tick_lower: i32, tick_upper: i32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            POSITION_SEED.as_bytes(),
+            pool.as_ref(),
+            owner.as_ref(),
+            &tick_lower.to_le_bytes(),
+            &tick_upper.to_le_bytes(),
+        ],
+        &ftrx_swap::ID,
+    )
+}
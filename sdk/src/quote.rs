@@ -0,0 +1,291 @@
+//! Quotes computed with the math of the program itself, so that they match
+//! the on-chain I64F64 rounding bit for bit
+//!
+//! Amounts are the ones credited to the vaults, Token-2022 transfer fees
+//! are left to the caller
+
+use anchor_lang::{err, Result};
+use ftrx_swap::{
+    constants::{CURVE_CONCENTRATED, CURVE_CONSTANT_PRODUCT, CURVE_STABLE, CURVE_WEIGHTED, MINIMUM_LIQUIDITY},
+    errors::FTRXSwapError,
+    math::{self, SwapAmounts},
+    stable_math,
+    state::SimplePool,
+    weighted_math,
+};
+
+/// Tokens moved by a deposit and the LP tokens it mints
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DepositQuote {
+    /// Amounts taken once matched to the price of the reserves
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub liquidity: u64,
+}
+
+/// Tokens paid out for burning LP tokens
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WithdrawQuote {
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+/// simple_swap_exact_in of input, selling token A if swap_a, with the LP
+/// fee charged at unix timestamp in dynamic fee mode
+pub fn swap_exact_in(
+    pool: &SimplePool,
+    reserve_a: u64,
+    reserve_b: u64,
+    swap_a: bool,
+    input: u64,
+    timestamp: i64,
+) -> Result<SwapAmounts> {
+    let mut pool = pool.clone();
+    pool.decay_volatility_to(timestamp);
+    let (reserve_in, reserve_out) = if swap_a { (reserve_a, reserve_b) } else { (reserve_b, reserve_a) };
+    pool.swap_exact_in_with_fee(swap_a, reserve_in, reserve_out, input, pool.dynamic_lp_fee())
+}
+
+/// simple_swap_exact_out of output, buying token B if swap_a, with the LP
+/// fee charged at unix timestamp in dynamic fee mode, only constant product
+/// pools support it
+pub fn swap_exact_out(
+    pool: &SimplePool,
+    reserve_a: u64,
+    reserve_b: u64,
+    swap_a: bool,
+    output: u64,
+    timestamp: i64,
+) -> Result<SwapAmounts> {
+    if pool.curve != CURVE_CONSTANT_PRODUCT {
+        return err!(FTRXSwapError::UnsupportedCurve);
+    }
+    let mut pool = pool.clone();
+    pool.decay_volatility_to(timestamp);
+    let (reserve_in, reserve_out) = if swap_a { (reserve_a, reserve_b) } else { (reserve_b, reserve_a) };
+    math::swap_exact_out(reserve_in, reserve_out, output, pool.dynamic_lp_fee(), pool.protocol_fee)
+}
+
+/// deposit_liquidity of up to amount_a and amount_b, lp_supply being the
+/// supply of the LP mint
+pub fn deposit(
+    pool: &SimplePool,
+    reserve_a: u64,
+    reserve_b: u64,
+    lp_supply: u64,
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<DepositQuote> {
+    if pool.curve == CURVE_CONCENTRATED {
+        return err!(FTRXSwapError::UnsupportedCurve);
+    }
+
+    let pool_creation = reserve_a == 0 && reserve_b == 0;
+    let stable = pool.curve == CURVE_STABLE;
+
+    let (amount_a, amount_b) = if !pool_creation && !stable {
+        math::balanced_deposit_amounts(reserve_a, reserve_b, amount_a, amount_b)?
+    } else {
+        (amount_a, amount_b)
+    };
+    let liquidity = deposit_liquidity(pool, reserve_a, reserve_b, lp_supply, amount_a, amount_b)?;
+
+    Ok(DepositQuote { amount_a, amount_b, liquidity })
+}
+
+/// LP tokens deposit_liquidity mints once the vaults received amount_a and
+/// amount_b, as they are and without matching them to the reserves
+pub fn deposit_liquidity(
+    pool: &SimplePool,
+    reserve_a: u64,
+    reserve_b: u64,
+    lp_supply: u64,
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<u64> {
+    if pool.curve == CURVE_CONCENTRATED {
+        return err!(FTRXSwapError::UnsupportedCurve);
+    }
+
+    let pool_creation = reserve_a == 0 && reserve_b == 0;
+    let stable = pool.curve == CURVE_STABLE;
+
+    let supply = lp_supply
+        .checked_add(MINIMUM_LIQUIDITY)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    let mut liquidity = if stable {
+        stable_math::deposit_liquidity(pool.amp, reserve_a, reserve_b, amount_a, amount_b, supply, pool.lp_fee)?
+    } else if pool.curve == CURVE_WEIGHTED && pool_creation {
+        weighted_math::initial_liquidity(pool.weight_a, pool.weight_b, amount_a, amount_b)?
+    } else {
+        math::deposit_liquidity(reserve_a, reserve_b, amount_a, amount_b, supply)?
+    };
+
+    // The first deposit locks MINIMUM_LIQUIDITY
+    if pool_creation {
+        if liquidity < MINIMUM_LIQUIDITY {
+            return err!(FTRXSwapError::DepositTooSmall);
+        }
+        liquidity -= MINIMUM_LIQUIDITY;
+    }
+
+    Ok(liquidity)
+}
+
+/// withdraw_liquidity of lp_amount, lp_supply being the supply of the LP
+/// mint
+pub fn withdraw(reserve_a: u64, reserve_b: u64, lp_supply: u64, lp_amount: u64) -> Result<WithdrawQuote> {
+    let supply = lp_supply
+        .checked_add(MINIMUM_LIQUIDITY)
+        .ok_or(FTRXSwapError::MathOverflow)?;
+    Ok(WithdrawQuote {
+        amount_a: math::withdraw_amount(lp_amount, reserve_a, supply)?,
+        amount_b: math::withdraw_amount(lp_amount, reserve_b, supply)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ftrx_swap::constants::{VOLATILITY_CEILING, VOLATILITY_DECAY_PERIOD, WEIGHT_MULTIPLIER};
+
+    const RESERVE_A: u64 = 500_000_000;
+    const RESERVE_B: u64 = 200_000_000;
+
+    fn pool(curve: u8) -> SimplePool {
+        SimplePool {
+            curve,
+            lp_fee: 30,
+            protocol_fee: 5,
+            amp: 100,
+            weight_a: WEIGHT_MULTIPLIER * 4 / 5,
+            weight_b: WEIGHT_MULTIPLIER / 5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn swap_exact_in_follows_the_curve_of_the_pool() {
+        for swap_a in [true, false] {
+            let (reserve_in, reserve_out) = if swap_a { (RESERVE_A, RESERVE_B) } else { (RESERVE_B, RESERVE_A) };
+            let (weight_in, weight_out) = if swap_a { (4, 1) } else { (1, 4) };
+            let input = 10_000_000;
+
+            let quote = swap_exact_in(&pool(CURVE_CONSTANT_PRODUCT), RESERVE_A, RESERVE_B, swap_a, input, 0).unwrap();
+            assert_eq!(quote, math::swap_exact_in(reserve_in, reserve_out, input, 30, 5).unwrap());
+
+            let quote = swap_exact_in(&pool(CURVE_STABLE), RESERVE_A, RESERVE_B, swap_a, input, 0).unwrap();
+            assert_eq!(quote, stable_math::swap_exact_in(100, reserve_in, reserve_out, input, 30, 5).unwrap());
+
+            let quote = swap_exact_in(&pool(CURVE_WEIGHTED), RESERVE_A, RESERVE_B, swap_a, input, 0).unwrap();
+            let expected = weighted_math::swap_exact_in(
+                weight_in * WEIGHT_MULTIPLIER / 5,
+                weight_out * WEIGHT_MULTIPLIER / 5,
+                reserve_in,
+                reserve_out,
+                input,
+                30,
+                5,
+            )
+            .unwrap();
+            assert_eq!(quote, expected);
+        }
+        assert!(swap_exact_in(&pool(CURVE_CONCENTRATED), RESERVE_A, RESERVE_B, true, 1_000, 0).is_err());
+    }
+
+    #[test]
+    fn swaps_charge_the_decayed_dynamic_fee() {
+        let mut dynamic_pool = pool(CURVE_CONSTANT_PRODUCT);
+        dynamic_pool.dynamic_fee = true;
+        dynamic_pool.max_dynamic_lp_fee = 230;
+        dynamic_pool.volatility_accumulator = VOLATILITY_CEILING;
+        dynamic_pool.volatility_last_timestamp = 1_000;
+
+        // Right after the move the whole dynamic range is charged, one decay
+        // period later only what the halved accumulator still covers
+        let input = 10_000_000;
+        let quote = swap_exact_in(&dynamic_pool, RESERVE_A, RESERVE_B, true, input, 1_000).unwrap();
+        assert_eq!(quote, math::swap_exact_in(RESERVE_A, RESERVE_B, input, 230, 5).unwrap());
+
+        let later = 1_000 + VOLATILITY_DECAY_PERIOD;
+        let mut decayed_pool = dynamic_pool.clone();
+        decayed_pool.decay_volatility_to(later);
+        let decayed_fee = decayed_pool.dynamic_lp_fee();
+        assert_eq!(decayed_fee, 130);
+        let quote = swap_exact_in(&dynamic_pool, RESERVE_A, RESERVE_B, true, input, later).unwrap();
+        assert_eq!(quote, math::swap_exact_in(RESERVE_A, RESERVE_B, input, decayed_fee, 5).unwrap());
+
+        let output = 1_000_000;
+        let quote = swap_exact_out(&dynamic_pool, RESERVE_A, RESERVE_B, true, output, 1_000).unwrap();
+        assert_eq!(quote, math::swap_exact_out(RESERVE_A, RESERVE_B, output, 230, 5).unwrap());
+        assert!(quote.lp_fee > math::swap_exact_out(RESERVE_A, RESERVE_B, output, 30, 5).unwrap().lp_fee);
+    }
+
+    #[test]
+    fn swap_exact_out_is_only_quoted_on_constant_product_pools() {
+        let quote = swap_exact_out(&pool(CURVE_CONSTANT_PRODUCT), RESERVE_A, RESERVE_B, false, 1_000_000, 0).unwrap();
+        assert_eq!(quote, math::swap_exact_out(RESERVE_B, RESERVE_A, 1_000_000, 30, 5).unwrap());
+        for curve in [CURVE_STABLE, CURVE_WEIGHTED, CURVE_CONCENTRATED] {
+            assert!(swap_exact_out(&pool(curve), RESERVE_A, RESERVE_B, true, 1_000_000, 0).is_err());
+        }
+    }
+
+    #[test]
+    fn deposit_follows_the_curve_of_the_pool() {
+        let lp_supply = 1_000_000_000;
+        let supply = lp_supply + MINIMUM_LIQUIDITY;
+
+        // Constant product and weighted pools take the price of the reserves
+        for curve in [CURVE_CONSTANT_PRODUCT, CURVE_WEIGHTED] {
+            let quote = deposit(&pool(curve), RESERVE_A, RESERVE_B, lp_supply, 50_000_000, 50_000_000).unwrap();
+            let (amount_a, amount_b) = math::balanced_deposit_amounts(RESERVE_A, RESERVE_B, 50_000_000, 50_000_000).unwrap();
+            let liquidity = math::deposit_liquidity(RESERVE_A, RESERVE_B, amount_a, amount_b, supply).unwrap();
+            assert_eq!(quote, DepositQuote { amount_a, amount_b, liquidity });
+        }
+
+        // Stable pools take any ratio and charge the imbalance
+        let quote = deposit(&pool(CURVE_STABLE), RESERVE_A, RESERVE_B, lp_supply, 50_000_000, 0).unwrap();
+        let liquidity = stable_math::deposit_liquidity(100, RESERVE_A, RESERVE_B, 50_000_000, 0, supply, 30).unwrap();
+        assert_eq!(quote, DepositQuote { amount_a: 50_000_000, amount_b: 0, liquidity });
+
+        assert!(deposit(&pool(CURVE_CONCENTRATED), RESERVE_A, RESERVE_B, lp_supply, 1_000, 1_000).is_err());
+    }
+
+    #[test]
+    fn first_deposit_locks_the_minimum_liquidity() {
+        let quote = deposit(&pool(CURVE_CONSTANT_PRODUCT), 0, 0, 0, RESERVE_A, RESERVE_B).unwrap();
+        let liquidity = math::deposit_liquidity(0, 0, RESERVE_A, RESERVE_B, MINIMUM_LIQUIDITY).unwrap();
+        assert_eq!(quote.liquidity, liquidity - MINIMUM_LIQUIDITY);
+
+        let quote = deposit(&pool(CURVE_WEIGHTED), 0, 0, 0, RESERVE_A, RESERVE_B).unwrap();
+        let liquidity = weighted_math::initial_liquidity(WEIGHT_MULTIPLIER * 4 / 5, WEIGHT_MULTIPLIER / 5, RESERVE_A, RESERVE_B).unwrap();
+        assert_eq!(quote.liquidity, liquidity - MINIMUM_LIQUIDITY);
+
+        let quote = deposit(&pool(CURVE_STABLE), 0, 0, 0, RESERVE_A, RESERVE_B).unwrap();
+        let liquidity = stable_math::deposit_liquidity(100, 0, 0, RESERVE_A, RESERVE_B, MINIMUM_LIQUIDITY, 30).unwrap();
+        assert_eq!(quote.liquidity, liquidity - MINIMUM_LIQUIDITY);
+
+        assert!(deposit(&pool(CURVE_CONSTANT_PRODUCT), 0, 0, 0, 10, 10).is_err());
+    }
+
+    #[test]
+    fn withdraw_pays_the_share_of_the_reserves() {
+        let (lp_supply, lp_amount) = (1_000_000_000, 250_000_000);
+        let supply = lp_supply + MINIMUM_LIQUIDITY;
+        let quote = withdraw(RESERVE_A, RESERVE_B, lp_supply, lp_amount).unwrap();
+        assert_eq!(
+            quote,
+            WithdrawQuote {
+                amount_a: math::withdraw_amount(lp_amount, RESERVE_A, supply).unwrap(),
+                amount_b: math::withdraw_amount(lp_amount, RESERVE_B, supply).unwrap(),
+            }
+        );
+        assert!(quote.amount_a <= RESERVE_A / 4 && quote.amount_b <= RESERVE_B / 4);
+    }
+
+    #[test]
+    fn supplies_past_the_lp_mint_range_are_rejected() {
+        assert!(withdraw(RESERVE_A, RESERVE_B, u64::MAX, 1).is_err());
+        assert!(deposit(&pool(CURVE_CONSTANT_PRODUCT), RESERVE_A, RESERVE_B, u64::MAX, 1_000, 1_000).is_err());
+    }
+}
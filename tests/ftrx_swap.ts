@@ -2,7 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 
 import { PublicKey } from "@solana/web3.js";
 import { FtrxSwap } from "../target/types/ftrx_swap";
-import { TestValues, createValues, expectRevert,mintingTokens } from "./utils";
+import { TestValues, createValues, expectRevert,mintingTokens, TOKEN_METADATA_PROGRAM_ID, metadataKey, pda, poolAccounts } from "./utils";
 import { expect } from "chai";
 import { Program, BN, web3  } from "@coral-xyz/anchor";
 import { superUserKey } from "./testKeys";
@@ -15,9 +15,17 @@ import {
   createSyncNativeInstruction,
   createCloseAccountInstruction,
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  getMintLen,
+  createInitializeMintInstruction,
+  createInitializeNonTransferableMintInstruction,
+  createInitializeMetadataPointerInstruction,
   NATIVE_MINT,
   getMint,
+  createMint,
+  mintTo,
 } from "@solana/spl-token";
 
 describe("ftrx_swap", () => {
@@ -28,6 +36,7 @@ describe("ftrx_swap", () => {
   const connection = program.provider.connection;
 
 
+
   async function get_onchain_logs(connection,tx1){
     
     const { lastValidBlockHeight, blockhash } =
@@ -78,6 +87,7 @@ describe("ftrx_swap", () => {
       values.mintAKeypair.publicKey.toBuffer(),
       values.mintBKeypair.publicKey.toBuffer(),
       superUserKey.pubKey.toBuffer(),
+      lpFeeBuffer,
       Buffer.from("liquidity"),
     ],
     program.programId
@@ -124,17 +134,82 @@ describe("ftrx_swap", () => {
     program.programId
   );
 
+  let [mintACreatorVaultKey] =
+  web3.PublicKey.findProgramAddressSync(
+    [
+      values.mintAKeypair.publicKey.toBuffer(),
+      poolKey.toBuffer(),
+      Buffer.from("creator"),
+      superUserKey.pubKey.toBuffer(),
+    ],
+    program.programId
+  );
+
+  let [mintBCreatorVaultKey] =
+  web3.PublicKey.findProgramAddressSync(
+    [
+      values.mintBKeypair.publicKey.toBuffer(),
+      poolKey.toBuffer(),
+      Buffer.from("creator"),
+      superUserKey.pubKey.toBuffer(),
+    ],
+    program.programId
+  );
+
+  let [observationsKey] =
+  web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("observation"),
+      poolKey.toBuffer(),
+    ],
+    program.programId
+  );
+
+  let [ammKey] =
+  web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("amm"),
+    ],
+    program.programId
+  );
+
+  let [programDataKey] =
+  web3.PublicKey.findProgramAddressSync(
+    [
+      program.programId.toBuffer(),
+    ],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
+  // Tier listing the LP fee of the pools
+  let [feeTierKey] =
+  web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("fee_tier"),
+      lpFeeBuffer,
+    ],
+    program.programId
+  );
+
   //Setting the account structure
   let accounts={
     pool: poolKey,
+    amm: ammKey,
+    feeTier: feeTierKey,
+    observations: observationsKey,
     admin: superUserKey.pubKey,
     mintLiquidity: lpTokenKey,
+    lpMetadata: metadataKey(lpTokenKey),
+    metadataA: metadataKey(values.mintAKeypair.publicKey),
+    metadataB: metadataKey(values.mintBKeypair.publicKey),
     mintA: values.mintAKeypair.publicKey,
     mintB: values.mintBKeypair.publicKey,
     poolAccountA: mintAVaultKey,
     poolAccountB: mintBVaultKey,
     treasuryMintA: mintATreasuryKey,
     treasuryMintB: mintBTreasuryKey,
+    creatorVaultA: mintACreatorVaultKey,
+    creatorVaultB: mintBCreatorVaultKey,
     payer:superUserKey.pubKey,
     depositorAccountLiquidity: values.liquidityAccount,
     depositorAccountA: values.holderAccountA,
@@ -142,8 +217,73 @@ describe("ftrx_swap", () => {
     traderAccountA: values.holderAccountA,
     traderAccountB: values.holderAccountB,
     depositor: superUserKey.pubKey,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    tokenProgramA: TOKEN_PROGRAM_ID,
+    tokenProgramB: TOKEN_PROGRAM_ID,
+    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
   }
 
+  // Pools are seeded by the AMM admin, another pool of the same pair needs its own fee tier
+  const createFeeTier = async (lpFee: number) => {
+    const feeBuffer = Buffer.alloc(2);
+    feeBuffer.writeUInt16LE(lpFee);
+    const feeTier = pda([Buffer.from("fee_tier"), feeBuffer], program.programId);
+    await program.methods
+      .createFeeTier(lpFee, 1, 2000)
+      .accounts({ amm: ammKey, feeTier, admin: superUserKey.pubKey, payer: superUserKey.pubKey })
+      .rpc();
+    return { feeBuffer, feeTier };
+  };
+
+  it("AMM Creation", async () => {
+
+    // LP fees between 1 and 2000, protocol fee up to 500
+    await program.methods
+      .createAmm(1,2000,500)
+      .accounts({
+        amm: ammKey,
+        admin: superUserKey.pubKey,
+        program: program.programId,
+        programData: programDataKey,
+        payer: superUserKey.pubKey,
+      })
+      .rpc();
+
+    const amm = await program.account.simpleAmm.fetch(ammKey);
+    expect(amm.admin.toBase58()).to.equal(superUserKey.pubKey.toBase58());
+    expect(amm.paused).to.equal(false);
+  });
+
+
+
+  it("Fee tiers list the LP fees pools are created with", async () => {
+
+    // Above the max LP fee of the AMM
+    const outOfBoundsFeeBuffer = Buffer.alloc(2);
+    outOfBoundsFeeBuffer.writeUInt16LE(2500);
+    const [outOfBoundsTierKey] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_tier"), outOfBoundsFeeBuffer],
+      program.programId
+    );
+    await expectRevert(
+      program.methods
+        .createFeeTier(2500, 1, 0)
+        .accounts({ amm: ammKey, feeTier: outOfBoundsTierKey, admin: superUserKey.pubKey, payer: superUserKey.pubKey })
+        .rpc()
+    );
+
+    await program.methods
+      .createFeeTier(new_pool_lp_fee_in_bp, 1, 2000)
+      .accounts({ amm: ammKey, feeTier: feeTierKey, admin: superUserKey.pubKey, payer: superUserKey.pubKey })
+      .rpc();
+
+    // Clients find the tiers, hence the canonical pools of a pair, among the program accounts
+    const tiers = await program.account.feeTier.all();
+    expect(tiers.map((tier) => tier.account.lpFee)).to.include(new_pool_lp_fee_in_bp);
+  });
+
+
+
   it("Pool Creation", async () => {
 
     //Minting 100 token A and token B to the superUser
@@ -154,6 +294,18 @@ describe("ftrx_swap", () => {
       mintBKeypair: values.mintBKeypair,
     });
     
+    // Only the AMM admin seeds pools
+    const stranger = web3.Keypair.generate().publicKey;
+    await expectRevert(
+      program.methods
+        .createPool(10, 0, 0, 0, 0, 0)
+        .accounts({
+          ...accounts,
+          ...poolAccounts({ programId: program.programId, mintA: accounts.mintA, mintB: accounts.mintB, admin: stranger, creator: superUserKey.pubKey, lpFeeBuffer }),
+        })
+        .rpc()
+    );
+
     // Creating the pool
     let pool_lp_fee=new BN(new_pool_lp_fee_in_bp)
     await program.methods
@@ -167,6 +319,40 @@ describe("ftrx_swap", () => {
 
 
 
+  it("LP mint carries token metadata", async () => {
+
+    // Both test mints have 6 decimals
+    const lpMint = await getMint(connection, lpTokenKey);
+    expect(lpMint.decimals).to.equal(6);
+
+    // The test mints have no metadata, the name shows their addresses and the URI starts empty
+    const metadataInfo = await connection.getAccountInfo(accounts.lpMetadata);
+    expect(metadataInfo.owner.toBase58()).to.equal(TOKEN_METADATA_PROGRAM_ID.toBase58());
+    const name = `FTRX-LP ${accounts.mintA.toBase58().slice(0, 4)}/${accounts.mintB.toBase58().slice(0, 4)}`;
+    expect(metadataInfo.data.includes(Buffer.from(name))).to.equal(true);
+
+    const uri = "https://example.com/ftrx-lp.json";
+    const stranger = web3.Keypair.generate();
+    await expectRevert(
+      program.methods
+        .updateLpMetadataUri(uri)
+        .accounts({ ...accounts, admin: stranger.publicKey })
+        .signers([stranger])
+        .rpc()
+    );
+
+    await program.methods
+      .updateLpMetadataUri(uri)
+      .accounts(accounts)
+      .rpc();
+    const updatedInfo = await connection.getAccountInfo(accounts.lpMetadata);
+    expect(updatedInfo.data.includes(Buffer.from(uri))).to.equal(true);
+    expect(updatedInfo.data.includes(Buffer.from(name))).to.equal(true);
+  });
+
+
+
+
   it("Creating uas ", async () => {
 
     const lptokenUTA = await getOrCreateAssociatedTokenAccount(
@@ -434,6 +620,946 @@ describe("ftrx_swap", () => {
 
 
 
+  it("Swap exact out from A to B", async () => {
+    const output = new BN(10 ** 5);
+
+    const traderTokenAccountA_before = await connection.getTokenAccountBalance(
+      accounts.depositorAccountA
+    );
+    const traderTokenAccountB_before = await connection.getTokenAccountBalance(
+      accounts.depositorAccountB
+    );
+
+    await program.methods
+      .simpleSwapExactOut(true, output, new BN(10 ** 6))
+      .accounts(accounts)
+      .rpc();
+
+    const traderTokenAccountA = await connection.getTokenAccountBalance(
+      accounts.depositorAccountA
+    );
+    const traderTokenAccountB = await connection.getTokenAccountBalance(
+      accounts.depositorAccountB
+    );
+
+    let impact_token_A=Number(traderTokenAccountA.value.amount)-Number(traderTokenAccountA_before.value.amount)
+    let impact_token_B=Number(traderTokenAccountB.value.amount)-Number(traderTokenAccountB_before.value.amount)
+    console.log("TOKEN A impact : ",impact_token_A)
+    console.log("TOKEN B impact : ",impact_token_B)
+    expect(impact_token_B).to.equal(output.toNumber())
+    expect(-impact_token_A).to.be.at.most(10 ** 6)
+
+    // The maximum input is below what the pool asks for
+    await expectRevert(
+      program.methods
+        .simpleSwapExactOut(true, output, new BN(1))
+        .accounts(accounts)
+        .rpc()
+    );
+  });
+
+
+
+  it("Routed swap through a single pool from B to A", async () => {
+    const input = new BN(10 ** 5);
+
+    const traderTokenAccountA_before = await connection.getTokenAccountBalance(
+      accounts.depositorAccountA
+    );
+
+    await program.methods
+      .simpleSwapRouted(input, new BN(100))
+      .accounts({
+        amm: accounts.amm,
+        traderSource: accounts.depositorAccountB,
+        traderDestination: accounts.depositorAccountA,
+        payer: accounts.payer,
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: accounts.pool, isSigner: false, isWritable: true },
+        { pubkey: accounts.mintA, isSigner: false, isWritable: false },
+        { pubkey: accounts.mintB, isSigner: false, isWritable: false },
+        { pubkey: accounts.poolAccountA, isSigner: false, isWritable: true },
+        { pubkey: accounts.poolAccountB, isSigner: false, isWritable: true },
+        { pubkey: accounts.treasuryMintA, isSigner: false, isWritable: true },
+        { pubkey: accounts.treasuryMintB, isSigner: false, isWritable: true },
+        { pubkey: accounts.creatorVaultA, isSigner: false, isWritable: true },
+        { pubkey: accounts.creatorVaultB, isSigner: false, isWritable: true },
+        { pubkey: accounts.observations, isSigner: false, isWritable: true },
+      ])
+      .rpc();
+
+    const traderTokenAccountA = await connection.getTokenAccountBalance(
+      accounts.depositorAccountA
+    );
+    let impact_token_A=Number(traderTokenAccountA.value.amount)-Number(traderTokenAccountA_before.value.amount)
+    console.log("TOKEN A impact : ",impact_token_A)
+    expect(impact_token_A).to.be.at.least(100)
+  });
+
+
+
+  it("Routed swap through two pools from A to C", async () => {
+
+    // Token C, listed against token B in a second pool
+    let mintCKeypair = web3.Keypair.generate();
+    while (new BN(mintCKeypair.publicKey.toBytes()).lt(new BN(accounts.mintB.toBytes()))) {
+      mintCKeypair = web3.Keypair.generate();
+    }
+    const mintC = await createMint(connection, superUser, superUser.publicKey, null, 6, mintCKeypair);
+    const traderAccountC = await getOrCreateAssociatedTokenAccount(connection, superUser, mintC, superUser.publicKey, true);
+    await mintTo(connection, superUser, mintC, traderAccountC.address, superUser, 100 * 10 ** 6);
+
+    const poolBC = {
+      ...accounts,
+      ...poolAccounts({ programId: program.programId, mintA: accounts.mintB, mintB: mintC, admin: superUserKey.pubKey, creator: superUserKey.pubKey, lpFeeBuffer }),
+      depositorAccountA: accounts.depositorAccountB,
+      depositorAccountB: traderAccountC.address,
+      traderAccountA: accounts.depositorAccountB,
+      traderAccountB: traderAccountC.address,
+    };
+    await program.methods
+      .createPool(10, 0, 0, 0, 0, 0)
+      .accounts(poolBC)
+      .rpc();
+    await program.methods
+      .depositLiquidity(new BN(10 ** 7), new BN(10 ** 7), new BN(0))
+      .accounts(poolBC)
+      .rpc();
+
+    const hopMetas = (hop: typeof accounts) => [
+      { pubkey: hop.pool, isSigner: false, isWritable: true },
+      { pubkey: hop.mintA, isSigner: false, isWritable: false },
+      { pubkey: hop.mintB, isSigner: false, isWritable: false },
+      { pubkey: hop.poolAccountA, isSigner: false, isWritable: true },
+      { pubkey: hop.poolAccountB, isSigner: false, isWritable: true },
+      { pubkey: hop.treasuryMintA, isSigner: false, isWritable: true },
+      { pubkey: hop.treasuryMintB, isSigner: false, isWritable: true },
+      { pubkey: hop.creatorVaultA, isSigner: false, isWritable: true },
+      { pubkey: hop.creatorVaultB, isSigner: false, isWritable: true },
+      { pubkey: hop.observations, isSigner: false, isWritable: true },
+    ];
+    const routeAccounts = {
+      amm: accounts.amm,
+      traderSource: accounts.depositorAccountA,
+      traderDestination: traderAccountC.address,
+      payer: accounts.payer,
+      tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+    };
+    const balance = async (account: PublicKey) =>
+      Number((await connection.getTokenAccountBalance(account)).value.amount);
+    const balances = async () => Promise.all([
+      accounts.poolAccountA, accounts.poolAccountB, accounts.treasuryMintA, accounts.creatorVaultA,
+      poolBC.poolAccountA, poolBC.poolAccountB, poolBC.treasuryMintA, poolBC.creatorVaultA,
+      traderAccountC.address,
+    ].map(balance));
+
+    const input = 10 ** 5;
+    const [vaultA, vaultB, treasuryA, creatorA, vaultBC_B, vaultBC_C, treasuryBC_B, creatorBC_B, traderC] = await balances();
+    await program.methods
+      .simpleSwapRouted(new BN(input), new BN(1))
+      .accounts(routeAccounts)
+      .remainingAccounts([...hopMetas(accounts), ...hopMetas(poolBC)])
+      .rpc();
+    const [vaultA2, vaultB2, treasuryA2, creatorA2, vaultBC_B2, vaultBC_C2, treasuryBC_B2, creatorBC_B2, traderC2] = await balances();
+
+    // Each pool keeps its cut of the token it receives, the protocol share leaving for the treasury and creator vault
+    const protocolFeeAB = treasuryA2 - treasuryA + creatorA2 - creatorA;
+    expect(protocolFeeAB).to.be.greaterThan(0);
+    expect(vaultA2 - vaultA + protocolFeeAB).to.equal(input);
+    const outputAB = vaultB - vaultB2;
+    const protocolFeeBC = treasuryBC_B2 - treasuryBC_B + creatorBC_B2 - creatorBC_B;
+    expect(protocolFeeBC).to.be.greaterThan(0);
+    expect(vaultBC_B2 - vaultBC_B + protocolFeeBC).to.equal(outputAB);
+
+    // The LP fee stays in the vaults, each hop pays less than a feeless constant product
+    const feelessAB = Math.floor(vaultB * (input - protocolFeeAB) / (vaultA + input - protocolFeeAB));
+    expect(outputAB).to.be.lessThan(feelessAB);
+    const outputBC = vaultBC_C - vaultBC_C2;
+    const feelessBC = Math.floor(vaultBC_C * (outputAB - protocolFeeBC) / (vaultBC_B + outputAB - protocolFeeBC));
+    expect(outputBC).to.be.lessThan(feelessBC);
+    expect(traderC2 - traderC).to.equal(outputBC);
+
+    // The minimum output bounds what the trader gets out of the last pool, and the
+    // same trade now gets less from both pools
+    await expectRevert(
+      program.methods
+        .simpleSwapRouted(new BN(input), new BN(outputBC))
+        .accounts(routeAccounts)
+        .remainingAccounts([...hopMetas(accounts), ...hopMetas(poolBC)])
+        .rpc()
+    );
+
+    // A pool can only be crossed once per route
+    await expectRevert(
+      program.methods
+        .simpleSwapRouted(new BN(input), new BN(0))
+        .accounts({ ...routeAccounts, traderDestination: accounts.depositorAccountA })
+        .remainingAccounts([...hopMetas(accounts), ...hopMetas(accounts)])
+        .rpc()
+    );
+
+    // Every hop must take the token the previous one pays out
+    await expectRevert(
+      program.methods
+        .simpleSwapRouted(new BN(input), new BN(0))
+        .accounts(routeAccounts)
+        .remainingAccounts([...hopMetas(poolBC), ...hopMetas(accounts)])
+        .rpc()
+    );
+  });
+
+
+
+  it("Admin updates the pool fees", async () => {
+
+    const feeAccounts = { pool: poolKey, amm: ammKey, admin: superUserKey.pubKey };
+    await program.methods
+      .setPoolFees(20, 2)
+      .accounts(feeAccounts)
+      .rpc();
+
+    // The pool keeps its address, only the fees change
+    const pool = await program.account.simplePool.fetch(poolKey);
+    expect(pool.lpFee).to.equal(20);
+    expect(pool.protocolFee).to.equal(2);
+    expect(pool.lpFeeSeed).to.equal(new_pool_lp_fee_in_bp);
+
+    // Above the bounds of the AMM config
+    await expectRevert(
+      program.methods
+        .setPoolFees(2001, 2)
+        .accounts(feeAccounts)
+        .rpc()
+    );
+    await expectRevert(
+      program.methods
+        .setPoolFees(new_pool_lp_fee_in_bp, 5000)
+        .accounts(feeAccounts)
+        .rpc()
+    );
+
+    // Back to the LP fee of its tier
+    await program.methods
+      .setPoolFees(new_pool_lp_fee_in_bp, 2)
+      .accounts(feeAccounts)
+      .rpc();
+  });
+
+
+
+  it("Admin collects the treasury", async () => {
+
+    const treasuryA_before = await connection.getTokenAccountBalance(
+      accounts.treasuryMintA
+    );
+    const treasuryB_before = await connection.getTokenAccountBalance(
+      accounts.treasuryMintB
+    );
+
+    // Partial withdrawal of side A only, side B is left untouched
+    await program.methods
+      .adminGetsTreasury(new BN(1), new BN(0))
+      .accounts({ ...accounts, depositorAccountB: null })
+      .rpc();
+
+    const treasuryA_after = await connection.getTokenAccountBalance(
+      accounts.treasuryMintA
+    );
+    const treasuryB_after = await connection.getTokenAccountBalance(
+      accounts.treasuryMintB
+    );
+    expect(Number(treasuryA_before.value.amount) - Number(treasuryA_after.value.amount)).to.equal(
+      Math.min(1, Number(treasuryA_before.value.amount))
+    );
+    expect(treasuryB_after.value.amount).to.equal(treasuryB_before.value.amount);
+
+    // Requesting more than the balance sweeps everything
+    await program.methods
+      .adminGetsTreasury(new BN("18446744073709551615"), new BN("18446744073709551615"))
+      .accounts(accounts)
+      .rpc();
+
+    const treasuryB_swept = await connection.getTokenAccountBalance(
+      accounts.treasuryMintB
+    );
+    expect(treasuryB_swept.value.amount).to.equal("0");
+  });
+
+
+
+  it("Creator collects the creator share of the fees", async () => {
+
+    // The fee tier gave a fifth of the protocol fee to the creator
+    const pool = await program.account.simplePool.fetch(poolKey);
+    expect(pool.creator.toBase58()).to.equal(superUserKey.pubKey.toBase58());
+    expect(pool.creatorFeeShare).to.equal(2000);
+    expect(pool.creatorVaultA.toBase58()).to.equal(accounts.creatorVaultA.toBase58());
+
+    const creatorA_before = await connection.getTokenAccountBalance(accounts.creatorVaultA);
+    await program.methods
+      .simpleSwapExactIn(true, new BN(10 ** 7), new BN(0))
+      .accounts(accounts)
+      .rpc();
+    const creatorA_after = await connection.getTokenAccountBalance(accounts.creatorVaultA);
+    expect(Number(creatorA_after.value.amount)).to.be.greaterThan(Number(creatorA_before.value.amount));
+
+    // Only the creator can collect
+    const stranger = web3.Keypair.generate();
+    await expectRevert(
+      program.methods
+        .creatorCollectFees(new BN("18446744073709551615"), new BN("18446744073709551615"))
+        .accounts({ ...accounts, creator: stranger.publicKey })
+        .signers([stranger])
+        .rpc()
+    );
+
+    await program.methods
+      .creatorCollectFees(new BN("18446744073709551615"), new BN("18446744073709551615"))
+      .accounts({ ...accounts, creator: superUserKey.pubKey })
+      .rpc();
+    const creatorA_swept = await connection.getTokenAccountBalance(accounts.creatorVaultA);
+    const creatorB_swept = await connection.getTokenAccountBalance(accounts.creatorVaultB);
+    expect(creatorA_swept.value.amount).to.equal("0");
+    expect(creatorB_swept.value.amount).to.equal("0");
+  });
+
+
+
+  it("Swap with a deadline", async () => {
+
+    // Already in the past
+    await expectRevert(
+      program.methods
+        .simpleSwapExactInWithDeadline(true, new BN(10 ** 5), new BN(0), { timestamp: { 0: new BN(1) } })
+        .accounts(accounts)
+        .rpc()
+    );
+
+    const slot = await connection.getSlot();
+    await program.methods
+      .simpleSwapExactInWithDeadline(true, new BN(10 ** 5), new BN(0), { slot: { 0: new BN(slot + 1000) } })
+      .accounts(accounts)
+      .rpc();
+  });
+
+
+
+  it("Single sided deposit of token A", async () => {
+
+    const traderLPToken_before = await connection.getTokenAccountBalance(
+      accounts.depositorAccountLiquidity
+    );
+    const poolTokenAccountB_before = await connection.getTokenAccountBalance(
+      accounts.poolAccountB
+    );
+
+    // Unreachable LP amount
+    await expectRevert(
+      program.methods
+        .depositSingleSided(true, new BN(10 ** 6), new BN("18446744073709551615"))
+        .accounts({ ...accounts, depositorSource: values.holderAccountA })
+        .rpc()
+    );
+
+    await program.methods
+      .depositSingleSided(true, new BN(10 ** 6), new BN(1))
+      .accounts({ ...accounts, depositorSource: values.holderAccountA })
+      .rpc();
+
+    const traderLPToken_after = await connection.getTokenAccountBalance(
+      accounts.depositorAccountLiquidity
+    );
+    const poolTokenAccountB_after = await connection.getTokenAccountBalance(
+      accounts.poolAccountB
+    );
+
+    // The swapped half never leaves the pool
+    expect(poolTokenAccountB_after.value.amount).to.equal(poolTokenAccountB_before.value.amount);
+    expect(Number(traderLPToken_after.value.amount)).to.be.greaterThan(Number(traderLPToken_before.value.amount));
+  });
+
+
+
+  it("Single sided withdrawal to token B", async () => {
+
+    const traderTokenAccountB_before = await connection.getTokenAccountBalance(
+      accounts.depositorAccountB
+    );
+    const poolTokenAccountA_before = await connection.getTokenAccountBalance(
+      accounts.poolAccountA
+    );
+
+    await program.methods
+      .withdrawSingleSided(new BN(10 ** 5), false, new BN(1))
+      .accounts({ ...accounts, depositorDestination: values.holderAccountB })
+      .rpc();
+
+    const traderTokenAccountB_after = await connection.getTokenAccountBalance(
+      accounts.depositorAccountB
+    );
+    const poolTokenAccountA_after = await connection.getTokenAccountBalance(
+      accounts.poolAccountA
+    );
+
+    // Token A is sold back to the pool, only its treasury cut leaves the vault
+    expect(Number(traderTokenAccountB_after.value.amount)).to.be.greaterThan(Number(traderTokenAccountB_before.value.amount));
+    expect(Number(poolTokenAccountA_after.value.amount)).to.be.at.most(Number(poolTokenAccountA_before.value.amount));
+  });
+
+
+
+  it("Swap emits an event", async () => {
+
+    let event = null;
+    const listener = program.addEventListener("Swap", (swapEvent) => {
+      event = swapEvent;
+    });
+
+    await program.methods
+      .simpleSwapExactIn(true, new BN(10 ** 5), new BN(0))
+      .accounts(accounts)
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+
+    const poolTokenAccountA = await connection.getTokenAccountBalance(
+      accounts.poolAccountA
+    );
+    expect(event).to.not.equal(null);
+    expect(event.pool.toBase58()).to.equal(poolKey.toBase58());
+    expect(event.input.toNumber()).to.equal(10 ** 5);
+    expect(event.reserveA.toString()).to.equal(poolTokenAccountA.value.amount);
+
+    // Exact out swaps report the output the trader asked for
+    event = null;
+    const exactOutListener = program.addEventListener("Swap", (swapEvent) => {
+      event = swapEvent;
+    });
+    await program.methods
+      .simpleSwapExactOut(true, new BN(10 ** 4), new BN(10 ** 6))
+      .accounts(accounts)
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(exactOutListener);
+    expect(event.output.toNumber()).to.equal(10 ** 4);
+  });
+
+
+
+  it("Swaps accumulate prices for TWAPs", async () => {
+
+    const poolBefore = await program.account.simplePool.fetch(poolKey);
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+
+    await program.methods
+      .simpleSwapExactIn(false, new BN(10 ** 5), new BN(0))
+      .accounts(accounts)
+      .rpc();
+
+    const poolAfter = await program.account.simplePool.fetch(poolKey);
+    const observations = await program.account.poolObservations.fetch(observationsKey);
+    const latest = observations.observations[observations.index];
+
+    expect(poolAfter.lastUpdateTimestamp.toNumber()).to.be.greaterThan(poolBefore.lastUpdateTimestamp.toNumber());
+    expect(poolAfter.priceACumulative.gt(poolBefore.priceACumulative)).to.equal(true);
+    expect(latest.timestamp.toNumber()).to.equal(poolAfter.lastUpdateTimestamp.toNumber());
+    expect(latest.priceACumulative.toString()).to.equal(poolAfter.priceACumulative.toString());
+  });
+
+
+
+  it("Pool creation rejects unsupported Token-2022 extensions", async () => {
+
+    const createToken2022Mint = async (extension: ExtensionType, initialize: (mint: PublicKey) => web3.TransactionInstruction) => {
+      const mintKeypair = web3.Keypair.generate();
+      const mintLen = getMintLen([extension]);
+      const lamports = await connection.getMinimumBalanceForRentExemption(mintLen);
+      await web3.sendAndConfirmTransaction(
+        connection,
+        new web3.Transaction().add(
+          web3.SystemProgram.createAccount({
+            fromPubkey: superUser.publicKey,
+            newAccountPubkey: mintKeypair.publicKey,
+            space: mintLen,
+            lamports,
+            programId: TOKEN_2022_PROGRAM_ID,
+          }),
+          initialize(mintKeypair.publicKey),
+          createInitializeMintInstruction(mintKeypair.publicKey, 6, superUser.publicKey, null, TOKEN_2022_PROGRAM_ID),
+        ),
+        [superUser, mintKeypair]
+      );
+      return mintKeypair.publicKey;
+    };
+
+    const poolAccountsWith = (mint: PublicKey) => {
+      const legacyFirst = new BN(values.mintAKeypair.publicKey.toBytes()).lt(new BN(mint.toBytes()));
+      return {
+        ...accounts,
+        ...poolAccounts({
+          programId: program.programId,
+          mintA: legacyFirst ? values.mintAKeypair.publicKey : mint,
+          mintB: legacyFirst ? mint : values.mintAKeypair.publicKey,
+          admin: superUserKey.pubKey,
+          creator: superUserKey.pubKey,
+          lpFeeBuffer,
+          tokenProgramA: legacyFirst ? TOKEN_PROGRAM_ID : TOKEN_2022_PROGRAM_ID,
+          tokenProgramB: legacyFirst ? TOKEN_2022_PROGRAM_ID : TOKEN_PROGRAM_ID,
+        }),
+      };
+    };
+
+    // A non transferable mint would lock the vault forever
+    const nonTransferableMint = await createToken2022Mint(ExtensionType.NonTransferable, (mint) =>
+      createInitializeNonTransferableMintInstruction(mint, TOKEN_2022_PROGRAM_ID)
+    );
+    await expectRevert(
+      program.methods
+        .createPool(10, 0, 0, 0, 0, 0)
+        .accounts(poolAccountsWith(nonTransferableMint))
+        .rpc()
+    );
+
+    // A metadata pointer only describes the mint
+    const metadataPointerMint = await createToken2022Mint(ExtensionType.MetadataPointer, (mint) =>
+      createInitializeMetadataPointerInstruction(mint, superUser.publicKey, mint, TOKEN_2022_PROGRAM_ID)
+    );
+    const metadataPointerAccounts = poolAccountsWith(metadataPointerMint);
+    await program.methods
+      .createPool(10, 0, 0, 0, 0, 0)
+      .accounts(metadataPointerAccounts)
+      .rpc();
+    const pool = await program.account.simplePool.fetch(metadataPointerAccounts.pool);
+    expect(pool.lpFee).to.equal(10);
+  });
+
+
+
+  it("Native SOL pool wraps and unwraps lamports", async () => {
+
+    const nativeFirst = new BN(NATIVE_MINT.toBytes()).lt(new BN(values.mintBKeypair.publicKey.toBytes()));
+    const [mintA, mintB] = nativeFirst
+      ? [NATIVE_MINT, values.mintBKeypair.publicKey]
+      : [values.mintBKeypair.publicKey, NATIVE_MINT];
+    const nativeAccounts = {
+      ...accounts,
+      ...poolAccounts({ programId: program.programId, mintA, mintB, admin: superUserKey.pubKey, creator: superUserKey.pubKey, lpFeeBuffer }),
+      depositorAccountA: getAssociatedTokenAddressSync(mintA, superUserKey.pubKey, true),
+      depositorAccountB: getAssociatedTokenAddressSync(mintB, superUserKey.pubKey, true),
+      traderAccountA: getAssociatedTokenAddressSync(mintA, superUserKey.pubKey, true),
+      traderAccountB: getAssociatedTokenAddressSync(mintB, superUserKey.pubKey, true),
+    };
+
+    await program.methods
+      .createPool(10, 0, 0, 0, 0, 0)
+      .accounts(nativeAccounts)
+      .rpc();
+
+    // One SOL against one token, the wSOL account is created, funded and closed
+    await program.methods
+      .depositLiquidityNative(new BN(10 ** 9), new BN(10 ** 6), new BN(0), true)
+      .accounts(nativeAccounts)
+      .rpc();
+    const wsolAccount = nativeFirst ? nativeAccounts.traderAccountA : nativeAccounts.traderAccountB;
+    expect(await connection.getAccountInfo(wsolAccount)).to.equal(null);
+
+    // Selling the token for SOL, received as lamports
+    const lamportsBefore = await connection.getBalance(superUserKey.pubKey);
+    await program.methods
+      .simpleSwapExactInNative(!nativeFirst, new BN(10 ** 5), new BN(0), true)
+      .accounts(nativeAccounts)
+      .rpc();
+    const lamportsAfter = await connection.getBalance(superUserKey.pubKey);
+
+    expect(lamportsAfter).to.be.greaterThan(lamportsBefore);
+    expect(await connection.getAccountInfo(wsolAccount)).to.equal(null);
+  });
+
+
+
+  it("Flash loan repaid with fees in the same transaction", async () => {
+
+    const flashAccounts = {
+      ...accounts,
+      borrowerAccount: accounts.depositorAccountA,
+      repayerAccount: accounts.depositorAccountA,
+      borrower: superUserKey.pubKey,
+      instructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+    };
+    const borrow = await program.methods
+      .flashBorrow(true, new BN(10 ** 6))
+      .accounts(flashAccounts)
+      .instruction();
+    const repay = await program.methods
+      .flashRepay()
+      .accounts(flashAccounts)
+      .instruction();
+
+    // Without the repayment in the transaction nothing is lent
+    await expectRevert(
+      web3.sendAndConfirmTransaction(connection, new web3.Transaction().add(borrow), [superUser])
+    );
+
+    // Nor is there anything to repay without a loan outstanding
+    await expectRevert(
+      web3.sendAndConfirmTransaction(connection, new web3.Transaction().add(repay), [superUser])
+    );
+
+    const poolTokenAccountA_before = await connection.getTokenAccountBalance(
+      accounts.poolAccountA
+    );
+    await web3.sendAndConfirmTransaction(connection, new web3.Transaction().add(borrow, repay), [superUser]);
+    const poolTokenAccountA_after = await connection.getTokenAccountBalance(
+      accounts.poolAccountA
+    );
+
+    const pool = await program.account.simplePool.fetch(poolKey);
+    expect(pool.locked).to.equal(false);
+    expect(Number(poolTokenAccountA_after.value.amount)).to.be.greaterThan(Number(poolTokenAccountA_before.value.amount));
+  });
+
+
+
+  it("Flash swap reverts unless the receiver pays the pool", async () => {
+
+    const flashSwapAccounts = {
+      ...accounts,
+      traderDestination: accounts.depositorAccountB,
+      payer: superUserKey.pubKey,
+    };
+
+    // The program cannot call itself back
+    await expectRevert(
+      program.methods
+        .simpleFlashSwap(true, new BN(10 ** 5), Buffer.from([]))
+        .accounts({ ...flashSwapAccounts, receiverProgram: program.programId })
+        .signers([superUser])
+        .rpc()
+    );
+
+    // A receiver that never pays the input back leaves the invariant broken
+    await expectRevert(
+      program.methods
+        .simpleFlashSwap(true, new BN(10 ** 5), Buffer.from([]))
+        .accounts({ ...flashSwapAccounts, receiverProgram: TOKEN_PROGRAM_ID })
+        .signers([superUser])
+        .rpc()
+    );
+
+    const pool = await program.account.simplePool.fetch(poolKey);
+    expect(pool.locked).to.equal(false);
+  });
+
+
+
+  it("Stable pool swaps pegged assets close to one to one", async () => {
+
+    const { feeBuffer, feeTier } = await createFeeTier(11);
+    const { mintA, mintB } = accounts;
+    const stableAccounts = {
+      ...accounts,
+      ...poolAccounts({ programId: program.programId, mintA, mintB, admin: superUserKey.pubKey, creator: superUserKey.pubKey, lpFeeBuffer: feeBuffer }),
+      feeTier,
+    };
+    const stablePoolKey = stableAccounts.pool;
+
+    // The amplification coefficient is bounded
+    await expectRevert(
+      program.methods
+        .createStablePool(11, new BN(0), 0, 0, 0, 0, 0)
+        .accounts(stableAccounts)
+        .rpc()
+    );
+    await program.methods
+      .createStablePool(11, new BN(100), 0, 0, 0, 0, 0)
+      .accounts(stableAccounts)
+      .rpc();
+
+    await program.methods
+      .depositLiquidity(new BN(10 ** 8), new BN(10 ** 8), new BN(0))
+      .accounts(stableAccounts)
+      .rpc();
+
+    // Imbalanced deposits are accepted and still mint LP tokens
+    const lpBefore = await connection.getTokenAccountBalance(stableAccounts.depositorAccountLiquidity);
+    await program.methods
+      .depositLiquidity(new BN(10 ** 6), new BN(0), new BN(1))
+      .accounts(stableAccounts)
+      .rpc();
+    const lpAfter = await connection.getTokenAccountBalance(stableAccounts.depositorAccountLiquidity);
+    expect(Number(lpAfter.value.amount)).to.be.greaterThan(Number(lpBefore.value.amount));
+
+    const traderB_before = await connection.getTokenAccountBalance(accounts.traderAccountB);
+    await program.methods
+      .simpleSwapExactIn(true, new BN(10 ** 6), new BN(0))
+      .accounts(stableAccounts)
+      .rpc();
+    const traderB_after = await connection.getTokenAccountBalance(accounts.traderAccountB);
+
+    // A constant product pool of this depth would give about 1% less
+    const output = Number(traderB_after.value.amount) - Number(traderB_before.value.amount);
+    expect(output).to.be.greaterThan(995000);
+    expect(output).to.be.lessThan(10 ** 6);
+
+    // Only the curve agnostic instructions are open to stable pools
+    await expectRevert(
+      program.methods
+        .simpleSwapExactOut(true, new BN(10 ** 5), new BN(10 ** 6))
+        .accounts(stableAccounts)
+        .rpc()
+    );
+  });
+
+
+
+  it("Weighted 80/20 pool prices token A on its weight", async () => {
+
+    const { feeBuffer, feeTier } = await createFeeTier(12);
+    const { mintA, mintB } = accounts;
+    const weightedAccounts = {
+      ...accounts,
+      ...poolAccounts({ programId: program.programId, mintA, mintB, admin: superUserKey.pubKey, creator: superUserKey.pubKey, lpFeeBuffer: feeBuffer }),
+      feeTier,
+    };
+    const weightedPoolKey = weightedAccounts.pool;
+
+    // Each weight is at least 2%
+    await expectRevert(
+      program.methods
+        .createWeightedPool(12, new BN(10000), 0, 0, 0, 0, 0)
+        .accounts(weightedAccounts)
+        .rpc()
+    );
+    await program.methods
+      .createWeightedPool(12, new BN(800000), 0, 0, 0, 0, 0)
+      .accounts(weightedAccounts)
+      .rpc();
+    const pool = await program.account.simplePool.fetch(weightedPoolKey);
+    expect(pool.weightB.toNumber()).to.equal(200000);
+
+    await program.methods
+      .depositLiquidity(new BN(10 ** 8), new BN(10 ** 8), new BN(0))
+      .accounts(weightedAccounts)
+      .rpc();
+
+    const traderB_before = await connection.getTokenAccountBalance(accounts.traderAccountB);
+    await program.methods
+      .simpleSwapExactIn(true, new BN(10 ** 6), new BN(0))
+      .accounts(weightedAccounts)
+      .rpc();
+    const traderB_after = await connection.getTokenAccountBalance(accounts.traderAccountB);
+
+    // With equal reserves one A is worth four B
+    const output = Number(traderB_after.value.amount) - Number(traderB_before.value.amount);
+    expect(output).to.be.greaterThan(3800000);
+    expect(output).to.be.lessThan(4 * 10 ** 6);
+  });
+
+
+
+  it("Concentrated position earns fees on swaps crossing its range", async () => {
+
+    const { feeBuffer, feeTier } = await createFeeTier(13);
+    const { mintA, mintB } = accounts;
+    const i32Buffer = (value: number) => {
+      const buffer = Buffer.alloc(4);
+      buffer.writeInt32LE(value);
+      return buffer;
+    };
+    const concentratedAccounts = {
+      ...accounts,
+      ...poolAccounts({ programId: program.programId, mintA, mintB, admin: superUserKey.pubKey, creator: superUserKey.pubKey, lpFeeBuffer: feeBuffer }),
+      feeTier,
+    };
+    const concentratedPoolKey = concentratedAccounts.pool;
+    const concentratedLpKey = concentratedAccounts.mintLiquidity;
+
+    // Price of one, each tick array spans 64 * 32 ticks
+    const tickSpacing = 64;
+    await program.methods
+      .createConcentratedPool(13, tickSpacing, new BN(1).shln(64), 0, 0, 0, 0, 0)
+      .accounts(concentratedAccounts)
+      .rpc();
+
+    const tickArrayKey = (start: number) => pda([Buffer.from("tick_array"), concentratedPoolKey.toBuffer(), i32Buffer(start)], program.programId);
+    for (const start of [-2048, 0]) {
+      await program.methods
+        .initializeTickArray(start)
+        .accounts({ pool: concentratedPoolKey, tickArray: tickArrayKey(start), payer: superUserKey.pubKey })
+        .rpc();
+    }
+
+    // Ticks off the spacing are rejected
+    const positionKey = (lower: number, upper: number) =>
+      pda([Buffer.from("position"), concentratedPoolKey.toBuffer(), superUserKey.pubKey.toBuffer(), i32Buffer(lower), i32Buffer(upper)], program.programId);
+    await expectRevert(
+      program.methods
+        .openPosition(-100, 128)
+        .accounts({ pool: concentratedPoolKey, position: positionKey(-100, 128), owner: superUserKey.pubKey, payer: superUserKey.pubKey })
+        .rpc()
+    );
+    await program.methods
+      .openPosition(-128, 128)
+      .accounts({ pool: concentratedPoolKey, position: positionKey(-128, 128), owner: superUserKey.pubKey, payer: superUserKey.pubKey })
+      .rpc();
+
+    const positionAccounts = {
+      ...concentratedAccounts,
+      position: positionKey(-128, 128),
+      tickArrayLower: tickArrayKey(-2048),
+      tickArrayUpper: tickArrayKey(0),
+      ownerAccountA: accounts.traderAccountA,
+      ownerAccountB: accounts.traderAccountB,
+      owner: superUserKey.pubKey,
+    };
+    const liquidity = new BN(10 ** 10);
+    await program.methods
+      .increaseLiquidity(liquidity, new BN(10 ** 8), new BN(10 ** 8))
+      .accounts(positionAccounts)
+      .rpc();
+
+    // The generic LP instructions do not apply to concentrated pools
+    await expectRevert(
+      program.methods
+        .depositLiquidity(new BN(10 ** 6), new BN(10 ** 6), new BN(0))
+        .accounts({ ...concentratedAccounts, depositorAccountLiquidity: getAssociatedTokenAddressSync(concentratedLpKey, superUserKey.pubKey, true) })
+        .rpc()
+    );
+
+    const traderB_before = await connection.getTokenAccountBalance(accounts.traderAccountB);
+    await program.methods
+      .concentratedSwap(true, new BN(10 ** 6), new BN(0))
+      .accounts(concentratedAccounts)
+      .remainingAccounts([
+        { pubkey: tickArrayKey(0), isSigner: false, isWritable: true },
+        { pubkey: tickArrayKey(-2048), isSigner: false, isWritable: true },
+      ])
+      .rpc();
+    const traderB_after = await connection.getTokenAccountBalance(accounts.traderAccountB);
+
+    // Close to one to one inside the range, the price moves down
+    const output = Number(traderB_after.value.amount) - Number(traderB_before.value.amount);
+    expect(output).to.be.greaterThan(990000);
+    expect(output).to.be.lessThan(10 ** 6);
+    const pool = await program.account.simplePool.fetch(concentratedPoolKey);
+    expect(pool.tickCurrent).to.be.lessThan(0);
+
+    // The position earned the LP fee on token A
+    await program.methods.collectPositionFees().accounts(positionAccounts).rpc();
+    const position = await program.account.position.fetch(positionAccounts.position);
+    expect(position.feesOwedA.toNumber()).to.equal(0);
+
+    // Positions holding liquidity cannot be closed
+    await expectRevert(
+      program.methods
+        .closePosition()
+        .accounts({ position: positionAccounts.position, owner: superUserKey.pubKey })
+        .rpc()
+    );
+    await program.methods
+      .decreaseLiquidity(liquidity, new BN(0), new BN(0))
+      .accounts(positionAccounts)
+      .rpc();
+    await program.methods
+      .closePosition()
+      .accounts({ position: positionAccounts.position, owner: superUserKey.pubKey })
+      .rpc();
+  });
+
+
+
+  it("Dynamic fee grows with the volatility of the pool", async () => {
+
+    const { feeBuffer, feeTier } = await createFeeTier(14);
+    const { mintA, mintB } = accounts;
+    const dynamicAccounts = {
+      ...accounts,
+      ...poolAccounts({ programId: program.programId, mintA, mintB, admin: superUserKey.pubKey, creator: superUserKey.pubKey, lpFeeBuffer: feeBuffer }),
+      feeTier,
+    };
+    const dynamicPoolKey = dynamicAccounts.pool;
+
+    await program.methods
+      .createPool(14, 0, 0, 0, 0, 0)
+      .accounts(dynamicAccounts)
+      .rpc();
+    await program.methods
+      .depositLiquidity(new BN(10 ** 8), new BN(10 ** 8), new BN(0))
+      .accounts(dynamicAccounts)
+      .rpc();
+
+    // The max fee cannot be below the base one
+    await expectRevert(
+      program.methods
+        .setPoolDynamicFee(true, 5)
+        .accounts({ pool: dynamicPoolKey, amm: accounts.amm, admin: superUserKey.pubKey })
+        .rpc()
+    );
+    await program.methods
+      .setPoolDynamicFee(true, 1000)
+      .accounts({ pool: dynamicPoolKey, amm: accounts.amm, admin: superUserKey.pubKey })
+      .rpc();
+
+    // The first swap sets the reference price, the next ones accumulate the moves
+    for (let i = 0; i < 3; i++) {
+      await program.methods
+        .simpleSwapExactIn(true, new BN(10 ** 6), new BN(0))
+        .accounts(dynamicAccounts)
+        .rpc();
+    }
+
+    const pool = await program.account.simplePool.fetch(dynamicPoolKey);
+    expect(pool.dynamicFee).to.equal(true);
+    expect(pool.volatilityAccumulator.toNumber()).to.be.greaterThan(0);
+
+    // The internal swap of a single sided deposit moves the price as well
+    await program.methods
+      .depositSingleSided(true, new BN(10 ** 6), new BN(1))
+      .accounts({ ...dynamicAccounts, depositorSource: values.holderAccountA })
+      .rpc();
+    const poolAfterDeposit = await program.account.simplePool.fetch(dynamicPoolKey);
+    expect(poolAfterDeposit.volatilityAccumulator.toNumber()).to.be.greaterThan(pool.volatilityAccumulator.toNumber());
+  });
+
+
+
+  it("Withdraw only mode blocks swaps and deposits", async () => {
+    const PAUSE_SWAP = 1;
+    const PAUSE_DEPOSIT = 2;
+
+    await program.methods
+      .setPoolPausedFlags(PAUSE_SWAP | PAUSE_DEPOSIT)
+      .accounts({ pool: poolKey, admin: superUserKey.pubKey })
+      .rpc();
+
+    await expectRevert(
+      program.methods
+        .simpleSwapExactIn(true, new BN(10 ** 5), new BN(0))
+        .accounts(accounts)
+        .rpc()
+    );
+    await expectRevert(
+      program.methods
+        .depositLiquidity(values.depositAmountA, values.depositAmountA, new BN(0))
+        .accounts(accounts)
+        .rpc()
+    );
+
+    // Unknown bits are rejected
+    await expectRevert(
+      program.methods
+        .setPoolPausedFlags(8)
+        .accounts({ pool: poolKey, admin: superUserKey.pubKey })
+        .rpc()
+    );
+
+    // The next test withdraws while trading is paused, then trading resumes
+  });
+
+
+
   it("First Withdraw everything", async () => {
 
 
@@ -500,6 +1626,11 @@ describe("ftrx_swap", () => {
 
   it("Deposit equal amounts third deposit", async () => {
 
+    await program.methods
+      .setPoolPausedFlags(0)
+      .accounts({ pool: poolKey, admin: superUserKey.pubKey })
+      .rpc();
+
     const traderTokenAccountA_before = await connection.getTokenAccountBalance(
       accounts.depositorAccountA
     );
@@ -625,4 +1756,62 @@ describe("ftrx_swap", () => {
   
 
 
+  it("Two-step admin handover", async () => {
+    const newAdmin = web3.Keypair.generate();
+
+    // A cancelled proposal cannot be accepted
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({ pool: poolKey, admin: superUserKey.pubKey })
+      .rpc();
+    let cancelled = null;
+    const listener = program.addEventListener("AdminProposalCancelled", (cancelEvent) => {
+      cancelled = cancelEvent;
+    });
+    await program.methods
+      .cancelAdminProposal()
+      .accounts({ pool: poolKey, admin: superUserKey.pubKey })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+    expect(cancelled).to.not.equal(null);
+    expect(cancelled.pendingAdmin.toBase58()).to.equal(newAdmin.publicKey.toBase58());
+
+    await expectRevert(
+      program.methods
+        .acceptAdmin()
+        .accounts({ pool: poolKey, pendingAdmin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc()
+    );
+
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({ pool: poolKey, admin: superUserKey.pubKey })
+      .rpc();
+    await program.methods
+      .acceptAdmin()
+      .accounts({ pool: poolKey, pendingAdmin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+
+    const pool = await program.account.simplePool.fetch(poolKey);
+    expect(pool.admin.toBase58()).to.equal(newAdmin.publicKey.toBase58());
+    expect(pool.adminSeed.toBase58()).to.equal(superUserKey.pubKey.toBase58());
+
+    // The treasury PDAs are still derived from the original admin and reachable by the new one
+    await program.methods
+      .adminGetsTreasury(new BN(0), new BN(0))
+      .accounts({ ...accounts, admin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+    await expectRevert(
+      program.methods
+        .adminGetsTreasury(new BN(0), new BN(0))
+        .accounts(accounts)
+        .rpc()
+    );
+  });
+
+
 });
@@ -1,5 +1,6 @@
 import * as anchor from "@project-serum/anchor";
 import {
+  TOKEN_PROGRAM_ID,
   createMint,
   getAssociatedTokenAddressSync,
   getOrCreateAssociatedTokenAccount,
@@ -133,7 +134,7 @@ export function createValues(defaults?: TestValuesDefaults): TestValues {
   const id = defaults?.id || Keypair.generate().publicKey;
   const admin = Keypair.generate();
   const ammKey = PublicKey.findProgramAddressSync(
-    [id.toBuffer()],
+    [Buffer.from("amm")],
     anchor.workspace.FtrxSwap.programId
   )[0];
 
@@ -217,4 +218,59 @@ export function createValues(defaults?: TestValuesDefaults): TestValues {
 
 
   
-}
\ No newline at end of file
+}
+
+// Metaplex token metadata program, cloned into the local validator
+export const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+export const pda = (seeds: Buffer[], programId: PublicKey) =>
+  PublicKey.findProgramAddressSync(seeds, programId)[0];
+
+export const metadataKey = (mint: PublicKey) =>
+  pda([Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()], TOKEN_METADATA_PROGRAM_ID);
+
+// Accounts of the pool of mintA and mintB derived with admin and the LP fee,
+// to be spread over the accounts of the tests. Several pools can share the
+// same mints as long as their admins differ
+export function poolAccounts({
+  programId,
+  mintA,
+  mintB,
+  admin,
+  creator,
+  lpFeeBuffer,
+  tokenProgramA = TOKEN_PROGRAM_ID,
+  tokenProgramB = TOKEN_PROGRAM_ID,
+}: {
+  programId: PublicKey;
+  mintA: PublicKey;
+  mintB: PublicKey;
+  admin: PublicKey;
+  creator: PublicKey;
+  lpFeeBuffer: Buffer;
+  tokenProgramA?: PublicKey;
+  tokenProgramB?: PublicKey;
+}) {
+  const pool = pda([mintA.toBuffer(), mintB.toBuffer(), admin.toBuffer(), lpFeeBuffer], programId);
+  const mintLiquidity = pda([mintA.toBuffer(), mintB.toBuffer(), admin.toBuffer(), lpFeeBuffer, Buffer.from("liquidity")], programId);
+  return {
+    admin,
+    pool,
+    observations: pda([Buffer.from("observation"), pool.toBuffer()], programId),
+    mintLiquidity,
+    lpMetadata: metadataKey(mintLiquidity),
+    metadataA: metadataKey(mintA),
+    metadataB: metadataKey(mintB),
+    mintA,
+    mintB,
+    poolAccountA: pda([mintA.toBuffer(), pool.toBuffer()], programId),
+    poolAccountB: pda([mintB.toBuffer(), pool.toBuffer()], programId),
+    treasuryMintA: pda([mintA.toBuffer(), pool.toBuffer(), Buffer.from("treasury"), admin.toBuffer()], programId),
+    treasuryMintB: pda([mintB.toBuffer(), pool.toBuffer(), Buffer.from("treasury"), admin.toBuffer()], programId),
+    creatorVaultA: pda([mintA.toBuffer(), pool.toBuffer(), Buffer.from("creator"), creator.toBuffer()], programId),
+    creatorVaultB: pda([mintB.toBuffer(), pool.toBuffer(), Buffer.from("creator"), creator.toBuffer()], programId),
+    depositorAccountLiquidity: getAssociatedTokenAddressSync(mintLiquidity, creator, true),
+    tokenProgramA,
+    tokenProgramB,
+  };
+}